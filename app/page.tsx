@@ -108,6 +108,34 @@ export default function Home() {
     initLogging();
   }, []);
 
+  // Lets the backend tell a live page from a crashed renderer; the first beat returns the interval to keep
+  useEffect(() => {
+    let timer: ReturnType<typeof setInterval> | undefined;
+    let cancelled = false;
+
+    const startHeartbeat = async () => {
+      try {
+        const intervalSecs = await invoke<number>('heartbeat');
+        if (cancelled) {
+          return;
+        }
+        timer = setInterval(() => {
+          invoke('heartbeat').catch((e) => console.error('Heartbeat failed:', e));
+        }, intervalSecs * 1000);
+      } catch (e) {
+        console.error('Failed to start the heartbeat:', e);
+      }
+    };
+
+    startHeartbeat();
+    return () => {
+      cancelled = true;
+      if (timer) {
+        clearInterval(timer);
+      }
+    };
+  }, []);
+
   useEffect(() => {
     const handleScroll = async () => {
       const logLevel = await useLogLevel();
@@ -189,9 +217,7 @@ export default function Home() {
 
     try {
       // Complete the authentication
-      const userProfile = await invoke<any>("complete_auth", {
-        requestState
-      });
+      const userProfile = await invoke<any>("complete_auth");
       
       // Update authentication state
       setIsAuthenticated(true);
@@ -0,0 +1,35 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use oci_auth_tauri::bench;
+use oci_auth_tauri::demo::DemoState;
+use oci_auth_tauri::redact;
+
+fn login_steps(c: &mut Criterion) {
+    let demo = DemoState::new(true);
+    let response = bench::initiate(&demo);
+    let serialized = bench::serialize_response(&response);
+    let profile = bench::complete(&demo, &bench::initiate(&demo).request_state);
+
+    let mut group = c.benchmark_group("auth_pipeline");
+    group.bench_function("initiate", |b| b.iter(|| bench::initiate(black_box(&demo))));
+    group.bench_function("serialize", |b| b.iter(|| bench::serialize_response(black_box(&response))));
+    group.bench_function("redact", |b| {
+        b.iter(|| {
+            let mut value: serde_json::Value = serde_json::from_str(black_box(&serialized)).unwrap();
+            redact::redact(&mut value);
+            value
+        })
+    });
+    group.bench_function("emit", |b| b.iter(|| bench::event_payload(black_box(&profile))));
+    group.finish();
+
+    // Full round trip, including the pending request bookkeeping in the mock provider
+    c.bench_function("login", |b| {
+        b.iter(|| {
+            let response = bench::initiate(&demo);
+            bench::complete(&demo, &response.request_state)
+        })
+    });
+}
+
+criterion_group!(benches, login_steps);
+criterion_main!(benches);
@@ -1,3 +1,44 @@
+use std::path::PathBuf;
+
 fn main() {
+  branding();
   tauri_build::build()
 }
+
+// Compiles the values from branding.toml (or $BRANDING_FILE) into the binary as BRANDING_* env vars
+fn branding() {
+  println!("cargo:rustc-check-cfg=cfg(branding_icon)");
+  println!("cargo:rerun-if-env-changed=BRANDING_FILE");
+  let path = std::env::var("BRANDING_FILE")
+    .map(PathBuf::from)
+    .unwrap_or_else(|_| PathBuf::from("branding.toml"));
+  println!("cargo:rerun-if-changed={}", path.display());
+
+  let Ok(contents) = std::fs::read_to_string(&path) else {
+    return;
+  };
+  let branding: toml::Table = contents
+    .parse()
+    .unwrap_or_else(|e| panic!("invalid branding file {}: {}", path.display(), e));
+
+  for (key, var) in [
+    ("app_name", "BRANDING_APP_NAME"),
+    ("about", "BRANDING_ABOUT"),
+    ("idcs_url", "BRANDING_IDCS_URL"),
+  ] {
+    if let Some(value) = branding.get(key).and_then(|value| value.as_str()) {
+      println!("cargo:rustc-env={}={}", var, value.replace('\n', "\\n"));
+    }
+  }
+
+  if let Some(icon) = branding.get("icon").and_then(|value| value.as_str()) {
+    // Relative to the branding file
+    let icon = path.parent().map(|dir| dir.join(icon)).unwrap_or_else(|| PathBuf::from(icon));
+    let icon = icon
+      .canonicalize()
+      .unwrap_or_else(|e| panic!("branding icon {}: {}", icon.display(), e));
+    println!("cargo:rerun-if-changed={}", icon.display());
+    println!("cargo:rustc-env=BRANDING_ICON={}", icon.display());
+    println!("cargo:rustc-cfg=branding_icon");
+  }
+}
@@ -0,0 +1,107 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, parse_quote, FnArg, Ident, ItemFn, Pat, ReturnType, Signature, Token, Type};
+
+// Registers a Tauri command that runs through `oci_auth_tauri::middleware`, which times and logs every call and
+// checks the gates named in the attribute before the body runs:
+//
+//     #[command(session, unlocked)]
+//     pub async fn revoke_my_session(app_handle: AppHandle, id: String) -> Result<(), CommandError> { ... }
+//
+// `session` needs a signed-in user, `unlocked` is refused in kiosk mode and `configured` needs client credentials.
+// A command with gates but no `AppHandle` argument gets one added, which Tauri fills in and the frontend never
+// sees. Commands return `Result<_, CommandError>`, the one error shape the frontend handles.
+#[proc_macro_attribute]
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let gates = match Punctuated::<Ident, Token![,]>::parse_terminated.parse(attr) {
+        Ok(gates) => gates,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let function = parse_macro_input!(item as ItemFn);
+    expand(&gates, function).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+fn expand(gates: &Punctuated<Ident, Token![,]>, mut function: ItemFn) -> syn::Result<TokenStream2> {
+    let gates = gates
+        .iter()
+        .map(|gate| match gate.to_string().as_str() {
+            "session" => Ok(format_ident!("SignedIn")),
+            "unlocked" => Ok(format_ident!("Unlocked")),
+            "configured" => Ok(format_ident!("Configured")),
+            _ => Err(syn::Error::new(gate.span(), "expected `session`, `unlocked` or `configured`")),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    if !returns_result(&function.sig) {
+        return Err(syn::Error::new_spanned(&function.sig, "a #[command] returns Result<_, CommandError>"));
+    }
+
+    let name = function.sig.ident.to_string();
+    let checked = if gates.is_empty() {
+        quote!(::core::result::Result::Ok(()))
+    } else {
+        let app_handle = match app_handle_arg(&function.sig) {
+            Some(app_handle) => app_handle,
+            None => {
+                let app_handle = format_ident!("__command_app_handle");
+                function.sig.inputs.insert(0, parse_quote!(#app_handle: ::tauri::AppHandle));
+                // The frontend still passes only the arguments it did before
+                function.attrs.push(parse_quote!(#[allow(clippy::too_many_arguments)]));
+                app_handle
+            }
+        };
+        quote!(::oci_auth_tauri::middleware::check(
+            &#app_handle,
+            &[#(::oci_auth_tauri::middleware::Gate::#gates),*],
+        ))
+    };
+
+    // The body becomes a closure or async block so its `return`s and `?`s still end the command, and the
+    // middleware sees the result either way
+    let body = &function.block;
+    let block = if function.sig.asyncness.is_some() {
+        quote!({
+            let __checked = #checked;
+            ::oci_auth_tauri::middleware::run_async(#name, __checked, async move #body).await
+        })
+    } else {
+        quote!({
+            let __checked = #checked;
+            ::oci_auth_tauri::middleware::run(#name, __checked, move || #body)
+        })
+    };
+    *function.block = syn::parse2(block)?;
+    Ok(quote! {
+        #[::tauri::command]
+        #function
+    })
+}
+
+fn returns_result(sig: &Signature) -> bool {
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return false;
+    };
+    let Type::Path(path) = ty.as_ref() else {
+        return false;
+    };
+    path.path.segments.last().is_some_and(|segment| segment.ident == "Result")
+}
+
+// The command's own `AppHandle` argument, if it takes one
+fn app_handle_arg(sig: &Signature) -> Option<Ident> {
+    sig.inputs.iter().find_map(|arg| {
+        let FnArg::Typed(arg) = arg else {
+            return None;
+        };
+        let (Pat::Ident(pat), Type::Path(ty)) = (arg.pat.as_ref(), arg.ty.as_ref()) else {
+            return None;
+        };
+        ty.path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "AppHandle")
+            .then(|| pat.ident.clone())
+    })
+}
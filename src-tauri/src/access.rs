@@ -0,0 +1,152 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::Store;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::schema::{self, Migration};
+
+const ACCESS_STORE_FILE: &str = "access-snapshots.json";
+// Format 1: `snapshots`, oldest first
+const MIGRATIONS: &[Migration] = &[schema::add_version];
+const MAX_SNAPSHOTS: usize = 50;
+// Unchanged entitlements are recorded at most once a day
+const SNAPSHOT_INTERVAL_HOURS: i64 = 24;
+const IDCS_USER_EXTENSION: &str = "urn:ietf:params:scim:schemas:oracle:idcs:extension:user:User";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessSnapshot {
+    pub id: String,
+    pub user_name: String,
+    pub taken_at: DateTime<Utc>,
+    pub groups: Vec<String>,
+    pub app_roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessDiff {
+    pub from: String,
+    pub to: String,
+    pub groups_added: Vec<String>,
+    pub groups_removed: Vec<String>,
+    pub app_roles_added: Vec<String>,
+    pub app_roles_removed: Vec<String>,
+}
+
+#[command]
+pub fn list_access_snapshots(app_handle: AppHandle) -> Result<Vec<AccessSnapshot>, CommandError> {
+    Ok(load_snapshots(&app_handle)?)
+}
+
+#[command]
+pub fn diff_access(app_handle: AppHandle, from: String, to: String) -> Result<AccessDiff, CommandError> {
+    let snapshots = load_snapshots(&app_handle)?;
+    let find = |id: &str| {
+        snapshots
+            .iter()
+            .find(|snapshot| snapshot.id == id)
+            .ok_or_else(|| format!("Access snapshot not found: {}", id))
+    };
+    let (before, after) = (find(&from)?, find(&to)?);
+    let (groups_added, groups_removed) = changes(&before.groups, &after.groups);
+    let (app_roles_added, app_roles_removed) = changes(&before.app_roles, &after.app_roles);
+
+    Ok(AccessDiff {
+        from,
+        to,
+        groups_added,
+        groups_removed,
+        app_roles_added,
+        app_roles_removed,
+    })
+}
+
+// Called after each sign-in with the user's profile
+pub fn record_access_snapshot(app_handle: &AppHandle, profile: &Value) -> Result<(), String> {
+    let taken_at = Utc::now();
+    let snapshot = AccessSnapshot {
+        id: taken_at.format("%Y%m%dT%H%M%S%.3fZ").to_string(),
+        user_name: profile["userName"].as_str().unwrap_or_default().to_string(),
+        taken_at,
+        groups: sorted_names(profile["groups"].as_array(), |group| {
+            group["display"].as_str().or_else(|| group["value"].as_str()).map(|g| g.to_string())
+        }),
+        app_roles: sorted_names(profile[IDCS_USER_EXTENSION]["appRoles"].as_array(), |role| {
+            let name = role["display"].as_str().or_else(|| role["value"].as_str())?;
+            Some(match role["appName"].as_str() {
+                Some(app) => format!("{}: {}", app, name),
+                None => name.to_string(),
+            })
+        }),
+    };
+
+    let mut snapshots = load_snapshots(app_handle)?;
+    let previous = snapshots.iter().rev().find(|s| s.user_name == snapshot.user_name);
+    if let Some(previous) = previous {
+        let unchanged = previous.groups == snapshot.groups && previous.app_roles == snapshot.app_roles;
+        if unchanged && snapshot.taken_at - previous.taken_at < Duration::hours(SNAPSHOT_INTERVAL_HOURS) {
+            return Ok(());
+        }
+    }
+
+    log::debug!("Recording access snapshot {} for {}", snapshot.id, snapshot.user_name);
+    snapshots.push(snapshot);
+    if snapshots.len() > MAX_SNAPSHOTS {
+        snapshots.drain(..snapshots.len() - MAX_SNAPSHOTS);
+    }
+
+    let store = open_store(app_handle)?;
+    store.set("snapshots", serde_json::to_value(&snapshots).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+// Drops snapshots taken before `cutoff`, returning how many were removed
+pub fn prune(app_handle: &AppHandle, cutoff: DateTime<Utc>) -> Result<usize, String> {
+    let snapshots = load_snapshots(app_handle)?;
+    let count = snapshots.len();
+    let kept: Vec<AccessSnapshot> = snapshots.into_iter().filter(|snapshot| snapshot.taken_at >= cutoff).collect();
+    if kept.len() == count {
+        return Ok(0);
+    }
+
+    let store = open_store(app_handle)?;
+    store.set("snapshots", serde_json::to_value(&kept).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())?;
+    Ok(count - kept.len())
+}
+
+fn load_snapshots(app_handle: &AppHandle) -> Result<Vec<AccessSnapshot>, String> {
+    let store = open_store(app_handle)?;
+    Ok(store
+        .get("snapshots")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+fn sorted_names(values: Option<&Vec<Value>>, name: impl Fn(&Value) -> Option<String>) -> Vec<String> {
+    values
+        .map(|values| values.iter().filter_map(name).collect::<BTreeSet<_>>().into_iter().collect())
+        .unwrap_or_default()
+}
+
+// (added, removed) going from `before` to `after`
+fn changes(before: &[String], after: &[String]) -> (Vec<String>, Vec<String>) {
+    let added = after.iter().filter(|item| !before.contains(item)).cloned().collect();
+    let removed = before.iter().filter(|item| !after.contains(item)).cloned().collect();
+    (added, removed)
+}
+
+fn open_store(app_handle: &AppHandle) -> Result<Arc<Store<Wry>>, String> {
+    schema::open(app_handle, &access_store_path(app_handle)?, MIGRATIONS)
+}
+
+fn access_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(ACCESS_STORE_FILE))
+}
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessibilityPrefs {
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+    pub screen_reader: bool,
+}
+
+#[command]
+pub async fn get_accessibility_prefs() -> Result<AccessibilityPrefs, CommandError> {
+    tauri::async_runtime::spawn_blocking(detect)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+// The OSes don't offer a portable change notification, so poll and emit `accessibility-changed`
+pub async fn watch_accessibility_prefs(app_handle: AppHandle) {
+    let mut last = tauri::async_runtime::spawn_blocking(detect).await.unwrap_or_default();
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let current = match tauri::async_runtime::spawn_blocking(detect).await {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                log::warn!("Accessibility detection failed: {}", e);
+                continue;
+            }
+        };
+        if current != last {
+            log::info!("Accessibility preferences changed: {:?}", current);
+            crate::bus::emit(&app_handle, "accessibility-changed", current);
+            last = current;
+        }
+    }
+}
+
+pub fn detect() -> AccessibilityPrefs {
+    if cfg!(target_os = "windows") {
+        AccessibilityPrefs {
+            // HCF_HIGHCONTRASTON is bit 0 of the HighContrast flags
+            high_contrast: reg_value(r"HKCU\Control Panel\Accessibility\HighContrast", "Flags")
+                .and_then(|v| v.parse::<u32>().ok())
+                .map(|flags| flags & 1 == 1)
+                .unwrap_or(false),
+            reduced_motion: reg_value(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate")
+                .map(|v| v == "0")
+                .unwrap_or(false),
+            screen_reader: reg_value(r"HKCU\Control Panel\Accessibility\Blind Access", "On")
+                .map(|v| v == "1")
+                .unwrap_or(false),
+        }
+    } else if cfg!(target_os = "macos") {
+        AccessibilityPrefs {
+            high_contrast: defaults_flag("increaseContrast"),
+            reduced_motion: defaults_flag("reduceMotion"),
+            screen_reader: defaults_flag("voiceOverOnOffKey"),
+        }
+    } else {
+        AccessibilityPrefs {
+            high_contrast: gsettings("org.gnome.desktop.a11y.interface", "high-contrast")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+            reduced_motion: gsettings("org.gnome.desktop.interface", "enable-animations")
+                .map(|v| v == "false")
+                .unwrap_or(false),
+            screen_reader: gsettings("org.gnome.desktop.a11y.applications", "screen-reader-enabled")
+                .map(|v| v == "true")
+                .unwrap_or(false),
+        }
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn gsettings(schema: &str, key: &str) -> Option<String> {
+    command_output("gsettings", &["get", schema, key])
+}
+
+fn defaults_flag(key: &str) -> bool {
+    command_output("defaults", &["read", "com.apple.universalaccess", key])
+        .map(|v| v == "1")
+        .unwrap_or(false)
+}
+
+// `reg query` prints "    Name    REG_SZ    Value"; DWORDs come back as hex like 0x7e
+fn reg_value(key: &str, name: &str) -> Option<String> {
+    let output = command_output("reg", &["query", key, "/v", name])?;
+    let value = output
+        .lines()
+        .find(|line| line.trim_start().starts_with(name))?
+        .split_whitespace()
+        .nth(2)?
+        .to_string();
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok().map(|v| v.to_string()),
+        None => Some(value),
+    }
+}
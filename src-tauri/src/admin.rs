@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::session::SessionState;
+
+const IDCS_USER_EXTENSION: &str = "urn:ietf:params:scim:schemas:oracle:idcs:extension:user:User";
+
+// IDCS administrator app roles the app knows how to scope features for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    IdentityDomainAdmin,
+    UserAdmin,
+    UserManager,
+    HelpDesk,
+}
+
+impl AdminRole {
+    // Matches app role display names, plus the admin group names used by the demo tenant
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "identity domain administrator" | "identity domain administrators" | "identity domain admin" => {
+                Some(AdminRole::IdentityDomainAdmin)
+            }
+            "user administrator" | "user administrators" => Some(AdminRole::UserAdmin),
+            "user manager" | "user managers" => Some(AdminRole::UserManager),
+            "help desk administrator" | "help desk administrators" | "helpdesk administrator" => Some(AdminRole::HelpDesk),
+            _ => None,
+        }
+    }
+}
+
+// Which admin features the signed-in user may use, so the UI can hide them instead of hitting 403s
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdminCapabilities {
+    pub roles: Vec<AdminRole>,
+    pub search_users: bool,
+    pub unlock_users: bool,
+    pub reset_passwords: bool,
+    pub manage_groups: bool,
+}
+
+impl AdminCapabilities {
+    pub fn from_profile(profile: &Value) -> Self {
+        let app_roles = profile[IDCS_USER_EXTENSION]["appRoles"].as_array().into_iter().flatten();
+        let groups = profile["groups"].as_array().into_iter().flatten();
+
+        let mut roles: Vec<AdminRole> = Vec::new();
+        for role in app_roles
+            .chain(groups)
+            .filter_map(|entry| entry["display"].as_str())
+            .filter_map(AdminRole::from_name)
+        {
+            if !roles.contains(&role) {
+                roles.push(role);
+            }
+        }
+
+        let has = |allowed: &[AdminRole]| roles.iter().any(|role| allowed.contains(role));
+        Self {
+            search_users: has(&[
+                AdminRole::IdentityDomainAdmin,
+                AdminRole::UserAdmin,
+                AdminRole::UserManager,
+                AdminRole::HelpDesk,
+            ]),
+            unlock_users: has(&[AdminRole::IdentityDomainAdmin, AdminRole::UserAdmin, AdminRole::HelpDesk]),
+            reset_passwords: has(&[
+                AdminRole::IdentityDomainAdmin,
+                AdminRole::UserAdmin,
+                AdminRole::UserManager,
+                AdminRole::HelpDesk,
+            ]),
+            manage_groups: has(&[AdminRole::IdentityDomainAdmin, AdminRole::UserAdmin]),
+            roles,
+        }
+    }
+}
+
+#[command]
+pub fn get_admin_capabilities(session: State<SessionState>) -> Result<AdminCapabilities, CommandError> {
+    // Nothing to offer when the admin modules are compiled out
+    if cfg!(not(feature = "scim-admin")) {
+        return Ok(AdminCapabilities::default());
+    }
+    match session.profile() {
+        Ok(profile) => Ok(AdminCapabilities::from_profile(&profile)),
+        Err(_) => Ok(AdminCapabilities::default()),
+    }
+}
+
+// Guard for admin commands, e.g. `require_capability(&session, |c| c.unlock_users, "unlock users")`
+pub fn require_capability(
+    session: &SessionState,
+    allowed: impl Fn(&AdminCapabilities) -> bool,
+    action: &str,
+) -> Result<AdminCapabilities, String> {
+    let capabilities = AdminCapabilities::from_profile(&session.profile()?);
+    if !allowed(&capabilities) {
+        return Err(format!("Your administrator role does not allow you to {}", action));
+    }
+    Ok(capabilities)
+}
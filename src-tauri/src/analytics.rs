@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::config::ConfigState;
+use crate::error::CommandError;
+use crate::middleware::command;
+
+const QUEUE_FILE: &str = "analytics-queue.json";
+const FLUSH_INTERVAL: Duration = Duration::from_secs(3600);
+
+// Aggregated counts only. Names are fixed strings from the code, never user or tenant data.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsQueue {
+    pub since: Option<DateTime<Utc>>,
+    pub features: BTreeMap<String, u64>,
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl AnalyticsQueue {
+    fn is_empty(&self) -> bool {
+        self.features.is_empty() && self.errors.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct AnalyticsState(pub Mutex<Option<AnalyticsQueue>>);
+
+// Counts one use of a feature, e.g. `analytics::track(&app_handle, "export_pdf")`
+pub fn track(app_handle: &AppHandle, feature: &'static str) {
+    record(app_handle, |queue| *queue.features.entry(feature.to_string()).or_insert(0) += 1);
+}
+
+// Counts an error by category, e.g. `analytics::track_error(&app_handle, "login_failed")`
+pub fn track_error(app_handle: &AppHandle, category: &'static str) {
+    record(app_handle, |queue| *queue.errors.entry(category.to_string()).or_insert(0) += 1);
+}
+
+#[command(unlocked)]
+pub fn set_analytics_consent(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    consent: bool,
+) -> Result<(), CommandError> {
+    let mut config = config_state.0.lock();
+    config
+        .set_analytics_consent(&app_handle, consent)
+        .map_err(|e| e.to_string())?;
+    drop(config);
+
+    // Opting out drops everything collected so far, sent or not
+    if !consent {
+        clear(&app_handle)?;
+    }
+    log::info!("Usage analytics {}", if consent { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+pub fn clear(app_handle: &AppHandle) -> Result<(), String> {
+    if let Some(analytics) = app_handle.try_state::<AnalyticsState>() {
+        *analytics.0.lock().map_err(|e| e.to_string())? = None;
+    }
+    let path = queue_path(app_handle)?;
+    if path.exists() {
+        std::fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Exactly what the next upload would contain
+#[command]
+pub fn get_analytics_queue(app_handle: AppHandle) -> Result<AnalyticsQueue, CommandError> {
+    Ok(load_queue(&app_handle).unwrap_or_default())
+}
+
+// Posts the queued counts to the configured endpoint once an hour while the user is opted in
+pub async fn flush_analytics(app_handle: AppHandle) {
+    loop {
+        tokio::time::sleep(FLUSH_INTERVAL).await;
+
+        let (enabled, endpoint) = app_handle
+            .state::<ConfigState>()
+            .0
+            .read(|config| (config.analytics.enabled, config.analytics.endpoint.clone()));
+        let Some(endpoint) = endpoint.filter(|_| enabled) else {
+            continue;
+        };
+        let Some(queue) = load_queue(&app_handle).filter(|queue| !queue.is_empty()) else {
+            continue;
+        };
+
+        match upload(&endpoint, &queue).await {
+            Ok(()) => {
+                // Keep anything counted while the upload was in flight
+                record(&app_handle, |current| {
+                    for (name, count) in &queue.features {
+                        subtract(&mut current.features, name, *count);
+                    }
+                    for (name, count) in &queue.errors {
+                        subtract(&mut current.errors, name, *count);
+                    }
+                    current.since = Some(Utc::now());
+                });
+                log::debug!("Uploaded usage analytics");
+            }
+            Err(e) => log::debug!("Failed to upload usage analytics: {}", e),
+        }
+    }
+}
+
+fn record(app_handle: &AppHandle, update: impl FnOnce(&mut AnalyticsQueue)) {
+    let enabled = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.analytics.enabled);
+    if !enabled {
+        return;
+    }
+
+    let analytics = app_handle.state::<AnalyticsState>();
+    let Ok(mut state) = analytics.0.lock() else {
+        return;
+    };
+    let queue = state.get_or_insert_with(|| read_queue_file(app_handle));
+    queue.since.get_or_insert_with(Utc::now);
+    update(queue);
+
+    if let Ok(path) = queue_path(app_handle) {
+        if let Ok(contents) = serde_json::to_string(queue) {
+            let _ = std::fs::write(path, contents);
+        }
+    }
+}
+
+fn load_queue(app_handle: &AppHandle) -> Option<AnalyticsQueue> {
+    let analytics = app_handle.state::<AnalyticsState>();
+    let mut state = analytics.0.lock().ok()?;
+    Some(state.get_or_insert_with(|| read_queue_file(app_handle)).clone())
+}
+
+fn read_queue_file(app_handle: &AppHandle) -> AnalyticsQueue {
+    queue_path(app_handle)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn subtract(counts: &mut BTreeMap<String, u64>, name: &str, count: u64) {
+    if let Some(current) = counts.get_mut(name) {
+        *current = current.saturating_sub(count);
+        if *current == 0 {
+            counts.remove(name);
+        }
+    }
+}
+
+async fn upload(endpoint: &str, queue: &AnalyticsQueue) -> Result<(), String> {
+    let body = json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "since": queue.since,
+        "until": Utc::now(),
+        "features": queue.features,
+        "errors": queue.errors,
+    });
+    reqwest::Client::new()
+        .post(endpoint)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn queue_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(QUEUE_FILE))
+}
@@ -0,0 +1,197 @@
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth_client::AuthClient;
+use crate::circuit;
+use crate::config::NotificationEvent;
+use crate::error::CommandError;
+use crate::i18n;
+use crate::maintenance;
+use crate::middleware::command;
+use crate::renewal;
+use crate::session::SessionState;
+use crate::tenant::{get_resource, ScimError};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+// A self-service access request waiting on the signed-in approver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequest {
+    pub id: String,
+    pub requester: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_name: Option<String>,
+    pub justification: Option<String>,
+    pub created: Option<String>,
+    pub expires: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum ApprovalAction {
+    Approve,
+    Reject,
+}
+
+// Pending count from the last poll, shown as the tray badge
+#[derive(Default)]
+pub struct ApprovalsState(pub Mutex<usize>);
+
+#[command(session)]
+pub async fn list_access_requests(
+    app_handle: AppHandle,
+    session: State<'_, SessionState>,
+) -> Result<Vec<AccessRequest>, CommandError> {
+    let client = app_handle.state::<AuthClient>();
+    let requests = fetch_pending(&client, &session.bearer_token()?).await?;
+    update_pending_count(&app_handle, requests.len());
+    Ok(requests)
+}
+
+#[command(session)]
+pub async fn approve_access_request(
+    app_handle: AppHandle,
+    session: State<'_, SessionState>,
+    id: String,
+    justification: Option<String>,
+) -> Result<(), CommandError> {
+    decide(&app_handle, &session, &id, ApprovalAction::Approve, justification).await?;
+    Ok(())
+}
+
+#[command(session)]
+pub async fn reject_access_request(
+    app_handle: AppHandle,
+    session: State<'_, SessionState>,
+    id: String,
+    justification: Option<String>,
+) -> Result<(), CommandError> {
+    decide(&app_handle, &session, &id, ApprovalAction::Reject, justification).await?;
+    Ok(())
+}
+
+// Keeps the tray badge current while an approver is signed in
+pub async fn poll_access_requests(app_handle: AppHandle) {
+    loop {
+        if maintenance::in_progress(&app_handle) {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        let client = app_handle.state::<AuthClient>();
+        let bearer_token = app_handle.state::<SessionState>().bearer_token();
+        if let Ok(bearer_token) = bearer_token {
+            let mut pending = fetch_pending(&client, &bearer_token).await;
+            // The access token may have expired early, e.g. revoked by a policy change
+            if let Err(e) = &pending {
+                if renewal::renew_after_unauthorized(&app_handle, e).await {
+                    if let Ok(bearer_token) = app_handle.state::<SessionState>().bearer_token() {
+                        pending = fetch_pending(&client, &bearer_token).await;
+                    }
+                }
+            }
+            match pending {
+                Ok(requests) => update_pending_count(&app_handle, requests.len()),
+                Err(e) => log::warn!("Failed to poll access requests: {}", e),
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn fetch_pending(client: &AuthClient, bearer_token: &str) -> Result<Vec<AccessRequest>, ScimError> {
+    let response = get_resource(client, bearer_token, "/admin/v1/MyPendingApprovals").await?;
+    Ok(response["Resources"]
+        .as_array()
+        .map(|resources| resources.iter().filter_map(parse_request).collect())
+        .unwrap_or_default())
+}
+
+fn parse_request(resource: &Value) -> Option<AccessRequest> {
+    let text = |value: &Value| value.as_str().map(|s| s.to_string());
+    Some(AccessRequest {
+        id: resource["id"].as_str()?.to_string(),
+        requester: text(&resource["requestor"]["display"]).or_else(|| text(&resource["requestor"]["value"])),
+        resource_type: text(&resource["resourceType"]),
+        resource_name: text(&resource["resourceDisplayName"]),
+        justification: text(&resource["justification"]),
+        created: text(&resource["meta"]["created"]),
+        expires: text(&resource["expires"]),
+    })
+}
+
+async fn decide(
+    app_handle: &AppHandle,
+    session: &SessionState,
+    id: &str,
+    action: ApprovalAction,
+    justification: Option<String>,
+) -> Result<(), String> {
+    let bearer_token = session.bearer_token()?;
+    let client = app_handle.state::<AuthClient>();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&bearer_token).map_err(|e| e.to_string())?,
+    );
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/scim+json"));
+
+    let url = client.url(&format!("/admin/v1/MyApprovals/{}", id));
+    log::debug!("Submitting {:?} for access request {}", action, id);
+
+    let response = circuit::send(
+        client
+            .http()
+            .put(&url)
+            .headers(headers)
+            .json(&json!({
+                "schemas": ["urn:ietf:params:scim:schemas:oracle:idcs:MyApproval"],
+                "id": id,
+                "action": action,
+                "justification": justification
+            })),
+    )
+    .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let response_text = response.text().await.map_err(|e| e.to_string())?;
+        return Err(format!("Failed to update access request {}: {} {}", id, status, response_text));
+    }
+
+    log::info!("Access request {} {}", id, if action == ApprovalAction::Approve { "approved" } else { "rejected" });
+    let remaining = fetch_pending(&client, &bearer_token).await.map(|requests| requests.len());
+    if let Ok(remaining) = remaining {
+        update_pending_count(app_handle, remaining);
+    }
+    Ok(())
+}
+
+fn update_pending_count(app_handle: &AppHandle, count: usize) {
+    let state = app_handle.state::<ApprovalsState>();
+    let Ok(mut pending) = state.0.lock() else {
+        return;
+    };
+    if *pending == count {
+        return;
+    }
+    let increased = count > *pending;
+    *pending = count;
+    drop(pending);
+
+    if increased {
+        let body = match count {
+            1 => "1 access request is waiting for your approval".to_string(),
+            n => format!("{} access requests are waiting for your approval", n),
+        };
+        crate::notifications::notify(app_handle, NotificationEvent::AccessRequests, &body);
+    }
+
+    crate::tray::set_badge(app_handle, count);
+    crate::bus::emit(app_handle, "access-requests-changed", count);
+}
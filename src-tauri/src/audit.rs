@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::Store;
+
+use crate::schema::{self, Migration};
+
+const AUDIT_STORE_FILE: &str = "audit.json";
+// Append-only JSON lines file the events were kept in before format 1
+const LEGACY_AUDIT_LOG_FILE: &str = "audit.log";
+// Format 1: `events`, oldest first, each with `at`, `event` and `details`
+const MIGRATIONS: &[Migration] = &[import_legacy_log];
+
+// Record of privileged admin actions and login locations, kept apart from the rotating app logs
+pub fn record(app_handle: &AppHandle, event: &str, details: Value) -> Result<(), String> {
+    let store = open_store(app_handle)?;
+    let mut events = load_events(&store);
+    events.push(json!({
+        "at": Utc::now().to_rfc3339(),
+        "event": event,
+        "details": details,
+    }));
+    store.set("events", Value::Array(events));
+    store.save().map_err(|e| e.to_string())
+}
+
+// Details of the most recent `event`, if any was recorded
+pub fn latest(app_handle: &AppHandle, event: &str) -> Result<Option<Value>, String> {
+    let store = open_store(app_handle)?;
+    Ok(load_events(&store)
+        .into_iter()
+        .rev()
+        .find(|entry| entry["event"] == event)
+        .map(|entry| entry["details"].clone()))
+}
+
+// Drops entries recorded before `cutoff`, returning how many were removed
+pub fn prune(app_handle: &AppHandle, cutoff: DateTime<Utc>) -> Result<usize, String> {
+    let store = open_store(app_handle)?;
+    let events = load_events(&store);
+    let count = events.len();
+    let kept: Vec<Value> = events
+        .into_iter()
+        .filter(|entry| {
+            entry["at"]
+                .as_str()
+                .and_then(|at| at.parse::<DateTime<Utc>>().ok())
+                .map(|at| at >= cutoff)
+                .unwrap_or(true)
+        })
+        .collect();
+    if kept.len() == count {
+        return Ok(0);
+    }
+    store.set("events", Value::Array(kept.clone()));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(count - kept.len())
+}
+
+fn load_events(store: &Store<Wry>) -> Vec<Value> {
+    match store.get("events") {
+        Some(Value::Array(events)) => events,
+        _ => Vec::new(),
+    }
+}
+
+// Moves the entries of `audit.log` into the store. The old file is only deleted once the store holding them is
+// saved, and an interrupted upgrade that already saved them doesn't import them twice.
+fn import_legacy_log(app_handle: &AppHandle, store: &Store<Wry>) -> Result<(), String> {
+    let legacy = audit_dir(app_handle)?.join(LEGACY_AUDIT_LOG_FILE);
+    if !legacy.exists() {
+        return Ok(());
+    }
+    if !store.has("events") {
+        let contents = std::fs::read_to_string(&legacy).map_err(|e| e.to_string())?;
+        // A line cut short by a crash is kept as it was rather than dropped
+        let events = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).unwrap_or_else(|_| json!({ "event": "unreadable", "details": line })))
+            .collect();
+        store.set("events", Value::Array(events));
+        store.save().map_err(|e| e.to_string())?;
+    }
+    std::fs::remove_file(&legacy).map_err(|e| e.to_string())
+}
+
+fn open_store(app_handle: &AppHandle) -> Result<Arc<Store<Wry>>, String> {
+    schema::open(app_handle, &audit_dir(app_handle)?.join(AUDIT_STORE_FILE), MIGRATIONS)
+}
+
+fn audit_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
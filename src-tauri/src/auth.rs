@@ -1,16 +1,61 @@
 use base64::{engine::general_purpose::STANDARD, Engine};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
 
-const BASE_URL: &str = "https://idcs-8e8265d058d54299bdc845382c75339f.identity.oraclecloud.com";
+use crate::access;
+use crate::analytics;
+use crate::auth_client::AuthClient;
+use crate::cache::{CacheState, JWKS_KEY, PROFILE_KEY, USER_SCHEMA_KEY};
+use crate::circuit;
+use crate::config::{AuthFactor, ConfigState, NotificationEvent};
+use crate::demo::DemoState;
+use crate::dpop::{self, DpopKey};
+use crate::endpoints::base_url;
+use crate::error::{AuthError, CommandError, ErrorKind};
+use crate::guest;
+use crate::i18n;
+use crate::lifecycle::{self, AppState};
+use crate::location;
+use crate::middleware::command;
+use crate::my_sessions::SessionGuardState;
+use crate::notifications;
+use crate::policy::{self, PolicyState};
+use crate::profiles;
+use crate::redact::{self, Redacted};
+use crate::session::{set_auth_session, AuthSession, AuthSessionState, ScopeGrant, Session, SessionState};
+use crate::tenant::{get_scim, get_scim_cached, get_settings, ScimResponse};
 
-#[derive(Debug, Serialize, Deserialize)]
+const PROFILE_MAX_AGE: Duration = Duration::from_secs(24 * 3600);
+const METADATA_TTL: Duration = Duration::from_secs(24 * 3600);
+pub(crate) const JWKS_PATH: &str = "/admin/v1/SigningCert/jwk";
+const USER_SCHEMA_PATH: &str = "/admin/v1/Schemas/urn:ietf:params:scim:schemas:core:2.0:User";
+const PUSH_POLL_INTERVAL: Duration = Duration::from_secs(3);
+// Oracle Mobile Authenticator notifications can't be answered after this
+const PUSH_TIMEOUT: Duration = Duration::from_secs(120);
+const TOKEN_EXCHANGE_GRANT: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+// User principal session token, what the OCI CLI and SDKs sign requests with in `security_token` auth
+const UPST_TOKEN_TYPE: &str = "urn:oci:token-type:oci-upst";
+
+// Bumped by every `start_push_auth`, so only the latest push keeps polling
+static PUSH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenResponse {
     pub access_token: String,
     pub token_type: String,
     pub expires_in: u32,
+    // Only with `offline_access`, for user tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    // Space-separated scopes IDCS granted; it may leave this out when they are exactly the requested ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,46 +99,111 @@ struct Credentials<'a> {
     password: &'a str,
 }
 
+// Payload of the `push-pending`, `push-approved` and `push-denied` events
+#[derive(Debug, Clone, Serialize)]
+pub struct PushEvent {
+    // The request state `complete_auth` continues from after approval
+    pub request_state: String,
+    pub message: Option<String>,
+    // Seconds left to answer the notification, while pending
+    pub expires_in: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InitAuthResponse {
     #[serde(rename = "requestState")]
     pub request_state: String,
 }
 
-#[tauri::command]
-pub async fn initiate_auth(username: String, password: String) -> Result<AuthResponse, String> {
+#[command(configured)]
+pub async fn initiate_auth(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    username: String,
+    password: String,
+    guest: Option<bool>,
+) -> Result<AuthResponse, CommandError> {
+    if profiles::is_public_client() && !demo.enabled {
+        return Err(CommandError::new(
+            ErrorKind::Config,
+            "This profile uses a public client, which signs in through the browser with start_pkce_login",
+        ));
+    }
+    policy::check_allowed_hours(&app_handle.state::<PolicyState>().0)?;
+    guest::set(&app_handle, guest.unwrap_or(false));
+    lifecycle::transition(&app_handle, AppState::Authenticating);
+    let result = if demo.enabled {
+        tracing::debug!("Demo mode: authenticating against seeded users");
+        demo.initiate(&username, &password).map_err(AuthError::from)
+    } else {
+        idcs_initiate_auth(&auth_client, username, password).await
+    };
+    let mut response = result.inspect_err(|e| {
+        set_auth_session(&app_handle, AuthSession::Unauthenticated);
+        lifecycle::sign_in_failed(&app_handle, e)
+    })?;
+    let mut preferred = None;
+    if !demo.enabled {
+        (response, preferred) = select_preferred_factor(&app_handle, response).await;
+    }
+    // A successful credential step stays Authenticating until `complete_auth`
+    if preferred.is_some() || !response.next_auth_factors.is_empty() {
+        lifecycle::transition(&app_handle, AppState::MfaPending);
+    }
+    set_auth_session(
+        &app_handle,
+        match preferred {
+            Some(factor) => AuthSession::MfaPending {
+                factor,
+                request_state: response.request_state.clone(),
+                verified: false,
+            },
+            None => AuthSession::PasswordSubmitted {
+                request_state: response.request_state.clone(),
+                next_factors: response.next_auth_factors.clone(),
+            },
+        },
+    );
+
+    if crate::clipboard::tokens_protected(&app_handle) {
+        response.authn_token = response.authn_token.as_deref().map(crate::clipboard::mask_token);
+    }
+    Ok(response)
+}
+
+// Runs steps 1-3 of the IDCS flow: client token, auth init and credential submission
+pub async fn idcs_initiate_auth(
+    client: &AuthClient,
+    username: String,
+    password: String,
+) -> Result<AuthResponse, AuthError> {
     // Step 1: Get client credentials token
-    println!("Step 1: Getting client credentials token");
-    let client_id = env::var("OCI_CLIENT_ID").map_err(|e| e.to_string())?;
-    let client_secret = env::var("OCI_CLIENT_SECRET").map_err(|e| e.to_string())?;
-    
-    let credentials = format!("{}:{}", client_id, client_secret);
-    let encoded_credentials = STANDARD.encode(credentials);
-    let auth_header = format!("Basic {}", encoded_credentials);
+    tracing::debug!("Step 1: Getting client credentials token");
+    let client_auth = ClientAuth::load()?;
     
-    let token_response = get_client_credentials_token(&auth_header)
+    let token_response = get_client_credentials_token(client, &client_auth)
         .await
         .map_err(|e| {
-            println!("Failed to get client credentials token: {}", e);
+            tracing::debug!("Failed to get client credentials token: {}", e);
             e
         })?;
-    println!("Successfully obtained access token");
+    tracing::debug!("Successfully obtained access token");
 
     // Step 2: Initialize authentication
-    println!("Step 2: Initializing authentication");
+    tracing::debug!("Step 2: Initializing authentication");
     let bearer_token = format!("Bearer {}", token_response.access_token);
-    let init_response = initialize_authentication(&bearer_token)
+    let init_response = initialize_authentication(client, &bearer_token)
         .await
         .map_err(|e| {
-            println!("Failed to initialize authentication: {}", e);
+            tracing::debug!("Failed to initialize authentication: {}", e);
             e
         })?;
-    println!("Successfully initialized authentication");
+    tracing::debug!("Successfully initialized authentication");
 
     // Step 3: Submit credentials
-    println!("Step 3: Submitting credentials");
-    let client = reqwest::Client::new();
-    let cred_url = format!("{}/sso/v1/sdk/authenticate", BASE_URL);
+    tracing::debug!("Step 3: Submitting credentials");
+    let cred_url = client.url("/sso/v1/sdk/authenticate");
     
     let cred_request = json!({
         "op": "credSubmit",
@@ -104,17 +214,11 @@ pub async fn initiate_auth(username: String, password: String) -> Result<AuthRes
         "requestState": init_response.request_state
     });
 
-    println!("Making request to URL: {}", cred_url);
-    println!("Request body structure: {}", serde_json::json!({
-        "op": "credSubmit",
-        "credentials": {
-            "username": "***",
-            "password": "***"
-        },
-        "requestState": "***"
-    }));
+    let span = tracing::debug_span!("submit_credentials", url = %cred_url, status = Empty, elapsed_ms = Empty);
+    span.in_scope(|| tracing::debug!("Request body: {}", Redacted(&cred_request)));
 
     let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
     headers.insert(
         AUTHORIZATION,
         HeaderValue::from_str(&bearer_token).map_err(|e| e.to_string())?,
@@ -124,301 +228,1104 @@ pub async fn initiate_auth(username: String, password: String) -> Result<AuthRes
         HeaderValue::from_static("application/json"),
     );
 
-    let response = client
-        .post(&cred_url)
-        .headers(headers)
-        .json(&cred_request)
-        .send()
-        .await
-        .map_err(|e| {
-            println!("Request failed: {}", e);
-            e.to_string()
-        })?;
-
-    println!("Response status: {}", response.status());
-    println!("Response headers: {:#?}", response.headers());
+    let started = Instant::now();
+    let response = circuit::send(
+        client
+            .http()
+            .post(&cred_url)
+            .headers(headers)
+            .json(&cred_request),
+    )
+    .instrument(span.clone())
+    .await
+    .map_err(|e| {
+        tracing::debug!("Request failed: {}", e);
+        e
+    })?;
+    span.in_scope(|| record_response(&response, started));
     
     let status = response.status();
     let response_text = response.text().await.map_err(|e| {
-        println!("Failed to get response text: {}", e);
+        tracing::debug!("Failed to get response text: {}", e);
         e.to_string()
     })?;
-    println!("Response body: {}", response_text);
+    tracing::debug!("Response body: {}", redact::body(&response_text));
 
     if !status.is_success() {
-        return Err(format!(
-            "Failed to get response: {}",
-            response_text
-        ));
+        return Err(response_error(status, &response_text));
     }
 
-    let response_json: AuthResponse = serde_json::from_str(&response_text)
+    let mut response_json: AuthResponse = serde_json::from_str(&response_text)
         .map_err(|e| {
-            println!("Failed to parse response as JSON: {}", e);
-            format!("Failed to parse response: {}. Response text: {}", e, response_text)
+            tracing::debug!("Failed to parse response as JSON: {}", e);
+            format!("Failed to parse response: {}. Response text: {}", e, redact::body(&response_text))
         })?;
+    i18n::localize_causes(&mut response_json.cause);
 
-    println!("Successfully parsed response into AuthResponse");
+    tracing::debug!("Successfully parsed response into AuthResponse");
     Ok(response_json)
 }
 
-#[tauri::command]
-pub async fn complete_auth(request_state: String) -> Result<Value, String> {
+#[command(unlocked)]
+pub fn set_preferred_factors(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    factors: Vec<AuthFactor>,
+) -> Result<(), CommandError> {
+    let mut config = config_state.0.lock();
+    config
+        .set_preferred_factors(&app_handle, factors)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// When IDCS offers a choice of factors, starts the user's preferred one so they don't have to pick it, and
+// returns it with the response. Any failure leaves the original response, and the choice, to the user.
+pub(crate) async fn select_preferred_factor(
+    app_handle: &AppHandle,
+    response: AuthResponse,
+) -> (AuthResponse, Option<AuthFactor>) {
+    if response.next_auth_factors.len() < 2 {
+        return (response, None);
+    }
+    let preferred = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.auth.preferred_factors.clone());
+    let Some(factor) = preferred.into_iter().find(|factor| {
+        response
+            .next_auth_factors
+            .iter()
+            .any(|offered| offered.eq_ignore_ascii_case(factor.idcs_name()))
+    }) else {
+        return (response, None);
+    };
+
+    match submit_factor(&app_handle.state::<AuthClient>(), factor, &response.request_state).await {
+        Ok(selected) => {
+            log::info!("Started preferred factor {}", factor.idcs_name());
+            (selected, Some(factor))
+        }
+        Err(e) => {
+            log::warn!("Failed to start preferred factor {}: {}", factor.idcs_name(), e);
+            (response, None)
+        }
+    }
+}
+
+async fn submit_factor(client: &AuthClient, factor: AuthFactor, request_state: &str) -> Result<AuthResponse, AuthError> {
+    authenticate_step(client, json!({
+        "op": "credSubmit",
+        "authFactor": factor.idcs_name(),
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Failed to select factor"))
+}
+
+// Starts the factor the user picked from `next_auth_factors`
+#[command]
+pub async fn select_auth_factor(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    factor: AuthFactor,
+) -> Result<AuthResponse, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let response = if demo.enabled {
+        demo.select_factor(&request_state, factor).map_err(AuthError::from)?
+    } else {
+        submit_factor(&auth_client, factor, &request_state).await?
+    };
+    factor_started(&app_handle, factor, &response.request_state);
+    Ok(response)
+}
+
+// Submits the authenticator app passcode; on success, finish with `complete_auth`
+#[command]
+pub async fn submit_totp(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    otp_code: String,
+) -> Result<AuthResponse, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    Ok(submit_passcode(&app_handle, &auth_client, &demo, AuthFactor::Totp, &request_state, &otp_code).await?)
+}
+
+// Has IDCS send a passcode by text message or email, or send it again when `resend` is set.
+// Every step returns a new request state, which the next one continues from.
+#[command]
+pub async fn request_otp(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    factor: AuthFactor,
+    resend: bool,
+) -> Result<AuthResponse, CommandError> {
+    if !matches!(factor, AuthFactor::Sms | AuthFactor::Email) {
+        return Err(format!("{} doesn't send a passcode", factor.idcs_name()).into());
+    }
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let response = match (demo.enabled, resend) {
+        (true, true) => demo.resend(&request_state).map_err(AuthError::from)?,
+        (true, false) => demo.select_factor(&request_state, factor).map_err(AuthError::from)?,
+        (false, true) => idcs_resend_otp(&auth_client, &request_state).await?,
+        (false, false) => submit_factor(&auth_client, factor, &request_state).await?,
+    };
+    factor_started(&app_handle, factor, &response.request_state);
+    Ok(response)
+}
+
+// Submits the passcode sent by `request_otp`; on success, finish with `complete_auth`
+#[command]
+pub async fn submit_otp(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    factor: AuthFactor,
+    otp_code: String,
+) -> Result<AuthResponse, CommandError> {
+    if !matches!(factor, AuthFactor::Sms | AuthFactor::Email) {
+        return Err(format!("{} doesn't use a sent passcode", factor.idcs_name()).into());
+    }
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    Ok(submit_passcode(&app_handle, &auth_client, &demo, factor, &request_state, &otp_code).await?)
+}
+
+// For users who can't use their usual second factor, e.g. a lost phone; on success, finish with `complete_auth`
+#[command]
+pub async fn submit_bypass_code(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    bypass_code: String,
+) -> Result<AuthResponse, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    Ok(submit_passcode(&app_handle, &auth_client, &demo, AuthFactor::BypassCode, &request_state, &bypass_code).await?)
+}
+
+async fn submit_passcode(
+    app_handle: &AppHandle,
+    client: &AuthClient,
+    demo: &DemoState,
+    factor: AuthFactor,
+    request_state: &str,
+    otp_code: &str,
+) -> Result<AuthResponse, AuthError> {
+    let result = if demo.enabled {
+        demo.submit_otp(request_state, factor, otp_code).map_err(AuthError::from)
+    } else {
+        idcs_submit_otp(client, factor, request_state, otp_code).await
+    };
+    let mut response = result.inspect_err(|e| lifecycle::factor_failed(app_handle, e))?;
+    factor_submitted(app_handle, factor, &response);
+
+    if crate::clipboard::tokens_protected(app_handle) {
+        response.authn_token = response.authn_token.as_deref().map(crate::clipboard::mask_token);
+    }
+    Ok(response)
+}
+
+pub async fn idcs_submit_otp(
+    client: &AuthClient,
+    factor: AuthFactor,
+    request_state: &str,
+    otp_code: &str,
+) -> Result<AuthResponse, AuthError> {
+    tracing::debug!("Submitting {} passcode", factor.idcs_name());
+    // Bypass codes go in their own credential field
+    let field = match factor {
+        AuthFactor::BypassCode => "bypassCode",
+        _ => "otpCode",
+    };
+    authenticate_step(client, json!({
+        "op": "credSubmit",
+        "authFactor": factor.idcs_name(),
+        "credentials": {
+            field: otp_code.trim()
+        },
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Passcode rejected"))
+}
+
+async fn idcs_resend_otp(client: &AuthClient, request_state: &str) -> Result<AuthResponse, AuthError> {
+    tracing::debug!("Requesting a new passcode");
+    authenticate_step(client, json!({
+        "op": "resend",
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Failed to resend the passcode"))
+}
+
+// Sends an Oracle Mobile Authenticator notification, then polls in the background until it is answered.
+// The outcome arrives as a `push-approved` or `push-denied` event; `push-pending` is emitted while waiting.
+#[command]
+pub async fn start_push_auth(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+) -> Result<AuthResponse, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let response = if demo.enabled {
+        demo.select_factor(&request_state, AuthFactor::Push).map_err(AuthError::from)?
+    } else {
+        submit_factor(&auth_client, AuthFactor::Push, &request_state).await?
+    };
+    factor_started(&app_handle, AuthFactor::Push, &response.request_state);
+
+    let generation = PUSH_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    tauri::async_runtime::spawn(poll_push(app_handle, response.request_state.clone(), generation));
+    Ok(response)
+}
+
+// Stops when the push is answered or expires, another push starts, or the sign-in is abandoned
+async fn poll_push(app_handle: AppHandle, mut request_state: String, generation: u64) {
+    let deadline = Instant::now() + PUSH_TIMEOUT;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            let message = "The push notification expired.".to_string();
+            emit_push(&app_handle, "push-denied", &request_state, Some(message.clone()), None);
+            lifecycle::sign_in_failed(&app_handle, &AuthError::from(message.as_str()));
+            return;
+        }
+        emit_push(&app_handle, "push-pending", &request_state, None, Some(remaining.as_secs()));
+        tokio::time::sleep(PUSH_POLL_INTERVAL).await;
+        if PUSH_GENERATION.load(Ordering::SeqCst) != generation
+            || lifecycle::current(&app_handle) != AppState::MfaPending
+        {
+            log::info!("Push polling stopped: the sign-in moved on");
+            return;
+        }
+
+        let demo = app_handle.state::<DemoState>();
+        let result = if demo.enabled {
+            demo.poll_push(&request_state).map_err(AuthError::from)
+        } else {
+            idcs_poll_push(&app_handle.state::<AuthClient>(), &request_state).await
+        };
+        match result {
+            Ok(response) if response.status == "success" => {
+                log::info!("Push notification approved");
+                factor_submitted(&app_handle, AuthFactor::Push, &response);
+                let message = response.cause.first().map(|cause| cause.message.clone());
+                emit_push(&app_handle, "push-approved", &response.request_state, message, None);
+                return;
+            }
+            // Every poll returns the request state the next one must use
+            Ok(response) => {
+                factor_started(&app_handle, AuthFactor::Push, &response.request_state);
+                request_state = response.request_state;
+            }
+            Err(e) => {
+                log::warn!("Push notification not approved: {}", e);
+                emit_push(&app_handle, "push-denied", &request_state, Some(e.to_string()), None);
+                lifecycle::sign_in_failed(&app_handle, &e);
+                return;
+            }
+        }
+    }
+}
+
+async fn idcs_poll_push(client: &AuthClient, request_state: &str) -> Result<AuthResponse, AuthError> {
+    authenticate_step(client, json!({
+        "op": "credSubmit",
+        "authFactor": AuthFactor::Push.idcs_name(),
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Push notification failed"))
+}
+
+fn emit_push(
+    app_handle: &AppHandle,
+    event: &'static str,
+    request_state: &str,
+    message: Option<String>,
+    expires_in: Option<u64>,
+) {
+    let payload = PushEvent {
+        request_state: request_state.to_string(),
+        message,
+        expires_in,
+    };
+    crate::bus::emit(app_handle, event, payload);
+}
+
+// Posts one step of the IDCS SDK flow; error statuses carry the reason in `cause`
+async fn authenticate_step(client: &AuthClient, body: Value) -> Result<AuthResponse, AuthError> {
+    let step = authenticate_raw(client, body).await?;
+    let mut step: AuthResponse =
+        serde_json::from_value(step).map_err(|e| format!("Failed to parse response: {}", e))?;
+    i18n::localize_causes(&mut step.cause);
+    Ok(step)
+}
+
+// The step's response as IDCS sent it, for factors that add their own fields such as FIDO_AUTHENTICATOR
+#[tracing::instrument(
+    name = "authenticate",
+    level = "debug",
+    skip_all,
+    fields(op = %body["op"], url = %client.url("/sso/v1/sdk/authenticate"), status = Empty, elapsed_ms = Empty)
+)]
+pub(crate) async fn authenticate_raw(client: &AuthClient, body: Value) -> Result<Value, AuthError> {
+    let started = Instant::now();
+    let response = circuit::send(
+        client
+            .http()
+            .post(client.url("/sso/v1/sdk/authenticate"))
+            .header(ACCEPT_LANGUAGE, i18n::accept_language())
+            .header(AUTHORIZATION, client_bearer_token(client).await?)
+            .json(&body),
+    )
+    .await?;
+    record_response(&response, started);
+
+    let status = response.status();
+    let response_text = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(response_error(status, &response_text));
+    }
+    serde_json::from_str(&response_text)
+        .map_err(|e| format!("Failed to parse response: {}. Response text: {}", e, redact::body(&response_text)).into())
+}
+
+// Error statuses from the SDK endpoints carry the reason in `cause`, the token endpoint's in `error`
+pub(crate) fn response_error(status: reqwest::StatusCode, response_text: &str) -> AuthError {
+    let body: Value = serde_json::from_str(response_text).unwrap_or_default();
+    let mut cause: Vec<CauseMessage> = serde_json::from_value(body["cause"].clone()).unwrap_or_default();
+    i18n::localize_causes(&mut cause);
+    if let Some(cause) = cause.first() {
+        return AuthError::from_cause(cause);
+    }
+    // A failing server says nothing about the request, whatever `error` it reports
+    if let Some(error) = body["error"].as_str().filter(|_| !status.is_server_error()) {
+        let code = Some(error.to_string());
+        let message = body["error_description"].as_str().unwrap_or(error).to_string();
+        return match error {
+            "invalid_grant" => AuthError::InvalidCredentials { code, message },
+            _ => AuthError::Rejected { code, message },
+        };
+    }
+    let message = format!("{}: {}", status, response_text);
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return AuthError::Unavailable { message };
+    }
+    AuthError::Rejected { code: None, message }
+}
+
+#[command]
+pub async fn complete_auth(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+) -> Result<Value, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let result = if demo.enabled {
+        tracing::debug!("Demo mode: completing authentication against seeded users");
+        demo.complete(&request_state)
+            .map(|profile| Session {
+                profile,
+                access_token: None,
+                client_ip: None,
+                expires_at: None,
+                dpop_key: None,
+                refresh_token: None,
+                scopes: None,
+            })
+            .map_err(AuthError::from)
+    } else {
+        idcs_complete_session(&app_handle, &auth_client, request_state).await
+    };
+    let signed_in = result.inspect_err(|e| {
+        // IDCS doesn't take a request state back once completing it failed
+        set_auth_session(&app_handle, AuthSession::Unauthenticated);
+        sign_in_error(&app_handle, e)
+    })?;
+    Ok(finish_sign_in(&app_handle, signed_in)?)
+}
+
+// The sign-in now waits on `factor`: a passcode, answers, a security key or an answer to the push
+pub(crate) fn factor_started(app_handle: &AppHandle, factor: AuthFactor, request_state: &str) {
+    lifecycle::transition(app_handle, AppState::MfaPending);
+    set_auth_session(
+        app_handle,
+        AuthSession::MfaPending {
+            factor,
+            request_state: request_state.to_string(),
+            verified: false,
+        },
+    );
+}
+
+// IDCS answered the credentials submitted for `factor`; once it accepted them, `complete_auth` finishes
+pub(crate) fn factor_submitted(app_handle: &AppHandle, factor: AuthFactor, response: &AuthResponse) {
+    let verified = response.status == "success";
+    if verified {
+        lifecycle::transition(app_handle, AppState::Authenticating);
+    }
+    set_auth_session(
+        app_handle,
+        AuthSession::MfaPending {
+            factor,
+            request_state: response.request_state.clone(),
+            verified,
+        },
+    );
+}
+
+pub(crate) fn sign_in_error(app_handle: &AppHandle, error: &AuthError) {
+    lifecycle::sign_in_failed(app_handle, error);
+    analytics::track_error(app_handle, "login_failed");
+    notifications::notify(app_handle, NotificationEvent::LoginFailed, "Sign-in failed. Open the app for details.");
+}
+
+// Everything a successful sign-in does, whichever flow produced the session; returns the profile
+pub(crate) fn finish_sign_in(app_handle: &AppHandle, signed_in: Session) -> Result<Value, String> {
+    let demo = app_handle.state::<DemoState>().enabled;
+    let guest = guest::is_active(app_handle);
+    app_handle.state::<CacheState>().invalidate_session();
+    // The IDCS path caches the profile itself, together with its ETag
+    if demo && !guest {
+        if let Err(e) = crate::cache::write_disk(app_handle, PROFILE_KEY, &signed_in.profile, PROFILE_MAX_AGE) {
+            log::warn!("Failed to cache profile: {}", e);
+        }
+    }
+    if !guest {
+        if let Err(e) = access::record_access_snapshot(app_handle, &signed_in.profile) {
+            log::warn!("Failed to record access snapshot: {}", e);
+        }
+    }
+    if !demo && !guest {
+        if let Err(e) = crate::device::record_session(app_handle, &signed_in.profile) {
+            log::warn!("Failed to record the sign-in for this device: {}", e);
+        }
+        tauri::async_runtime::spawn(location::record_login_location(
+            app_handle.clone(),
+            signed_in.client_ip.clone(),
+        ));
+    }
+    if let Ok(mut guard) = app_handle.state::<SessionGuardState>().0.lock() {
+        *guard = None;
+    }
+    if let Some(refresh_token) = signed_in.refresh_token.as_deref().filter(|_| !demo) {
+        crate::renewal::remember(app_handle, refresh_token);
+    }
+    let profile = signed_in.profile.clone();
+    *app_handle.state::<SessionState>().0.lock().map_err(|e| e.to_string())? = Some(signed_in);
+    set_auth_session(app_handle, AuthSession::Authenticated);
+    lifecycle::transition(app_handle, AppState::LoggedIn);
+    analytics::track(app_handle, "login");
+    notifications::notify(app_handle, NotificationEvent::Login, "You're signed in");
+    Ok(profile)
+}
+
+// Signs out: ends this app's IDCS session, revokes the session's tokens and the cached client token, then clears
+// the session and caches and emits `logged-out`. Local sign-out always completes; IDCS failures are only logged.
+#[command]
+pub async fn logout(app_handle: AppHandle, auth_client: State<'_, AuthClient>) -> Result<(), CommandError> {
+    Ok(end_session(&app_handle, &auth_client).await?)
+}
+
+pub(crate) async fn end_session(app_handle: &AppHandle, client: &AuthClient) -> Result<(), String> {
+    // Waits for a renewal in flight, so the refresh token revoked below is the latest one
+    let manager = app_handle.state::<crate::token_manager::TokenManager>();
+    let _rotation = manager.begin_rotation().await;
+    // Sign out as the helpdesk user, not the impersonated one
+    crate::impersonation::restore_original(app_handle)?;
+    let session = app_handle.state::<SessionState>().0.lock().map_err(|e| e.to_string())?.take();
+    let guard = app_handle.state::<SessionGuardState>();
+
+    if let Some(session) = session.filter(|session| session.access_token.is_some()) {
+        // The SSO session has to end while the token that may delete it is still valid
+        let access_token = session.access_token.clone().unwrap_or_default();
+        let bearer_token = dpop::authorization(&access_token, session.dpop_key.as_deref());
+        match crate::my_sessions::end_own_session(client, &guard, &bearer_token).await {
+            Ok(Some(id)) => log::info!("Ended IDCS session {}", id),
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to end the IDCS session: {}", e),
+        }
+        let user_tokens = session.access_token.into_iter().chain(session.refresh_token).collect();
+        match revoke_tokens(client, user_tokens).await {
+            Ok(count) => log::info!("Revoked {} tokens", count),
+            Err(e) => log::warn!("Failed to revoke tokens: {}", e),
+        }
+    }
+    // Nothing to revoke for demo sessions, but the client token is still dropped
+    client.take_token();
+    crate::renewal::forget_saved(app_handle);
+
+    if let Ok(mut guard) = guard.0.lock() {
+        *guard = None;
+    }
+    app_handle.state::<CacheState>().invalidate_all();
+    set_auth_session(app_handle, AuthSession::Unauthenticated);
+    lifecycle::transition(app_handle, AppState::LoggedOut);
+    analytics::track(app_handle, "logout");
+    crate::bus::emit(app_handle, "logged-out", ());
+    guest::set(app_handle, false);
+    log::info!("Signed out");
+    Ok(())
+}
+
+// Runs the remaining IDCS steps: completion, token exchange and profile lookup
+pub async fn idcs_complete_auth(
+    app_handle: &AppHandle,
+    client: &AuthClient,
+    request_state: String,
+) -> Result<Value, AuthError> {
+    idcs_complete_session(app_handle, client, request_state).await.map(|session| session.profile)
+}
+
+pub async fn idcs_complete_session(
+    app_handle: &AppHandle,
+    client: &AuthClient,
+    request_state: String,
+) -> Result<Session, AuthError> {
     // Step 1: Get client credentials token
-    println!("Step 1: Getting client credentials token");
-    let client_id = env::var("OCI_CLIENT_ID").map_err(|e| e.to_string())?;
-    let client_secret = env::var("OCI_CLIENT_SECRET").map_err(|e| e.to_string())?;
-    let auth_string = format!("{}:{}", client_id, client_secret);
-    let auth_header = format!("Basic {}", STANDARD.encode(auth_string));
+    tracing::debug!("Step 1: Getting client credentials token");
+    let client_auth = ClientAuth::load()?;
     
-    let token_response = get_client_credentials_token(&auth_header)
+    let token_response = get_client_credentials_token(client, &client_auth)
         .await
         .map_err(|e| {
-            println!("Failed to get client credentials token: {}", e);
+            tracing::debug!("Failed to get client credentials token: {}", e);
             e
         })?;
-    println!("Successfully obtained access token");
+    tracing::debug!("Successfully obtained access token");
 
     // Step 4: Complete authentication
-    println!("Step 4: Completing authentication");
+    tracing::debug!("Step 4: Completing authentication");
     let bearer_token = format!("Bearer {}", token_response.access_token);
-    let complete_url = format!("{}/sso/v1/sdk/authenticate", BASE_URL);
+    let complete_url = client.url("/sso/v1/sdk/authenticate");
 
-    let client = reqwest::Client::new();
     let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
     headers.insert(
         AUTHORIZATION,
         HeaderValue::from_str(&bearer_token).map_err(|e| e.to_string())?,
     );
     headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
-    println!("Making request to URL: {}", complete_url);
-    println!("Request headers: Authorization: Bearer *****, Content-Type: application/json");
-    println!("Request body: {}", serde_json::json!({
+    let started = Instant::now();
+    let complete_request = json!({
         "op": "credSubmit",
         "requestState": request_state
-    }));
-
-    let response = client
-        .post(&complete_url)
-        .headers(headers)
-        .json(&json!({
-            "op": "credSubmit",
-            "requestState": request_state
-        }))
-        .send()
-        .await
-        .map_err(|e| {
-            println!("Failed to complete authentication: {}", e);
-            e.to_string()
-        })?;
-
-    println!("Response status: {}", response.status());
-    println!("Response headers: {:#?}", response.headers());
+    });
+    let span = tracing::debug_span!("complete_authentication", url = %complete_url, status = Empty, elapsed_ms = Empty);
+    span.in_scope(|| {
+        tracing::debug!("Request headers: {}", redact::headers(&headers));
+        tracing::debug!("Request body: {}", Redacted(&complete_request));
+    });
 
-    if !response.status().is_success() {
-        println!("Authentication failed with status: {}", response.status());
-        return Err(format!("Authentication failed with status: {}", response.status()));
-    }
+    let response = circuit::send(
+        client
+            .http()
+            .post(&complete_url)
+            .headers(headers)
+            .json(&complete_request),
+    )
+    .instrument(span.clone())
+    .await
+    .map_err(|e| {
+        tracing::debug!("Failed to complete authentication: {}", e);
+        e
+    })?;
+    span.in_scope(|| record_response(&response, started));
+    let client_ip = client.location_header().and_then(|name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            // Proxies append to the header; the first address is the client's
+            .and_then(|value| value.split(',').next())
+            .map(|ip| ip.trim().to_string())
+    });
 
+    let status = response.status();
     let response_text = response.text().await.map_err(|e| {
-        println!("Failed to get response text: {}", e);
+        tracing::debug!("Failed to get response text: {}", e);
         e.to_string()
     })?;
-    println!("Response body: {}", response_text);
+    tracing::debug!("Response body: {}", redact::body(&response_text));
+    if !status.is_success() {
+        return Err(response_error(status, &response_text).context("Authentication failed"));
+    }
 
     let response_json: serde_json::Value = serde_json::from_str(&response_text)
         .map_err(|e| {
-            println!("Failed to parse response JSON: {}", e);
-            format!("Failed to parse response JSON: {}. Response text: {}", e, response_text)
+            tracing::debug!("Failed to parse response JSON: {}", e);
+            format!("Failed to parse response JSON: {}. Response text: {}", e, redact::body(&response_text))
         })?;
 
     if response_json["status"] != "success" {
-        return Err(format!("Authentication failed: {}", response_text));
+        let message = format!("Authentication failed: {}", redact::body(&response_text));
+        // Completing before the second factor is verified leaves the step pending
+        if response_json["nextAuthFactors"].as_array().is_some_and(|factors| !factors.is_empty()) {
+            return Err(AuthError::MfaRequired { code: None, message });
+        }
+        return Err(AuthError::Rejected { code: None, message });
     }
 
     // Step 5: Exchange token
-    println!("Step 5: Exchanging token for access token");
-    let token_response = get_token_with_assertion(&auth_header, &response_json["authnToken"].as_str().unwrap())
+    tracing::debug!("Step 5: Exchanging token for access token");
+    check_token_endpoint_overrides(app_handle).await?;
+    let authn_token = response_json["authnToken"].as_str().ok_or("IDCS did not return an authn token")?;
+    let dpop_key = if profiles::dpop_enabled() { Some(DpopKey::generate()?) } else { None };
+    let token_response = get_token_with_assertion(client, &client_auth, authn_token, dpop_key.as_deref())
         .await
         .map_err(|e| {
-            println!("Failed to exchange token: {}", e);
+            tracing::debug!("Failed to exchange token: {}", e);
             e
         })?;
     
-    // Step 6: Get user profile
-    println!("Step 6: Getting user profile");
-    let bearer_token = format!("Bearer {}", token_response.access_token);
-    let user_profile = get_user_profile(&bearer_token)
-        .await
-        .map_err(|e| {
-            println!("Failed to get user profile: {}", e);
-            e
-        })?;
-        
-    println!("Successfully retrieved user profile");
-    Ok(user_profile)
+    // Step 6: Get user profile, warming the JWKS and user schema caches alongside it
+    tracing::debug!("Step 6: Getting user profile");
+    if let Some(key) = &dpop_key {
+        key.bind(&token_response.access_token);
+    }
+    let user_bearer_token = dpop::authorization(&token_response.access_token, dpop_key.as_deref());
+    let (user_profile, _, _) = tokio::join!(
+        get_user_profile(app_handle, client, &user_bearer_token),
+        prefetch(app_handle, client, JWKS_KEY, &bearer_token, JWKS_PATH),
+        prefetch(app_handle, client, USER_SCHEMA_KEY, &bearer_token, USER_SCHEMA_PATH),
+    );
+    let user_profile = user_profile.map_err(|e| {
+        tracing::debug!("Failed to get user profile: {}", e);
+        e
+    })?;
+
+    tracing::debug!("Successfully retrieved user profile");
+    Ok(Session {
+        profile: user_profile,
+        expires_at: Some(chrono::Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64)),
+        scopes: Some(ScopeGrant::new(&profiles::user_scope(), token_response.scope.as_deref())),
+        access_token: Some(token_response.access_token),
+        client_ip,
+        dpop_key,
+        refresh_token: token_response.refresh_token,
+    })
+}
+
+// How the active profile's client app authenticates to the token endpoint
+enum ClientAuth {
+    // client_secret_basic: the Basic Authorization header
+    Basic(String),
+    // private_key_jwt: a new assertion is signed for every request, since IDCS rejects reused ones
+    PrivateKey {
+        client_id: String,
+        key_file: String,
+        key_id: String,
+        // Profile overrides of the assertion's `aud` and `iss`
+        audience: Option<String>,
+        issuer: Option<String>,
+    },
+}
+
+impl ClientAuth {
+    fn load() -> Result<Self, String> {
+        if let Some((key_file, key_id)) = profiles::client_assertion_key() {
+            let overrides = profiles::token_endpoint_overrides();
+            return Ok(Self::PrivateKey {
+                client_id: profiles::client_id()?,
+                key_file,
+                key_id,
+                audience: overrides.audience,
+                issuer: overrides.issuer,
+            });
+        }
+        let (client_id, client_secret) = profiles::client_credentials()?;
+        Ok(Self::Basic(format!("Basic {}", STANDARD.encode(format!("{}:{}", client_id, client_secret)))))
+    }
+
+    // Identifies the credentials in the client token cache
+    fn cache_key(&self) -> String {
+        match self {
+            Self::Basic(auth_header) => auth_header.clone(),
+            Self::PrivateKey { client_id, key_file, key_id, .. } => format!("{} {} {}", client_id, key_file, key_id),
+        }
+    }
+
+    fn method(&self) -> &'static str {
+        match self {
+            Self::Basic(_) => "client_secret_basic",
+            Self::PrivateKey { .. } => "private_key_jwt",
+        }
+    }
+
+    // Sends `form` with the client's credentials: in the header, or as assertion fields next to it
+    fn apply(&self, request: reqwest::RequestBuilder, form: &[(&str, &str)]) -> Result<reqwest::RequestBuilder, String> {
+        match self {
+            Self::Basic(auth_header) => Ok(request
+                .header(AUTHORIZATION, HeaderValue::from_str(auth_header).map_err(|e| e.to_string())?)
+                .form(form)),
+            Self::PrivateKey {
+                client_id,
+                key_file,
+                key_id,
+                audience,
+                issuer,
+            } => {
+                let assertion =
+                    crate::client_assertion::sign(client_id, key_file, key_id, audience.as_deref(), issuer.as_deref())?;
+                let mut fields = form.to_vec();
+                fields.extend([
+                    ("client_id", client_id.as_str()),
+                    ("client_assertion_type", crate::client_assertion::ASSERTION_TYPE),
+                    ("client_assertion", assertion.as_str()),
+                ]);
+                Ok(request.form(&fields))
+            }
+        }
+    }
+}
+
+// Sends `form` to the token or revoke endpoint as the active profile's client app. Public clients have no
+// credentials and identify themselves in the form, as in the code exchange.
+pub(crate) fn as_client(
+    request: reqwest::RequestBuilder,
+    form: &[(&str, &str)],
+) -> Result<reqwest::RequestBuilder, String> {
+    if profiles::is_public_client() {
+        let client_id = profiles::client_id()?;
+        let mut fields = form.to_vec();
+        fields.push(("client_id", client_id.as_str()));
+        return Ok(request.form(&fields));
+    }
+    ClientAuth::load()?.apply(request, form)
+}
+
+// Fetches a client credentials token and returns it ready to use as an Authorization header
+pub(crate) async fn client_bearer_token(client: &AuthClient) -> Result<String, String> {
+    let client_auth = ClientAuth::load()?;
+
+    let token_response = get_client_credentials_token(client, &client_auth).await?;
+    Ok(format!("Bearer {}", token_response.access_token))
+}
+
+// Drops the cached client token and requests a new one, exercising the client registration end to end
+pub(crate) async fn refresh_client_bearer_token(client: &AuthClient) -> Result<String, String> {
+    client.take_token();
+    client_bearer_token(client).await
+}
+
+// Each IDCS call of a sign-in runs in a `debug` span carrying its `url`, and the `status` and `elapsed_ms` of the
+// response are recorded on it. No tracing subscriber is installed, so spans and events reach tauri-plugin-log
+// through tracing's `log` feature and `logging.level` governs them like any other record.
+fn record_response(response: &reqwest::Response, started: Instant) {
+    let span = Span::current();
+    span.record("status", response.status().as_u16());
+    span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+    tracing::debug!("Response headers: {}", redact::headers(response.headers()));
+}
+
+// Reuses the cached client token until shortly before it expires
+async fn get_client_credentials_token(client: &AuthClient, client_auth: &ClientAuth) -> Result<TokenResponse, AuthError> {
+    let client_key = client_auth.cache_key();
+    if let Some(token) = client.cached_token(&client_key) {
+        tracing::debug!("Reusing cached client credentials token");
+        return Ok(token);
+    }
+
+    let requested_at = Instant::now();
+    let token = request_client_credentials_token(client, client_auth).await?;
+    client.store_token(client_key, &token, requested_at);
+    Ok(token)
+}
+
+// Revokes the given user tokens and then the cached client token at IDCS, returning how many were revoked. Every
+// token is tried even when an earlier one fails; the failures are reported together.
+pub(crate) async fn revoke_tokens(client: &AuthClient, user_tokens: Vec<String>) -> Result<usize, String> {
+    let client_token = client.take_token().map(|token| token.access_token);
+    let tokens: Vec<String> = user_tokens.into_iter().chain(client_token).collect();
+
+    let mut revoked = 0;
+    let mut failures = Vec::new();
+    for token in &tokens {
+        match revoke_token(client, token).await {
+            Ok(()) => revoked += 1,
+            Err(e) => failures.push(e),
+        }
+    }
+    if !failures.is_empty() {
+        return Err(format!("Revoked {} of {} tokens: {}", revoked, tokens.len(), failures.join("; ")));
+    }
+    Ok(revoked)
+}
+
+async fn revoke_token(client: &AuthClient, token: &str) -> Result<(), String> {
+    let response = circuit::send(as_client(
+        client.http().post(client.url("/oauth2/v1/revoke")),
+        &[("token", token)],
+    )?)
+    .await?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to revoke token: {}", response.status()));
+    }
+    Ok(())
+}
+
+// Warms the shared cache with tenant metadata; failures only cost a later fetch
+pub(crate) async fn prefetch(app_handle: &AppHandle, client: &AuthClient, key: &str, bearer_token: &str, path: &str) {
+    let cache = app_handle.state::<CacheState>();
+    if cache.get::<Value>(key).is_some() {
+        return;
+    }
+    match get_settings(client, bearer_token, path).await {
+        Ok(value) => cache.insert(key, &value, METADATA_TTL),
+        Err(e) => tracing::debug!("Prefetch of {} failed: {}", path, e),
+    }
 }
 
-async fn get_client_credentials_token(auth_header: &str) -> Result<TokenResponse, String> {
-    let client = reqwest::Client::new();
+#[tracing::instrument(
+    name = "client_credentials_token",
+    level = "debug",
+    skip_all,
+    fields(url = %client.url(&profiles::token_path()), status = Empty, elapsed_ms = Empty)
+)]
+async fn request_client_credentials_token(client: &AuthClient, client_auth: &ClientAuth) -> Result<TokenResponse, AuthError> {
     let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(auth_header).map_err(|e| e.to_string())?,
-    );
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
     headers.insert(
         CONTENT_TYPE,
         HeaderValue::from_static("application/x-www-form-urlencoded"),
     );
 
-    println!("Making token request to URL: {}/oauth2/v1/token", BASE_URL);
-    println!("Request headers: Authorization: Basic *****, Content-Type: application/x-www-form-urlencoded");
-    println!("Request form data: grant_type=client_credentials, scope=urn:opc:idm:__myscopes__");
+    tracing::debug!("Request headers: {}", redact::headers(&headers));
+    tracing::debug!("Client authentication: {}", client_auth.method());
+    tracing::debug!("Request form data: grant_type=client_credentials, scope={}", profiles::scope());
 
-    let response = client
-        .post(&format!("{}/oauth2/v1/token", BASE_URL))
-        .headers(headers)
-        .form(&[
+    let started = Instant::now();
+    let response = circuit::send(client_auth.apply(
+        client.http().post(client.url(&profiles::token_path())).headers(headers),
+        &[
             ("grant_type", "client_credentials"),
-            ("scope", "urn:opc:idm:__myscopes__"),
-        ])
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    println!("Response status: {}", response.status());
-    println!("Response headers: {:#?}", response.headers());
+            ("scope", profiles::scope().as_str()),
+        ],
+    )?)
+    .await?;
+    record_response(&response, started);
     
     let status = response.status();
     let response_text = response.text().await.map_err(|e| e.to_string())?;
-    println!("Response body: {}", response_text);
+    tracing::debug!("Response body: {}", redact::body(&response_text));
 
     if !status.is_success() {
-        return Err(format!("Failed to get token: {}", response_text));
+        return Err(response_error(status, &response_text).context("Failed to get token"));
     }
 
     let token_response: TokenResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse token response: {}. Response text: {}", e, response_text))?;
+        .map_err(|e| format!("Failed to parse token response: {}. Response text: {}", e, redact::body(&response_text)))?;
 
     Ok(token_response)
 }
 
-async fn initialize_authentication(bearer_token: &str) -> Result<InitAuthResponse, String> {
-    let client = reqwest::Client::new();
+#[tracing::instrument(
+    name = "initialize_authentication",
+    level = "debug",
+    skip_all,
+    fields(url = %client.url("/sso/v1/sdk/authenticate"), status = Empty, elapsed_ms = Empty)
+)]
+async fn initialize_authentication(client: &AuthClient, bearer_token: &str) -> Result<InitAuthResponse, AuthError> {
     let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
     headers.insert(
         AUTHORIZATION,
         HeaderValue::from_str(bearer_token).map_err(|e| e.to_string())?,
     );
     headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
-    println!("Making auth init request to URL: {}/sso/v1/sdk/authenticate", BASE_URL);
-    println!("Request headers: Authorization: Bearer *****, Content-Type: application/json");
-
-    let response = client
-        .get(&format!("{}/sso/v1/sdk/authenticate", BASE_URL))
-        .headers(headers)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    tracing::debug!("Request headers: {}", redact::headers(&headers));
 
-    println!("Response status: {}", response.status());
-    println!("Response headers: {:#?}", response.headers());
+    let started = Instant::now();
+    let response = circuit::send(
+        client
+            .http()
+            .get(client.url("/sso/v1/sdk/authenticate"))
+            .headers(headers),
+    )
+    .await?;
+    record_response(&response, started);
     
     let status = response.status();
     let response_text = response.text().await.map_err(|e| e.to_string())?;
-    println!("Response body: {}", response_text);
+    tracing::debug!("Response body: {}", redact::body(&response_text));
 
     if !status.is_success() {
-        return Err(format!("Failed to initialize auth: {}", response_text));
+        return Err(response_error(status, &response_text).context("Failed to initialize auth"));
     }
 
     let init_response: InitAuthResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse init response: {}. Response text: {}", e, response_text))?;
+        .map_err(|e| format!("Failed to parse init response: {}. Response text: {}", e, redact::body(&response_text)))?;
 
     Ok(init_response)
 }
 
-async fn get_token_with_assertion(auth_header: &str, authn_token: &str) -> Result<TokenResponse, String> {
-    let client = reqwest::Client::new();
+// With a DPoP key, the user token IDCS issues is bound to it
+#[tracing::instrument(
+    name = "token_exchange",
+    level = "debug",
+    skip_all,
+    fields(url = %client.url(&profiles::token_path()), status = Empty, elapsed_ms = Empty)
+)]
+async fn get_token_with_assertion(
+    client: &AuthClient,
+    client_auth: &ClientAuth,
+    authn_token: &str,
+    dpop_key: Option<&DpopKey>,
+) -> Result<TokenResponse, AuthError> {
     let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(auth_header).map_err(|e| e.to_string())?,
-    );
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
+    if let Some((name, thumbprint)) = dpop_key.map(DpopKey::token_request_header) {
+        headers.insert(name, HeaderValue::from_str(thumbprint).map_err(|e| e.to_string())?);
+    }
     headers.insert(
         CONTENT_TYPE,
         HeaderValue::from_static("application/x-www-form-urlencoded"),
     );
 
-    println!("Making token exchange request to URL: {}/oauth2/v1/token", BASE_URL);
-    println!("Request headers: Authorization: Basic *****, Content-Type: application/x-www-form-urlencoded");
-    println!("Request form data: grant_type=urn:ietf:params:oauth:grant-type:jwt-bearer, scope=urn:opc:idm:__myscopes__, assertion=*****");
+    tracing::debug!("Request headers: {}", redact::headers(&headers));
+    tracing::debug!("Client authentication: {}", client_auth.method());
+    tracing::debug!("Request form data: grant_type=urn:ietf:params:oauth:grant-type:jwt-bearer, scope={}, assertion={}", profiles::user_scope(), redact::REDACTED);
 
-    let response = client
-        .post(&format!("{}/oauth2/v1/token", BASE_URL))
-        .headers(headers)
-        .form(&[
+    let started = Instant::now();
+    let response = circuit::send(client_auth.apply(
+        client.http().post(client.url(&profiles::token_path())).headers(headers),
+        &[
             (
                 "grant_type",
                 "urn:ietf:params:oauth:grant-type:jwt-bearer",
             ),
-            ("scope", "urn:opc:idm:__myscopes__"),
+            ("scope", profiles::user_scope().as_str()),
             ("assertion", authn_token),
-        ])
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    println!("Response status: {}", response.status());
-    println!("Response headers: {:#?}", response.headers());
+        ],
+    )?)
+    .await?;
+    record_response(&response, started);
     
     let status = response.status();
     let response_text = response.text().await.map_err(|e| e.to_string())?;
-    println!("Response body: {}", response_text);
+    tracing::debug!("Response body: {}", redact::body(&response_text));
 
     if !status.is_success() {
-        return Err(format!("Failed to get token: {}", response_text));
+        return Err(response_error(status, &response_text).context("Failed to get token"));
     }
 
     let token_response: TokenResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse token response: {}. Response text: {}", e, response_text))?;
+        .map_err(|e| format!("Failed to parse token response: {}. Response text: {}", e, redact::body(&response_text)))?;
 
     Ok(token_response)
 }
 
-async fn get_user_profile(bearer_token: &str) -> Result<Value, String> {
-    let client = reqwest::Client::new();
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        HeaderValue::from_str(bearer_token).map_err(|e| e.to_string())?,
-    );
-
-    println!("Making user profile request to URL: {}/admin/v1/Me", BASE_URL);
-    println!("Request headers: Authorization: Bearer *****, Content-Type: application/json");
+// Checks the profile's assertion audience and token path against the tenant's discovery document, so a typo
+// fails with a clear error instead of a rejected assertion. Skipped when discovery can't be read.
+async fn check_token_endpoint_overrides(app_handle: &AppHandle) -> Result<(), AuthError> {
+    let overrides = profiles::token_endpoint_overrides();
+    if overrides.audience.is_none() && overrides.token_path.is_none() {
+        return Ok(());
+    }
+    let discovery = match crate::warmup::discovery(app_handle).await {
+        Ok(discovery) => discovery,
+        Err(e) => {
+            log::warn!("Could not check the token endpoint overrides against discovery: {}", e);
+            return Ok(());
+        }
+    };
+    let token_endpoint = discovery["token_endpoint"].as_str().unwrap_or_default();
+    if let Some(path) = &overrides.token_path {
+        let advertised = reqwest::Url::parse(token_endpoint).map(|url| url.path().to_string());
+        if advertised.is_ok_and(|advertised| advertised != *path) {
+            return Err(AuthError::NotConfigured {
+                message: format!("The profile's token path {} doesn't match the tenant's token endpoint {}", path, token_endpoint),
+            });
+        }
+    }
+    if let Some(audience) = &overrides.audience {
+        let issuer = discovery["issuer"].as_str().unwrap_or_default();
+        if ![issuer, token_endpoint, crate::client_assertion::AUDIENCE].contains(&audience.as_str()) {
+            return Err(AuthError::NotConfigured {
+                message: format!(
+                    "The profile's assertion audience {} is neither the tenant's issuer {} nor its token endpoint",
+                    audience, issuer
+                ),
+            });
+        }
+    }
+    Ok(())
+}
 
-    let response = client
-        .get(&format!("{}/admin/v1/Me", BASE_URL))
-        .headers(headers)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+// Redeems a session's refresh token for a new access token. DPoP-bound sessions present their key again, since
+// IDCS binds the refresh token to it.
+pub(crate) async fn refresh_user_token(
+    client: &AuthClient,
+    refresh_token: &str,
+    dpop_key: Option<&DpopKey>,
+) -> Result<TokenResponse, AuthError> {
+    let mut request = client
+        .http()
+        .post(client.url(&profiles::token_path()))
+        .header(ACCEPT_LANGUAGE, i18n::accept_language());
+    if let Some((name, thumbprint)) = dpop_key.map(DpopKey::token_request_header) {
+        request = request.header(name, thumbprint);
+    }
+    let form = [("grant_type", "refresh_token"), ("refresh_token", refresh_token)];
+    let response = circuit::send(as_client(request, &form)?).await?;
 
-    println!("Response status: {}", response.status());
-    println!("Response headers: {:#?}", response.headers());
-    
     let status = response.status();
     let response_text = response.text().await.map_err(|e| e.to_string())?;
-    println!("Response body: {}", response_text);
-
     if !status.is_success() {
-        return Err(format!("Failed to get user profile: {}", response_text));
+        return Err(response_error(status, &response_text).context("Failed to renew the session"));
     }
+    Ok(serde_json::from_str(&response_text).map_err(|e| format!("Failed to parse token response: {}", e))?)
+}
 
-    let profile: Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse profile response: {}. Response text: {}", e, response_text))?;
+// Exchanges a user access token for a UPST bound to `public_key`, the base64 DER of an RSA public key. OCI API
+// calls made with the UPST must be signed with the matching private key.
+pub(crate) async fn exchange_for_upst(
+    client: &AuthClient,
+    access_token: &str,
+    public_key: &str,
+) -> Result<String, AuthError> {
+    let request = client
+        .http()
+        .post(client.url(&profiles::token_path()))
+        .header(ACCEPT_LANGUAGE, i18n::accept_language());
+    let form = [
+        ("grant_type", TOKEN_EXCHANGE_GRANT),
+        ("requested_token_type", UPST_TOKEN_TYPE),
+        ("subject_token", access_token),
+        ("subject_token_type", "jwt"),
+        ("public_key", public_key),
+    ];
+    let response = circuit::send(as_client(request, &form)?).await?;
 
-    Ok(profile)
+    let status = response.status();
+    let response_text = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(response_error(status, &response_text).context("Failed to get an OCI session token"));
+    }
+    let body: Value =
+        serde_json::from_str(&response_text).map_err(|e| format!("Failed to parse token response: {}", e))?;
+    Ok(body["token"].as_str().ok_or("IDCS returned no OCI session token")?.to_string())
+}
+
+// Revalidates the cached profile with If-None-Match, so an unchanged profile costs a 304
+#[tracing::instrument(name = "user_profile", level = "debug", skip_all, fields(url = %format!("{}/admin/v1/Me", base_url())))]
+pub(crate) async fn get_user_profile(
+    app_handle: &AppHandle,
+    client: &AuthClient,
+    bearer_token: &str,
+) -> Result<Value, String> {
+    // A guest's profile isn't cached on disk
+    if guest::is_active(app_handle) {
+        return match get_scim(client, bearer_token, "/admin/v1/Me", None).await? {
+            ScimResponse::Modified { value, .. } => Ok(value),
+            ScimResponse::NotModified => Err("Unexpected 304 for /admin/v1/Me".to_string()),
+        };
+    }
+    get_scim_cached(app_handle, client, PROFILE_KEY, bearer_token, "/admin/v1/Me", PROFILE_MAX_AGE).await
 }
@@ -0,0 +1,179 @@
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::auth::TokenResponse;
+use crate::config::NetworkConfig;
+use crate::endpoints::base_url;
+use crate::profiles;
+
+// Connections to IDCS are kept this long between sign-in steps
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+// Refetch the client token this long before IDCS would reject it
+const TOKEN_LEEWAY: Duration = Duration::from_secs(60);
+
+struct CachedToken {
+    // `ClientAuth::cache_key` of the credentials it was issued to
+    client_key: String,
+    token: TokenResponse,
+    expires_at: Instant,
+}
+
+// The HTTP client every step of the sign-in flow shares, so connections to IDCS are pooled, configured from
+// `network`. Built at startup, and again when the active profile changes so it presents that profile's client
+// certificate or the proxy settings change; other changes to the network settings apply after a restart.
+#[derive(Clone)]
+pub struct AuthClient {
+    network: Arc<RwLock<NetworkConfig>>,
+    http: Arc<RwLock<reqwest::Client>>,
+    // For URLs outside IDCS, e.g. a branding logo on a CDN: same network settings, but without the client
+    // certificate or the device thumbprint
+    external: Arc<RwLock<reqwest::Client>>,
+    // Header on the authenticate response carrying the client's public IP, from `network.location_header`
+    location_header: Option<String>,
+    // Client credentials token shared by every command; it doesn't depend on the user. Clones share it.
+    token: Arc<Mutex<Option<CachedToken>>>,
+}
+
+impl AuthClient {
+    pub fn new(network: &NetworkConfig) -> Result<Self, String> {
+        Ok(Self {
+            network: Arc::new(RwLock::new(network.clone())),
+            http: Arc::new(RwLock::new(build(network, None)?)),
+            external: Arc::new(RwLock::new(build_external(network)?)),
+            location_header: network.location_header.clone(),
+            token: Arc::default(),
+        })
+    }
+
+    // Falls back to the defaults when the configured settings can't be used, so sign-in still works
+    pub fn from_config(network: &NetworkConfig) -> Self {
+        Self::new(network).unwrap_or_else(|e| {
+            log::error!("{}; using the default network settings", e);
+            Self::new(&NetworkConfig::default()).unwrap_or_else(|_| Self {
+                network: Arc::default(),
+                http: Arc::new(RwLock::new(reqwest::Client::new())),
+                external: Arc::new(RwLock::new(reqwest::Client::new())),
+                location_header: None,
+                token: Arc::default(),
+            })
+        })
+    }
+
+    // Cheap to call per request: clones share the connection pool
+    pub fn http(&self) -> reqwest::Client {
+        match self.http.read() {
+            Ok(http) => http.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    // The client for requests that don't go to IDCS
+    pub fn external(&self) -> reqwest::Client {
+        match self.external.read() {
+            Ok(external) => external.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    // Rebuilds the client for the active profile, with its client certificate for tenants that require mutual
+    // TLS. Called by `profiles::activate`; the old connections close once requests using them finish.
+    pub(crate) fn load_profile(&self) -> Result<(), String> {
+        let network = self.network.read().map_err(|e| e.to_string())?.clone();
+        self.rebuild(&network)
+    }
+
+    // Switches to new proxy settings; `network::set_network_settings` calls this after saving them
+    pub(crate) fn set_network(&self, network: &NetworkConfig) -> Result<(), String> {
+        self.rebuild(network)?;
+        *self.network.write().map_err(|e| e.to_string())? = network.clone();
+        Ok(())
+    }
+
+    fn rebuild(&self, network: &NetworkConfig) -> Result<(), String> {
+        let identity = match profiles::client_certificate() {
+            Some((file, passphrase)) => Some(load_identity(&file, &passphrase)?),
+            None => None,
+        };
+        let http = build(network, identity)?;
+        let external = build_external(network)?;
+        *self.http.write().map_err(|e| e.to_string())? = http;
+        *self.external.write().map_err(|e| e.to_string())? = external;
+        Ok(())
+    }
+
+    // URL of an IDCS path on the tenant URL in use; the HTTP layer fails over to the others
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", base_url(), path)
+    }
+
+    pub fn location_header(&self) -> Option<&str> {
+        self.location_header.as_deref()
+    }
+
+    // The cached client token for these credentials, unless it is about to expire
+    pub(crate) fn cached_token(&self, client_key: &str) -> Option<TokenResponse> {
+        let cached = self.token.lock().ok()?;
+        cached
+            .as_ref()
+            .filter(|cached| cached.client_key == client_key && cached.expires_at > Instant::now() + TOKEN_LEEWAY)
+            .map(|cached| cached.token.clone())
+    }
+
+    // `requested_at` is when the token request was sent, since `expires_in` counts from when IDCS issued it
+    pub(crate) fn store_token(&self, client_key: String, token: &TokenResponse, requested_at: Instant) {
+        if let Ok(mut cached) = self.token.lock() {
+            *cached = Some(CachedToken {
+                client_key,
+                token: token.clone(),
+                expires_at: requested_at + Duration::from_secs(token.expires_in as u64),
+            });
+        }
+    }
+
+    // Forgets the cached client token, returning it so it can be revoked
+    pub(crate) fn take_token(&self) -> Option<TokenResponse> {
+        self.token.lock().ok()?.take().map(|cached| cached.token)
+    }
+}
+
+fn build(network: &NetworkConfig, identity: Option<reqwest::Identity>) -> Result<reqwest::Client, String> {
+    let mut builder = builder(network)?;
+    if let Some(identity) = identity {
+        builder = builder.identity(identity);
+    }
+    if let Some(thumbprint) = crate::device::thumbprint() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            crate::device::HEADER,
+            reqwest::header::HeaderValue::from_str(thumbprint).map_err(|e| e.to_string())?,
+        );
+        builder = builder.default_headers(headers);
+    }
+    builder.build().map_err(|e| format!("Failed to build the HTTP client: {}", e))
+}
+
+fn build_external(network: &NetworkConfig) -> Result<reqwest::Client, String> {
+    builder(network)?
+        .build()
+        .map_err(|e| format!("Failed to build the HTTP client: {}", e))
+}
+
+// Timeouts, TLS and proxy settings shared by both clients
+fn builder(network: &NetworkConfig) -> Result<reqwest::ClientBuilder, String> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(network.timeout_seconds.max(1)))
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .user_agent(format!("{}/{}", crate::branding::APP_NAME, env!("CARGO_PKG_VERSION")));
+    builder = crate::network::tls(builder, network)?;
+    if let Some(proxy) = crate::network::proxy(network)? {
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder)
+}
+
+// PKCS#12 bundle with the client certificate and its private key
+fn load_identity(file: &str, passphrase: &str) -> Result<reqwest::Identity, String> {
+    let der = std::fs::read(file).map_err(|e| format!("Failed to read client certificate {}: {}", file, e))?;
+    reqwest::Identity::from_pkcs12_der(&der, passphrase)
+        .map_err(|e| format!("Invalid client certificate {}: {}", file, e))
+}
@@ -0,0 +1,303 @@
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreBuilder;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::paths;
+use crate::platform;
+use crate::profiles::{self, PENDING_REFRESH_TOKEN_ENTRY, REFRESH_TOKEN_ENTRY};
+
+// A backup file is this header, the salt, the nonce and the encrypted archive. The header is also authenticated,
+// so a file can't be passed off as another format.
+const MAGIC: &[u8] = b"OCI-AUTH-BACKUP\0";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+// Format of the archive inside; newer formats are refused
+const FORMAT: u32 = 1;
+const MIN_PASSPHRASE_LEN: usize = 12;
+// Long enough for the restore result to reach the window before the app restarts
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy)]
+enum Location {
+    Data,
+    LocalData,
+}
+
+// Settings and history worth taking to another machine. Caches, logs, captures, the window position and this
+// device's binding stay behind.
+const STORES: &[(Location, &str)] = &[
+    (Location::Data, "config.json"),
+    (Location::Data, "profiles.json"),
+    (Location::Data, "access-snapshots.json"),
+    (Location::Data, "audit.json"),
+    (Location::Data, "features.json"),
+    (Location::Data, "motd.json"),
+    (Location::LocalData, "verification.json"),
+];
+
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    format: u32,
+    created_at: DateTime<Utc>,
+    app_version: String,
+    // Contents of each store, by file name
+    stores: BTreeMap<String, Map<String, Value>>,
+    // Secrets by keychain account
+    keychain: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub created_at: DateTime<Utc>,
+    pub app_version: String,
+    pub stores: Vec<String>,
+    pub keychain_entries: usize,
+    pub includes_sessions: bool,
+}
+
+impl BackupSummary {
+    fn of(archive: &Archive) -> Self {
+        BackupSummary {
+            created_at: archive.created_at,
+            app_version: archive.app_version.clone(),
+            stores: archive.stores.keys().cloned().collect(),
+            keychain_entries: archive.keychain.len(),
+            includes_sessions: archive.keychain.keys().any(|account| is_session_entry(account)),
+        }
+    }
+}
+
+// Writes the settings, login history and saved client credentials to one file encrypted with `passphrase`, for
+// moving to another machine or keeping across a reimage. Saved sessions are only included with
+// `include_sessions`; this device's binding key never is, since a new machine must enroll as itself.
+#[command(unlocked)]
+pub fn backup_app_data(
+    app_handle: AppHandle,
+    path: String,
+    passphrase: String,
+    include_sessions: Option<bool>,
+) -> Result<BackupSummary, CommandError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("Use a passphrase of at least {} characters", MIN_PASSPHRASE_LEN).into());
+    }
+    let include_sessions = include_sessions.unwrap_or(false);
+
+    let mut stores = BTreeMap::new();
+    for (location, name) in STORES {
+        let path = store_path(&app_handle, *location, name)?;
+        if !path.exists() {
+            continue;
+        }
+        let store = StoreBuilder::new(&app_handle, &path).build().map_err(|e| e.to_string())?;
+        stores.insert(name.to_string(), store.entries().into_iter().collect());
+    }
+    let mut keychain = BTreeMap::new();
+    for (account, _) in profiles::saved_keychain_entries(&app_handle) {
+        if account == crate::device::KEYCHAIN_ACCOUNT || (is_session_entry(&account) && !include_sessions) {
+            continue;
+        }
+        if let Some(secret) = platform::keychain_get(&account)? {
+            keychain.insert(account, secret);
+        }
+    }
+
+    let archive = Archive {
+        format: FORMAT,
+        created_at: Utc::now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        stores,
+        keychain,
+    };
+    let plaintext = serde_json::to_vec(&archive).map_err(|e| e.to_string())?;
+    let file = encrypt(&plaintext, &passphrase)?;
+    write_file(Path::new(&path), &file)?;
+
+    let summary = BackupSummary::of(&archive);
+    log::info!(
+        "Backed up {} stores and {} keychain entries to {}",
+        summary.stores.len(),
+        summary.keychain_entries,
+        path
+    );
+    if let Err(e) = crate::audit::record(
+        &app_handle,
+        "app_data_backed_up",
+        serde_json::json!({ "stores": summary.stores, "includes_sessions": summary.includes_sessions }),
+    ) {
+        log::warn!("Failed to record the backup in the audit log: {}", e);
+    }
+    Ok(summary)
+}
+
+// Replaces the settings, history and saved credentials with those in a backup, then restarts the app so every
+// part of it picks them up. `app-data-restored` is emitted with the summary just before the restart.
+#[command(unlocked)]
+pub fn restore_app_data(app_handle: AppHandle, path: String, passphrase: String) -> Result<BackupSummary, CommandError> {
+    let file = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let plaintext = decrypt(&file, &passphrase)?;
+    let archive: Archive =
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Failed to parse the backup: {}", e))?;
+    if archive.format > FORMAT {
+        return Err(format!(
+            "The backup was made by a newer version of the app ({}); update before restoring it",
+            archive.app_version
+        )
+        .into());
+    }
+    // Only ever written to the files and accounts a backup is made of, whatever the archive names
+    for name in archive.stores.keys() {
+        if !STORES.iter().any(|(_, known)| known == name) {
+            return Err(format!("The backup contains an unexpected file: {}", name).into());
+        }
+    }
+    let known_accounts: Vec<String> = profiles::saved_keychain_entries(&app_handle)
+        .into_iter()
+        .map(|(account, _)| account)
+        .collect();
+    for account in archive.keychain.keys() {
+        if account == crate::device::KEYCHAIN_ACCOUNT || !is_profile_entry(account) {
+            return Err(format!("The backup contains an unexpected keychain entry: {}", account).into());
+        }
+    }
+
+    for (location, name) in STORES {
+        let Some(entries) = archive.stores.get(*name) else {
+            continue;
+        };
+        let path = store_path(&app_handle, *location, name)?;
+        // Through the store plugin, so the copy it holds in memory matches and isn't saved over the restored one
+        let store = StoreBuilder::new(&app_handle, &path).build().map_err(|e| e.to_string())?;
+        store.clear();
+        for (key, value) in entries {
+            store.set(key.clone(), value.clone());
+        }
+        store.save().map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+    }
+    // Sessions saved on this machine would otherwise outlive a backup made without them
+    for account in known_accounts.iter().filter(|account| is_session_entry(account)) {
+        if !archive.keychain.contains_key(account) {
+            platform::keychain_delete(account)?;
+        }
+    }
+    for (account, secret) in &archive.keychain {
+        platform::keychain_set(account, secret)?;
+    }
+
+    let summary = BackupSummary::of(&archive);
+    log::info!(
+        "Restored {} stores and {} keychain entries from a backup made {}; restarting",
+        summary.stores.len(),
+        summary.keychain_entries,
+        summary.created_at
+    );
+    crate::bus::emit(&app_handle, "app-data-restored", &summary);
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RESTART_DELAY).await;
+        handle.restart();
+    });
+    Ok(summary)
+}
+
+fn store_path(app_handle: &AppHandle, location: Location, name: &str) -> Result<PathBuf, String> {
+    let dir = match location {
+        Location::Data => paths::data_dir(app_handle)?,
+        Location::LocalData => paths::local_data_dir(app_handle)?,
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(name))
+}
+
+// `<profile>/<entry>` accounts of the profiles' keychain entries
+fn is_profile_entry(account: &str) -> bool {
+    account.split_once('/').is_some_and(|(profile, entry)| !profile.is_empty() && !entry.is_empty())
+}
+
+fn is_session_entry(account: &str) -> bool {
+    [REFRESH_TOKEN_ENTRY, PENDING_REFRESH_TOKEN_ENTRY]
+        .iter()
+        .any(|entry| account.ends_with(&format!("/{}", entry)))
+}
+
+// Argon2id, so a stolen backup can't be guessed at quickly
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm, String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive the backup key: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| e.to_string())
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt).map_err(|e| e.to_string())?;
+    getrandom::getrandom(&mut nonce).map_err(|e| e.to_string())?;
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: MAGIC })
+        .map_err(|_| "Failed to encrypt the backup".to_string())?;
+    Ok([MAGIC, &salt, &nonce, &ciphertext].concat())
+}
+
+fn decrypt(file: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if file.len() < header_len || !file.starts_with(MAGIC) {
+        return Err("Not a backup made by this app".to_string());
+    }
+    let (salt, rest) = file[MAGIC.len()..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    cipher(passphrase, salt)?
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: MAGIC })
+        .map_err(|_| "Wrong passphrase, or the backup is damaged".to_string())
+}
+
+// Through a temporary file, so an interrupted backup doesn't replace a good one with half a file
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    std::fs::rename(&partial, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    #[test]
+    fn decrypt_round_trips_encrypt() {
+        let file = encrypt(b"{\"format\":1}", PASSPHRASE).unwrap();
+        assert!(file.starts_with(MAGIC));
+        assert_eq!(decrypt(&file, PASSPHRASE).unwrap(), b"{\"format\":1}");
+    }
+
+    #[test]
+    fn decrypt_rejects_a_wrong_passphrase() {
+        let file = encrypt(b"{\"format\":1}", PASSPHRASE).unwrap();
+        assert!(decrypt(&file, "incorrect horse battery").is_err());
+    }
+
+    #[test]
+    fn decrypt_rejects_a_damaged_file() {
+        let mut file = encrypt(b"{\"format\":1}", PASSPHRASE).unwrap();
+        let last = file.len() - 1;
+        file[last] ^= 1;
+        assert!(decrypt(&file, PASSPHRASE).is_err());
+    }
+
+    #[test]
+    fn decrypt_rejects_other_files() {
+        assert!(decrypt(b"{\"format\":1}", PASSPHRASE).is_err());
+        assert!(decrypt(MAGIC, PASSPHRASE).is_err());
+    }
+}
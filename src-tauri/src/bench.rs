@@ -0,0 +1,89 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+use crate::auth::AuthResponse;
+use crate::demo::DemoState;
+use crate::redact::redact;
+
+const BENCH_USERNAME: &str = "demo.user";
+const BENCH_PASSWORD: &str = "Welcome1!";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepTiming {
+    pub step: String,
+    pub iterations: u32,
+    pub total_us: u128,
+    pub mean_us: f64,
+}
+
+// Steps of a mock login, kept separate so criterion and `--bench-auth` measure the same code
+pub fn initiate(demo: &DemoState) -> AuthResponse {
+    demo.initiate(BENCH_USERNAME, BENCH_PASSWORD).expect("demo login failed")
+}
+
+pub fn complete(demo: &DemoState, request_state: &str) -> Value {
+    demo.complete(request_state).expect("demo completion failed")
+}
+
+pub fn serialize_response(response: &AuthResponse) -> String {
+    serde_json::to_string(response).expect("response serialization failed")
+}
+
+// Tauri serializes event payloads to JSON before handing them to the webview
+pub fn event_payload(profile: &Value) -> String {
+    serde_json::to_string(&json!({ "event": "auth-completed", "payload": profile }))
+        .expect("event serialization failed")
+}
+
+// Runs the mock login `iterations` times and reports the time spent in each step
+pub fn run_auth_benchmark(iterations: u32) -> Vec<StepTiming> {
+    let demo = DemoState::new(true);
+    let mut totals = [Duration::ZERO; 5];
+
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let response = initiate(&demo);
+        totals[0] += start.elapsed();
+
+        let start = Instant::now();
+        let serialized = serialize_response(&response);
+        totals[1] += start.elapsed();
+
+        let start = Instant::now();
+        let mut logged: Value = serde_json::from_str(&serialized).expect("response round trip failed");
+        redact(&mut logged);
+        totals[2] += start.elapsed();
+
+        let start = Instant::now();
+        let profile = complete(&demo, &response.request_state);
+        totals[3] += start.elapsed();
+
+        let start = Instant::now();
+        std::hint::black_box(event_payload(&profile));
+        totals[4] += start.elapsed();
+    }
+
+    ["initiate", "serialize", "redact", "complete", "emit"]
+        .iter()
+        .zip(totals)
+        .map(|(step, total)| StepTiming {
+            step: step.to_string(),
+            iterations,
+            total_us: total.as_micros(),
+            mean_us: total.as_secs_f64() * 1_000_000.0 / iterations.max(1) as f64,
+        })
+        .collect()
+}
+
+pub fn print_report(timings: &[StepTiming]) {
+    println!("{:<12} {:>10} {:>14} {:>12}", "step", "iterations", "total (us)", "mean (us)");
+    for timing in timings {
+        println!(
+            "{:<12} {:>10} {:>14} {:>12.2}",
+            timing.step, timing.iterations, timing.total_us, timing.mean_us
+        );
+    }
+    let mean: f64 = timings.iter().map(|timing| timing.mean_us).sum();
+    println!("{:<12} {:>10} {:>14} {:>12.2}", "login", "", "", mean);
+}
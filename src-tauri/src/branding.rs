@@ -0,0 +1,48 @@
+use tauri::image::Image;
+use tauri::{AppHandle, Manager};
+
+// White-label values compiled in by build.rs from `branding.toml` (or `$BRANDING_FILE`).
+// Anything the file leaves out keeps the stock OCI Auth branding.
+pub const APP_NAME: &str = or(option_env!("BRANDING_APP_NAME"), "OCI Auth Tauri");
+pub const DEFAULT_IDCS_URL: &str = or(
+    option_env!("BRANDING_IDCS_URL"),
+    "https://idcs-8e8265d058d54299bdc845382c75339f.identity.oraclecloud.com",
+);
+// Newlines arrive escaped, since build script env vars are single-line
+const ABOUT: &str = or(
+    option_env!("BRANDING_ABOUT"),
+    "A Tauri authentication app for Oracle Cloud Infrastructure.\\n\\n 2025 OCI Auth Team",
+);
+
+#[cfg(branding_icon)]
+const ICON: Option<&[u8]> = Some(include_bytes!(env!("BRANDING_ICON")));
+#[cfg(not(branding_icon))]
+const ICON: Option<&[u8]> = None;
+
+const fn or(value: Option<&'static str>, default: &'static str) -> &'static str {
+    match value {
+        Some(value) => value,
+        None => default,
+    }
+}
+
+pub fn about_text() -> String {
+    format!("{}\nVersion 1.0.0\n\n{}", APP_NAME, ABOUT.replace("\\n", "\n"))
+}
+
+// The branded PNG icon, if the build has one; otherwise callers fall back to the bundle icon
+pub fn icon() -> Option<Image<'static>> {
+    ICON.and_then(|bytes| Image::from_bytes(bytes).ok())
+}
+
+// Titles the main window and swaps in the branded icon
+pub fn apply(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return Ok(());
+    };
+    window.set_title(APP_NAME).map_err(|e| e.to_string())?;
+    if let Some(icon) = icon() {
+        window.set_icon(icon).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
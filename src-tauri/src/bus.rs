@@ -0,0 +1,71 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::lifecycle::AppState;
+
+// What the core has to tell the Tauri layer; `forward` turns each into webview events and UI updates
+#[derive(Debug, Clone)]
+pub enum CoreEvent {
+    // The menu is rebuilt and `app-state-changed` emitted
+    AppStateChanged(AppState),
+    // Passed on to the webview as it is
+    Frontend { name: &'static str, payload: Value },
+}
+
+// Sending end of the bus between the core and the Tauri layer. It holds no Tauri type, so core state can keep one
+// instead of an `AppHandle` for publishing; only the lifecycle state and the circuit breaker do so far. Clones send
+// to the same receiver.
+#[derive(Clone)]
+pub struct EventBus(UnboundedSender<CoreEvent>);
+
+impl EventBus {
+    // Events sent before the receiver is read wait in it, so nothing is lost while the app starts
+    pub fn new() -> (Self, UnboundedReceiver<CoreEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self(sender), receiver)
+    }
+
+    pub fn publish(&self, event: CoreEvent) {
+        if let Err(e) = self.0.send(event) {
+            log::warn!("The event bus is closed, dropping {:?}", e.0);
+        }
+    }
+
+    pub fn emit(&self, name: &'static str, payload: impl Serialize) {
+        match serde_json::to_value(payload) {
+            Ok(payload) => self.publish(CoreEvent::Frontend { name, payload }),
+            Err(e) => log::warn!("Failed to serialize {}: {}", name, e),
+        }
+    }
+}
+
+// For code that reaches the bus through the managed state
+pub fn emit(app_handle: &AppHandle, name: &'static str, payload: impl Serialize) {
+    match app_handle.try_state::<EventBus>() {
+        Some(bus) => bus.emit(name, payload),
+        None => log::warn!("No event bus yet, dropping {}", name),
+    }
+}
+
+// The Tauri end of the bus and the one place events reach the webview; runs until every sender is gone
+pub async fn forward(app_handle: AppHandle, mut events: UnboundedReceiver<CoreEvent>) {
+    while let Some(event) = events.recv().await {
+        match event {
+            CoreEvent::AppStateChanged(state) => {
+                if let Err(e) = crate::menu::refresh_menu(app_handle.clone()) {
+                    log::warn!("Failed to refresh the menu: {}", e);
+                }
+                if let Err(e) = app_handle.emit("app-state-changed", state) {
+                    log::warn!("Failed to emit app-state-changed: {}", e);
+                }
+            }
+            CoreEvent::Frontend { name, payload } => {
+                if let Err(e) = app_handle.emit(name, payload) {
+                    log::warn!("Failed to emit {}: {}", name, e);
+                }
+            }
+        }
+    }
+}
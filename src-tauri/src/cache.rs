@@ -0,0 +1,361 @@
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::CommandError;
+use crate::middleware::command;
+
+const DEFAULT_CAPACITY: usize = 256;
+const DISK_CACHE_DIR: &str = "cache";
+
+// Keys are namespaced so related entries can be dropped together, e.g. "user:<id>", "directory:<query>:<page>"
+pub const BRANDING_KEY: &str = "branding";
+pub const PROFILE_KEY: &str = "profile";
+pub const JWKS_KEY: &str = "jwks";
+pub const DISCOVERY_KEY: &str = "discovery";
+pub const USER_SCHEMA_KEY: &str = "user-schema";
+pub const LOGIN_HINTS_KEY: &str = "login-hints";
+pub const USER_PREFIX: &str = "user:";
+pub const DIRECTORY_PREFIX: &str = "directory:";
+
+struct CacheEntry {
+    value: Value,
+    expires_at: Instant,
+    last_used: u64,
+    // Serialized length, a cheap stand-in for memory use
+    size: usize,
+}
+
+// In-memory LRU with per-entry TTL for read-only IDCS data
+pub struct LruCache {
+    capacity: usize,
+    entries: HashMap<String, CacheEntry>,
+    clock: u64,
+}
+
+#[derive(Default)]
+pub struct CacheState(pub Mutex<LruCache>);
+
+impl Default for LruCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl LruCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<Value> {
+        self.clock += 1;
+        let clock = self.clock;
+        match self.entries.get_mut(key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                entry.last_used = clock;
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&mut self, key: &str, value: Value, ttl: Duration) {
+        self.clock += 1;
+        if !self.entries.contains_key(key) && self.entries.len() >= self.capacity {
+            self.evict();
+        }
+        let size = key.len() + value.to_string().len();
+        self.entries.insert(
+            key.to_string(),
+            CacheEntry {
+                value,
+                expires_at: Instant::now() + ttl,
+                last_used: self.clock,
+                size,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    pub fn invalidate_prefix(&mut self, prefix: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|key, _| !key.starts_with(prefix));
+        before - self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    // Largest entries first, as (key, bytes)
+    pub fn top_consumers(&self, count: usize) -> Vec<(String, usize)> {
+        let mut sizes: Vec<(String, usize)> = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.size))
+            .collect();
+        sizes.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        sizes.truncate(count);
+        sizes
+    }
+
+    // Evicts until the cache fits in `max_bytes`; returns the number of entries removed
+    pub fn trim_to(&mut self, max_bytes: usize) -> usize {
+        let before = self.entries.len();
+        while self.size_bytes() > max_bytes && !self.entries.is_empty() {
+            self.evict();
+        }
+        before - self.entries.len()
+    }
+
+    // Expired entries go first, otherwise the least recently used one
+    fn evict(&mut self) {
+        let now = Instant::now();
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| entry.expires_at > now);
+        if self.entries.len() < before {
+            return;
+        }
+
+        if let Some(key) = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone())
+        {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+impl CacheState {
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.0.lock().ok()?.get(key)?;
+        serde_json::from_value(value).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        if let (Ok(mut cache), Ok(value)) = (self.0.lock(), serde_json::to_value(value)) {
+            cache.insert(key, value, ttl);
+        }
+    }
+
+    // Returns the cached value, or runs `fetch` and caches its result
+    pub async fn get_or_fetch<T, F, Fut>(&self, key: &str, ttl: Duration, fetch: F) -> Result<T, String>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        if let Some(value) = self.get(key) {
+            log::trace!("Cache hit for {}", key);
+            return Ok(value);
+        }
+
+        let value = fetch().await?;
+        self.insert(key, &value, ttl);
+        Ok(value)
+    }
+
+    // User-scoped entries; results depend on who is signed in. Tenant-wide ones are kept.
+    pub fn invalidate_session(&self) {
+        if let Ok(mut cache) = self.0.lock() {
+            let removed = cache.invalidate_prefix(USER_PREFIX) + cache.invalidate_prefix(DIRECTORY_PREFIX);
+            log::debug!("Cleared {} user-scoped cache entries", removed);
+        }
+    }
+
+    pub fn invalidate_all(&self) {
+        if let Ok(mut cache) = self.0.lock() {
+            log::debug!("Clearing {} cached entries", cache.len());
+            cache.clear();
+        }
+    }
+}
+
+// On-disk copy of a value. The checksum catches truncated or hand-edited files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    stored_at: DateTime<Utc>,
+    max_age_secs: i64,
+    checksum: String,
+    #[serde(default)]
+    etag: Option<String>,
+    value: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedValue<T> {
+    pub value: T,
+    pub stored_at: DateTime<Utc>,
+    // Past its max-age; fine to render while a fresh copy loads
+    pub stale: bool,
+    #[serde(skip)]
+    pub etag: Option<String>,
+}
+
+pub fn read_disk<T: DeserializeOwned>(app_handle: &AppHandle, key: &str) -> Option<CachedValue<T>> {
+    let path = disk_entry_path(app_handle, key).ok()?;
+    let contents = std::fs::read_to_string(&path).ok()?;
+
+    let entry = serde_json::from_str::<DiskEntry>(&contents)
+        .ok()
+        .filter(|entry| entry.key == key && entry.checksum == checksum(&entry.value));
+    let Some(entry) = entry else {
+        log::warn!("Discarding corrupt cache entry {}", key);
+        let _ = std::fs::remove_file(&path);
+        return None;
+    };
+
+    let stale = Utc::now() - entry.stored_at > chrono::Duration::seconds(entry.max_age_secs);
+    Some(CachedValue {
+        value: serde_json::from_value(entry.value).ok()?,
+        stored_at: entry.stored_at,
+        stale,
+        etag: entry.etag,
+    })
+}
+
+pub fn write_disk<T: Serialize>(app_handle: &AppHandle, key: &str, value: &T, max_age: Duration) -> Result<(), String> {
+    write_disk_with_etag(app_handle, key, value, max_age, None)
+}
+
+// `etag` is the server's validator for the value, sent back as If-None-Match on the next fetch
+pub fn write_disk_with_etag<T: Serialize>(
+    app_handle: &AppHandle,
+    key: &str,
+    value: &T,
+    max_age: Duration,
+    etag: Option<String>,
+) -> Result<(), String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    let entry = DiskEntry {
+        key: key.to_string(),
+        stored_at: Utc::now(),
+        max_age_secs: max_age.as_secs() as i64,
+        checksum: checksum(&value),
+        etag,
+        value,
+    };
+
+    // Write then rename so a crash mid-write never leaves a half-written entry behind
+    let path = disk_entry_path(app_handle, key)?;
+    let temp_path = path.with_extension("tmp");
+    let contents = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+    std::fs::write(&temp_path, contents).map_err(|e| e.to_string())?;
+    std::fs::rename(&temp_path, &path).map_err(|e| e.to_string())
+}
+
+pub fn remove_disk(app_handle: &AppHandle, key: &str) {
+    if let Ok(path) = disk_entry_path(app_handle, key) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+// Stale-while-revalidate: a stale disk copy is returned immediately and refreshed in the
+// background; the fresh value is announced with a `cache-updated` event
+pub async fn get_or_revalidate<T, F, Fut>(app_handle: &AppHandle, key: &str, max_age: Duration, fetch: F) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned + Send + 'static,
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+{
+    match read_disk::<T>(app_handle, key) {
+        Some(cached) if !cached.stale => Ok(cached.value),
+        Some(cached) => {
+            let handle = app_handle.clone();
+            let key = key.to_string();
+            tauri::async_runtime::spawn(async move {
+                match fetch().await {
+                    Ok(value) => {
+                        if let Err(e) = write_disk(&handle, &key, &value, max_age) {
+                            log::warn!("Failed to write cache entry {}: {}", key, e);
+                        }
+                        if let Ok(mut cache) = handle.state::<CacheState>().0.lock() {
+                            cache.remove(&key);
+                        }
+                        crate::bus::emit(&handle, "cache-updated", json!({ "key": key, "value": value }));
+                    }
+                    Err(e) => log::warn!("Failed to revalidate cache entry {}: {}", key, e),
+                }
+            });
+            Ok(cached.value)
+        }
+        None => {
+            let value = fetch().await?;
+            if let Err(e) = write_disk(app_handle, key, &value, max_age) {
+                log::warn!("Failed to write cache entry {}: {}", key, e);
+            }
+            Ok(value)
+        }
+    }
+}
+
+// Last signed-in profile, so the UI can render it while the user signs in again
+#[command]
+pub fn get_cached_profile(app_handle: AppHandle) -> Result<Option<CachedValue<Value>>, CommandError> {
+    Ok(read_disk(&app_handle, PROFILE_KEY))
+}
+
+fn checksum(value: &Value) -> String {
+    let digest = Sha256::digest(value.to_string().as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn disk_entry_path(app_handle: &AppHandle, key: &str) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?.join(DISK_CACHE_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let file_name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    Ok(dir.join(format!("{}.json", file_name)))
+}
+
+// Drops every entry, or only those whose key starts with `prefix`
+#[command]
+pub fn invalidate_cache(cache: State<CacheState>, prefix: Option<String>) -> Result<usize, CommandError> {
+    let mut cache = cache.0.lock().map_err(|e| e.to_string())?;
+    let removed = match prefix {
+        Some(prefix) => cache.invalidate_prefix(&prefix),
+        None => {
+            let removed = cache.len();
+            cache.clear();
+            removed
+        }
+    };
+    log::debug!("Invalidated {} cache entries", removed);
+    Ok(removed)
+}
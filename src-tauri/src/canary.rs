@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::auth::{refresh_client_bearer_token, JWKS_PATH};
+use crate::auth_client::AuthClient;
+use crate::demo::DemoState;
+use crate::error::CommandError;
+use crate::maintenance;
+use crate::middleware::command;
+use crate::policy::PolicyState;
+use crate::tenant::get_settings;
+
+const CANARY_FILE: &str = "canary.json";
+const DEFAULT_INTERVAL_MINUTES: u64 = 60;
+const MAX_RESULTS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryResult {
+    pub at: DateTime<Utc>,
+    pub success: bool,
+    // Time to get a fresh client token
+    pub token_ms: u64,
+    // Time for the authenticated call; None when the token step failed
+    pub call_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+// Most recent first
+#[command]
+pub fn get_canary_results(app_handle: AppHandle) -> Result<Vec<CanaryResult>, CommandError> {
+    let mut results = read_results(&app_handle);
+    results.reverse();
+    Ok(results)
+}
+
+// Runs `policy.canary`: a silent token refresh plus a JWKS fetch on a schedule, so a broken client
+// registration shows up in the results before users hit it at login
+pub async fn run_canary(app_handle: AppHandle) {
+    let Some(policy) = app_handle.state::<PolicyState>().0.canary.clone() else {
+        return;
+    };
+    if app_handle.state::<DemoState>().enabled {
+        return;
+    }
+
+    let interval = Duration::from_secs(policy.interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES).max(1) * 60);
+    loop {
+        tokio::time::sleep(interval).await;
+        if maintenance::in_progress(&app_handle) {
+            log::info!("Skipping canary during IDCS maintenance");
+            continue;
+        }
+
+        let result = probe(&app_handle.state::<AuthClient>()).await;
+        match &result.error {
+            None => log::info!("Canary passed: token {} ms, call {} ms", result.token_ms, result.call_ms.unwrap_or(0)),
+            Some(e) => log::error!("Canary failed: {}", e),
+        }
+        if let Err(e) = append_result(&app_handle, &result) {
+            log::warn!("Failed to record canary result: {}", e);
+        }
+        if let Some(url) = &policy.report_url {
+            if let Err(e) = report(url, &result).await {
+                log::warn!("Failed to report canary result: {}", e);
+            }
+        }
+    }
+}
+
+async fn probe(client: &AuthClient) -> CanaryResult {
+    let at = Utc::now();
+    let started = Instant::now();
+    let bearer_token = match refresh_client_bearer_token(client).await {
+        Ok(token) => token,
+        Err(e) => {
+            return CanaryResult {
+                at,
+                success: false,
+                token_ms: started.elapsed().as_millis() as u64,
+                call_ms: None,
+                error: Some(format!("Token refresh failed: {}", e)),
+            }
+        }
+    };
+    let token_ms = started.elapsed().as_millis() as u64;
+
+    let started = Instant::now();
+    let call = get_settings(client, &bearer_token, JWKS_PATH).await;
+    CanaryResult {
+        at,
+        success: call.is_ok(),
+        token_ms,
+        call_ms: Some(started.elapsed().as_millis() as u64),
+        error: call.err().map(|e| format!("Authenticated call failed: {}", e)),
+    }
+}
+
+async fn report(url: &str, result: &CanaryResult) -> Result<(), String> {
+    let body = json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "result": result,
+    });
+    reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn append_result(app_handle: &AppHandle, result: &CanaryResult) -> Result<(), String> {
+    let mut results = read_results(app_handle);
+    results.push(result.clone());
+    let overflow = results.len().saturating_sub(MAX_RESULTS);
+    results.drain(..overflow);
+    let contents = serde_json::to_string(&results).map_err(|e| e.to_string())?;
+    std::fs::write(canary_path(app_handle)?, contents).map_err(|e| e.to_string())
+}
+
+fn read_results(app_handle: &AppHandle) -> Vec<CanaryResult> {
+    canary_path(app_handle)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn canary_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(CANARY_FILE))
+}
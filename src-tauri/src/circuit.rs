@@ -0,0 +1,184 @@
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::bus::EventBus;
+use crate::endpoints;
+use crate::error::CommandError;
+use crate::middleware::command;
+
+// Consecutive failures before the circuit opens
+const FAILURE_THRESHOLD: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+// A trial request that hasn't finished by then, e.g. because its caller gave up, stops blocking others
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    // Failing fast until the backoff has passed
+    Open,
+    // Backoff passed; the next request decides whether to close or reopen
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    // Seconds until a request is let through again, while open
+    pub retry_after_secs: Option<u64>,
+}
+
+// Returned instead of contacting IDCS while the circuit is open
+#[derive(Debug, Clone)]
+pub struct ServiceUnavailable {
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for ServiceUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Service unavailable: IDCS is not responding, retrying in {}s",
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for ServiceUnavailable {}
+
+impl From<ServiceUnavailable> for String {
+    fn from(error: ServiceUnavailable) -> Self {
+        error.to_string()
+    }
+}
+
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    backoff: Duration,
+    open_until: Option<Instant>,
+    // Set while the half-open trial request is in flight so only one goes through
+    probe_deadline: Option<Instant>,
+}
+
+static BREAKER: Mutex<Breaker> = Mutex::new(Breaker {
+    state: CircuitState::Closed,
+    consecutive_failures: 0,
+    backoff: INITIAL_BACKOFF,
+    open_until: None,
+    probe_deadline: None,
+});
+
+// Where state changes are published; set once at startup
+static EVENTS: OnceLock<EventBus> = OnceLock::new();
+
+pub fn install(bus: &EventBus) {
+    let _ = EVENTS.set(bus.clone());
+}
+
+#[command]
+pub fn get_circuit_status() -> Result<CircuitStatus, CommandError> {
+    let breaker = BREAKER.lock().map_err(|e| e.to_string())?;
+    Ok(status(&breaker))
+}
+
+// Sends a request to IDCS through the breaker, failing over between the profile's URLs. Connection errors, 5xx and 429 count as failures;
+// any other response means the tenant is up, even if it rejected the request.
+pub async fn send(request: RequestBuilder) -> Result<Response, String> {
+    check()?;
+    let result = endpoints::send(request).await;
+    record(matches!(&result, Ok(response) if !is_failure(response.status())));
+    result
+}
+
+// Fails fast while the circuit is open
+pub fn check() -> Result<(), ServiceUnavailable> {
+    let Ok(mut breaker) = BREAKER.lock() else {
+        return Ok(());
+    };
+    let now = Instant::now();
+    match breaker.state {
+        CircuitState::Closed => Ok(()),
+        CircuitState::HalfOpen => match breaker.probe_deadline.filter(|deadline| *deadline > now) {
+            Some(deadline) => Err(ServiceUnavailable { retry_after: deadline - now }),
+            None => {
+                breaker.probe_deadline = Some(now + PROBE_TIMEOUT);
+                Ok(())
+            }
+        },
+        CircuitState::Open => match breaker.open_until.filter(|until| *until > now) {
+            Some(until) => Err(ServiceUnavailable { retry_after: until - now }),
+            None => {
+                breaker.probe_deadline = Some(now + PROBE_TIMEOUT);
+                transition(&mut breaker, CircuitState::HalfOpen);
+                Ok(())
+            }
+        },
+    }
+}
+
+pub(crate) fn is_failure(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn record(success: bool) {
+    let Ok(mut breaker) = BREAKER.lock() else {
+        return;
+    };
+    breaker.probe_deadline = None;
+    if success {
+        breaker.consecutive_failures = 0;
+        breaker.backoff = INITIAL_BACKOFF;
+        breaker.open_until = None;
+        if breaker.state != CircuitState::Closed {
+            transition(&mut breaker, CircuitState::Closed);
+        }
+        return;
+    }
+
+    breaker.consecutive_failures += 1;
+    match breaker.state {
+        // The trial request failed too; wait longer before the next one
+        CircuitState::HalfOpen => {
+            breaker.backoff = (breaker.backoff * 2).min(MAX_BACKOFF);
+            open(&mut breaker);
+        }
+        CircuitState::Closed if breaker.consecutive_failures >= FAILURE_THRESHOLD => open(&mut breaker),
+        _ => {}
+    }
+}
+
+fn open(breaker: &mut Breaker) {
+    breaker.open_until = Some(Instant::now() + breaker.backoff);
+    log::warn!(
+        "IDCS failed {} times in a row, pausing requests for {}s",
+        breaker.consecutive_failures,
+        breaker.backoff.as_secs()
+    );
+    transition(breaker, CircuitState::Open);
+}
+
+fn transition(breaker: &mut Breaker, state: CircuitState) {
+    breaker.state = state;
+    log::info!("IDCS circuit is now {:?}", state);
+    if let Some(bus) = EVENTS.get() {
+        bus.emit("circuit-state-changed", status(breaker));
+    }
+}
+
+fn status(breaker: &Breaker) -> CircuitStatus {
+    let now = Instant::now();
+    CircuitStatus {
+        state: breaker.state,
+        consecutive_failures: breaker.consecutive_failures,
+        retry_after_secs: breaker
+            .open_until
+            .filter(|until| breaker.state == CircuitState::Open && *until > now)
+            .map(|until| (until - now).as_secs().max(1)),
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::oci_cli::OciSessionProfile;
+use crate::privacy::WipeSummary;
+
+// How CLI commands print their result. The JSON of each command is the struct below as serialized, so fields are
+// only ever added to them; the text is for people and may change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            None | Some("text") => Ok(Self::Text),
+            Some("json") => Ok(Self::Json),
+            Some(other) => Err(format!("Unknown output {}; use text or json", other)),
+        }
+    }
+}
+
+pub trait CliOutput: Serialize {
+    fn text(&self) -> String;
+}
+
+pub fn print(format: OutputFormat, output: &impl CliOutput) {
+    match format {
+        OutputFormat::Text => println!("{}", output.text()),
+        OutputFormat::Json => match serde_json::to_string_pretty(output) {
+            Ok(json) => println!("{}", json),
+            Err(e) => print_error(format, &e.to_string()),
+        },
+    }
+}
+
+// What a failed command prints with `--output json`, on stdout like any other result so scripts read one stream
+#[derive(Debug, Clone, Serialize)]
+pub struct CliError {
+    pub error: String,
+}
+
+pub fn print_error(format: OutputFormat, message: &str) {
+    match format {
+        OutputFormat::Text => eprintln!("{}", message),
+        OutputFormat::Json => {
+            let error = CliError { error: message.to_string() };
+            println!("{}", serde_json::to_string_pretty(&error).unwrap_or_default());
+        }
+    }
+}
+
+// `--get-config`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReport {
+    pub log_file: String,
+    pub config_file: String,
+    // Set in portable mode
+    pub portable_home: Option<String>,
+    pub log_level: String,
+    pub log_file_size_mb: u64,
+    pub log_file_count: u32,
+    pub idcs_url: String,
+}
+
+impl CliOutput for ConfigReport {
+    fn text(&self) -> String {
+        let mut lines = vec![
+            "Current configuration:".to_string(),
+            format!("Log filename: {}", self.log_file),
+            format!("Store plugin config: {}", self.config_file),
+        ];
+        if let Some(home) = &self.portable_home {
+            lines.push(format!("Portable mode: {}", home));
+        }
+        lines.extend([
+            format!("Log level: {}", self.log_level),
+            format!("Max log file size: {}MB", self.log_file_size_mb),
+            format!("Number of log files: {}", self.log_file_count),
+            format!("IDCS URL: {}", self.idcs_url),
+        ]);
+        lines.join("\n")
+    }
+}
+
+// `--idcs-url`, `--log-level`, `--log-size`, `--log-count` and `--clear-config`
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChanged {
+    // `idcs_url`, `log_level`, `log_file_size_mb`, `log_file_count`, or `all` when everything was reset
+    pub setting: &'static str,
+    // None after a reset to the defaults
+    pub value: Option<Value>,
+    #[serde(skip)]
+    pub message: String,
+}
+
+impl CliOutput for SettingChanged {
+    fn text(&self) -> String {
+        self.message.clone()
+    }
+}
+
+// `--wipe`
+#[derive(Debug, Clone, Serialize)]
+pub struct WipeOutput {
+    // False when the confirmation was declined and nothing was touched
+    pub wiped: bool,
+    #[serde(flatten)]
+    pub summary: WipeSummary,
+}
+
+impl CliOutput for WipeOutput {
+    fn text(&self) -> String {
+        if !self.wiped {
+            return "Nothing was removed".to_string();
+        }
+        let summary = &self.summary;
+        let mut lines: Vec<String> = summary
+            .removed
+            .iter()
+            .map(|item| format!("Removed {} ({} bytes)", item.path, item.bytes))
+            .collect();
+        lines.extend(summary.failed.iter().map(|path| format!("Could not remove {}", path)));
+        lines.push(format!(
+            "Removed {} items ({} bytes), revoked {} tokens",
+            summary.removed.len(),
+            summary.bytes,
+            summary.revoked_tokens
+        ));
+        lines.join("\n")
+    }
+}
+
+// `--authenticate`: the access token or the profile, as `--print` asks
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthenticateOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Value>,
+}
+
+impl CliOutput for AuthenticateOutput {
+    fn text(&self) -> String {
+        match (&self.access_token, &self.profile) {
+            (Some(token), _) => token.clone(),
+            (None, Some(profile)) => serde_json::to_string_pretty(profile).unwrap_or_default(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+// `--export-token`: the session profile as written
+impl CliOutput for OciSessionProfile {
+    fn text(&self) -> String {
+        let mut lines = vec![
+            format!("Wrote profile {} to {}", self.profile, self.config_file),
+            format!("Session token: {}", self.token_file),
+            format!("API key: {} ({})", self.key_file, self.fingerprint),
+        ];
+        if let Some(expires_at) = self.expires_at {
+            lines.push(format!("Expires: {}", expires_at.to_rfc3339()));
+        }
+        lines.push(format!("Use it with: oci --profile {} --auth security_token ...", self.profile));
+        lines.join("\n")
+    }
+}
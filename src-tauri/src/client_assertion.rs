@@ -0,0 +1,53 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::Serialize;
+
+// IDCS accepts client assertions addressed to this audience unless the profile overrides it
+pub(crate) const AUDIENCE: &str = "https://identity.oraclecloud.com/";
+// Each assertion is used for one request, so it only has to outlive the round trip
+const LIFETIME_SECS: i64 = 300;
+pub const ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+    jti: String,
+}
+
+// Signs a `private_key_jwt` client assertion with the PEM RSA key at `key_file`. `key_id` is the alias of the
+// certificate uploaded to the client app in IDCS, which it uses to find the public key. `aud` and `iss` default
+// to `AUDIENCE` and the client id.
+pub fn sign(
+    client_id: &str,
+    key_file: &str,
+    key_id: &str,
+    audience: Option<&str>,
+    issuer: Option<&str>,
+) -> Result<String, String> {
+    let pem = std::fs::read(key_file).map_err(|e| format!("Failed to read private key {}: {}", key_file, e))?;
+    let key = EncodingKey::from_rsa_pem(&pem).map_err(|e| format!("Invalid private key {}: {}", key_file, e))?;
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(key_id.to_string());
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        iss: issuer.unwrap_or(client_id),
+        sub: client_id,
+        aud: audience.unwrap_or(AUDIENCE),
+        iat: now,
+        exp: now + LIFETIME_SECS,
+        jti: jti()?,
+    };
+    jsonwebtoken::encode(&header, &claims, &key).map_err(|e| format!("Failed to sign client assertion: {}", e))
+}
+
+// Unique per assertion, so IDCS can reject replays
+fn jti() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
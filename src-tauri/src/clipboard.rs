@@ -0,0 +1,118 @@
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::config::ConfigState;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::policy::PolicyState;
+use crate::session::SessionState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenProtection {
+    pub enabled: bool,
+    // Turned on by policy, so the user can't switch it off
+    pub enforced: bool,
+}
+
+// Whether full tokens must stay in the backend
+pub fn tokens_protected(app_handle: &AppHandle) -> bool {
+    app_handle.state::<PolicyState>().0.protect_tokens
+        || app_handle
+            .state::<ConfigState>()
+            .0
+            .read(|config| config.security.protect_tokens)
+}
+
+// First and last few characters only, e.g. `eyJraW…Q1fA`
+pub fn mask_token(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() <= 12 {
+        return "•".repeat(chars.len());
+    }
+    let head: String = chars[..6].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+#[command]
+pub fn get_token_protection(app_handle: AppHandle) -> Result<TokenProtection, CommandError> {
+    Ok(TokenProtection {
+        enabled: tokens_protected(&app_handle),
+        enforced: app_handle.state::<PolicyState>().0.protect_tokens,
+    })
+}
+
+#[command(unlocked)]
+pub fn set_token_protection(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    policy: State<PolicyState>,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    if !enabled && policy.0.protect_tokens {
+        return Err("Token protection is required by your administrator".to_string().into());
+    }
+    let mut config = config_state.0.lock();
+    config.set_protect_tokens(&app_handle, enabled).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[command]
+pub fn get_token_preview(session: State<SessionState>) -> Result<Option<String>, CommandError> {
+    Ok(session
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|session| session.access_token.as_deref())
+        .map(mask_token))
+}
+
+#[command(session)]
+pub fn get_access_token(app_handle: AppHandle, session: State<SessionState>) -> Result<String, CommandError> {
+    if tokens_protected(&app_handle) {
+        return Err("Token protection is on; use copy instead".to_string().into());
+    }
+    Ok(access_token(&session)?)
+}
+
+// Copies the token natively, so it never passes through the webview, and clears it again
+// after `security.clipboard_clear_seconds` unless something else was copied in the meantime.
+// Returns the number of seconds until the clipboard is cleared.
+#[command(session)]
+pub fn copy_access_token(app_handle: AppHandle, session: State<SessionState>) -> Result<u64, CommandError> {
+    let token = access_token(&session)?;
+    app_handle
+        .clipboard()
+        .write_text(token.clone())
+        .map_err(|e| e.to_string())?;
+
+    let clear_after = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.security.clipboard_clear_seconds);
+    if clear_after > 0 {
+        let handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(clear_after)).await;
+            if handle.clipboard().read_text().map(|text| text == token).unwrap_or(false) {
+                let _ = handle.clipboard().clear();
+                log::debug!("Cleared copied access token from the clipboard");
+            }
+        });
+    }
+    log::info!("Access token copied to the clipboard");
+    Ok(clear_after)
+}
+
+fn access_token(session: &SessionState) -> Result<String, String> {
+    session
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|session| session.access_token.clone())
+        .ok_or_else(|| "Not signed in to IDCS".to_string())
+}
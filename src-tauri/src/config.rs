@@ -1,9 +1,13 @@
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
 use tauri::AppHandle;
-use tauri::Manager;
 use tauri_plugin_store::StoreBuilder;
 
+use crate::policy::{deserialize_hhmm, serialize_hhmm};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
@@ -27,6 +31,19 @@ impl Default for LogLevel {
     }
 }
 
+impl LogLevel {
+    pub fn filter(&self) -> log::LevelFilter {
+        match self {
+            LogLevel::Trace => log::LevelFilter::Trace,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Off => log::LevelFilter::Off,
+        }
+    }
+}
+
 impl std::fmt::Display for LogLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -45,6 +62,9 @@ pub struct LoggingConfig {
     pub level: LogLevel,
     pub file_size_mb: u64,
     pub file_count: u32,
+    // Extra destinations on top of the console, log directory and webview
+    #[serde(default)]
+    pub sinks: Vec<LogSinkConfig>,
 }
 
 impl Default for LoggingConfig {
@@ -53,19 +73,474 @@ impl Default for LoggingConfig {
             level: LogLevel::Info,
             file_size_mb: 10,
             file_count: 5,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LogSinkKind {
+    File { path: PathBuf },
+    Syslog {
+        #[serde(default = "default_syslog_address")]
+        address: String,
+    },
+    Webhook { url: String },
+    // OTLP/HTTP collector base URL; records are posted to `<endpoint>/v1/logs`
+    Otlp { endpoint: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSinkConfig {
+    #[serde(flatten)]
+    pub kind: LogSinkKind,
+    // Used to toggle the sink at runtime; defaults to the sink type
+    pub name: Option<String>,
+    #[serde(default = "default_sink_enabled")]
+    pub enabled: bool,
+    // Most verbose level sent to this sink; the global level applies when unset
+    pub level: Option<LogLevel>,
+}
+
+impl LogSinkConfig {
+    pub fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match self.kind {
+            LogSinkKind::File { .. } => "file",
+            LogSinkKind::Syslog { .. } => "syslog",
+            LogSinkKind::Webhook { .. } => "webhook",
+            LogSinkKind::Otlp { .. } => "otlp",
+        }
+        .to_string()
+    }
+}
+
+fn default_syslog_address() -> String {
+    "127.0.0.1:514".to_string()
+}
+
+fn default_sink_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl std::fmt::Display for ThemePreference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemePreference::System => write!(f, "system"),
+            ThemePreference::Light => write!(f, "light"),
+            ThemePreference::Dark => write!(f, "dark"),
+        }
+    }
+}
+
+impl ThemePreference {
+    // `None` lets the window follow the OS theme
+    pub fn to_window_theme(self) -> Option<tauri::Theme> {
+        match self {
+            ThemePreference::System => None,
+            ThemePreference::Light => Some(tauri::Theme::Light),
+            ThemePreference::Dark => Some(tauri::Theme::Dark),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiConfig {
+    // BCP 47 tag such as "en-US"; falls back to the OS locale when unset
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub theme: ThemePreference,
+    #[serde(default)]
+    pub always_on_top: bool,
+    // Render the WebView without the GPU; unset means only for GPUs known to misrender. Read at startup.
+    #[serde(default)]
+    pub force_software_rendering: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    // Warm up connections and tenant metadata at startup so the first login is fast
+    pub preconnect: bool,
+    // Geo-IP service queried after each login, e.g. "https://ipapi.co/json/"; off when unset
+    #[serde(default)]
+    pub location_lookup: Option<String>,
+    // Header on the IDCS authenticate response carrying the client's public IP, if the tenant's gateway adds one
+    #[serde(default)]
+    pub location_header: Option<String>,
+    // Limit for each sign-in request to IDCS, from connecting to the last byte
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+    // Proxy for IDCS requests, e.g. "http://proxy.corp:8080"; `HTTPS_PROXY` is used when unset
+    #[serde(default)]
+    pub proxy: Option<String>,
+    // Basic auth user for the proxy; its password is kept in the keychain
+    #[serde(default)]
+    pub proxy_username: Option<String>,
+    // Hosts, domains and IP ranges reached without the proxy, e.g. "idcs.internal.corp" or "10.0.0.0/8"
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    // PEM file with extra root certificates to trust, e.g. the one a TLS-intercepting proxy signs with
+    #[serde(default)]
+    pub ca_certificate: Option<String>,
+    // Off to trust only `ca_certificate`
+    #[serde(default = "default_use_system_roots")]
+    pub use_system_roots: bool,
+    #[serde(default)]
+    pub min_tls_version: Option<TlsVersion>,
+}
+
+fn default_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_use_system_roots() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    #[serde(rename = "1.0")]
+    Tls10,
+    #[serde(rename = "1.1")]
+    Tls11,
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl std::fmt::Display for TlsVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsVersion::Tls10 => write!(f, "TLS 1.0"),
+            TlsVersion::Tls11 => write!(f, "TLS 1.1"),
+            TlsVersion::Tls12 => write!(f, "TLS 1.2"),
+            TlsVersion::Tls13 => write!(f, "TLS 1.3"),
+        }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            preconnect: true,
+            location_lookup: None,
+            location_header: None,
+            timeout_seconds: default_timeout_seconds(),
+            proxy: None,
+            proxy_username: None,
+            no_proxy: Vec::new(),
+            ca_certificate: None,
+            use_system_roots: default_use_system_roots(),
+            min_tls_version: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceConfig {
+    // In-memory cache budget; the cache is trimmed when it grows past this
+    pub max_cache_mb: u64,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self { max_cache_mb: 64 }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalyticsConfig {
+    // Off until the user opts in
+    #[serde(default)]
+    pub enabled: bool,
+    // Where aggregated usage counts are posted; nothing is sent when unset
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    // Only masked token previews reach the webview; tokens are copied through the native clipboard
+    pub protect_tokens: bool,
+    // A copied token is cleared from the clipboard after this many seconds
+    pub clipboard_clear_seconds: u64,
+    // Poll IDCS for the user's other sessions and warn about ones from unfamiliar addresses
+    pub watch_other_sessions: bool,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            protect_tokens: false,
+            clipboard_clear_seconds: 30,
+            watch_other_sessions: true,
+        }
+    }
+}
+
+// Maximum age in days of locally kept artifacts; null keeps them forever
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub audit_days: Option<u32>,
+    pub login_history_days: Option<u32>,
+    pub flow_trace_days: Option<u32>,
+    pub har_days: Option<u32>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            audit_days: Some(365),
+            login_history_days: Some(180),
+            flow_trace_days: Some(14),
+            har_days: Some(7),
+        }
+    }
+}
+
+// Second factors as written in the config file; IDCS uses the upper-case names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthFactor {
+    Push,
+    Totp,
+    Sms,
+    Email,
+    PhoneCall,
+    SecurityQuestions,
+    BypassCode,
+    // FIDO2 security key or platform authenticator, used through WebAuthn
+    FidoAuthenticator,
+}
+
+impl AuthFactor {
+    pub fn idcs_name(self) -> &'static str {
+        match self {
+            AuthFactor::Push => "PUSH",
+            AuthFactor::Totp => "TOTP",
+            AuthFactor::Sms => "SMS",
+            AuthFactor::Email => "EMAIL",
+            AuthFactor::PhoneCall => "PHONE_CALL",
+            AuthFactor::SecurityQuestions => "SECURITY_QUESTIONS",
+            AuthFactor::BypassCode => "BYPASSCODE",
+            AuthFactor::FidoAuthenticator => "FIDO_AUTHENTICATOR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    // When IDCS offers several factors, the first of these on offer is started automatically
+    pub preferred_factors: Vec<AuthFactor>,
+}
+
+// Auth events that can raise an OS notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Login,
+    LoginFailed,
+    LocationChanged,
+    AccessRequests,
+    OtherSession,
+    Maintenance,
+}
+
+impl NotificationEvent {
+    // Used for events missing from `notifications.events`; a successful login is already on screen
+    pub fn enabled_by_default(self) -> bool {
+        self != NotificationEvent::Login
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub events: BTreeMap<NotificationEvent, bool>,
+    pub sound: bool,
+    pub do_not_disturb: Option<QuietHours>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            events: BTreeMap::new(),
+            sound: true,
+            do_not_disturb: None,
         }
     }
 }
 
+// Local time; an `end` before `start` spans midnight, e.g. 22:00-07:00
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    #[serde(serialize_with = "serialize_hhmm", deserialize_with = "deserialize_hhmm")]
+    pub start: NaiveTime,
+    #[serde(serialize_with = "serialize_hhmm", deserialize_with = "deserialize_hhmm")]
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdcsConfig {
+    // Tenant URL, e.g. "https://idcs-0123456789abcdef.identity.oraclecloud.com"; the built-in URL when unset
+    pub base_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlatformConfig {
+    // Keep the app's windows out of screenshots and screen sharing where the OS supports it
+    pub block_screen_capture: bool,
+}
+
+// Well-formed HTTPS URL of a host, returned without a trailing slash
+pub fn validate_idcs_url(url: &str) -> Result<String, String> {
+    let url = url.trim().trim_end_matches('/');
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid IDCS URL {}: {}", url, e))?;
+    if parsed.scheme() != "https" {
+        return Err(format!("IDCS URL must use https: {}", url));
+    }
+    if parsed.host_str().is_none() || parsed.path() != "/" || parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(format!("IDCS URL must be just the tenant address: {}", url));
+    }
+    Ok(url.to_string())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacyConfig {
+    #[serde(default)]
+    pub retention: RetentionConfig,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub idcs: IdcsConfig,
+    #[serde(default)]
+    pub platform: PlatformConfig,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             logging: LoggingConfig::default(),
+            ui: UiConfig::default(),
+            network: NetworkConfig::default(),
+            performance: PerformanceConfig::default(),
+            analytics: AnalyticsConfig::default(),
+            privacy: PrivacyConfig::default(),
+            security: SecurityConfig::default(),
+            notifications: NotificationsConfig::default(),
+            auth: AuthConfig::default(),
+            idcs: IdcsConfig::default(),
+            platform: PlatformConfig::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ConfigState(pub ConfigLock);
+
+impl ConfigState {
+    pub fn new(app_handle: &AppHandle, config: AppConfig) -> Self {
+        Self(ConfigLock {
+            config: Mutex::new(config),
+            file: config_file(app_handle).ok(),
+        })
+    }
+}
+
+// A lock on the config that a panic can't poison, so a command that panicked while holding it doesn't fail every
+// later one. The panic may have left a change half made and unsaved, so the config is read back from its file then.
+#[derive(Default)]
+pub struct ConfigLock {
+    config: Mutex<AppConfig>,
+    // Where `AppConfig::save` writes it; None when the data folder couldn't be found
+    file: Option<PathBuf>,
+}
+
+impl ConfigLock {
+    pub fn lock(&self) -> MutexGuard<'_, AppConfig> {
+        self.config.lock().unwrap_or_else(|poisoned| {
+            let mut config = poisoned.into_inner();
+            match self.read_saved() {
+                Ok(saved) => {
+                    log::warn!("A command panicked while changing the settings; reloaded them from disk");
+                    *config = saved;
+                }
+                Err(e) => log::warn!(
+                    "A command panicked while changing the settings and they couldn't be reloaded, keeping them as \
+                     they are: {}",
+                    e
+                ),
+            }
+            self.config.clear_poison();
+            config
+        })
+    }
+
+    // Reads one value out of the config without holding the lock any longer
+    pub fn read<T>(&self, f: impl FnOnce(&AppConfig) -> T) -> T {
+        f(&self.lock())
+    }
+
+    // The config as last saved, read from the file directly since the store needs an `AppHandle`
+    fn read_saved(&self) -> Result<AppConfig, String> {
+        let file = self.file.as_ref().ok_or("The config file's location isn't known")?;
+        let store: serde_json::Value = match std::fs::read_to_string(file) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Invalid {}: {}", file.display(), e))?,
+            // Never saved, so the defaults are what `AppConfig::load` would return too
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AppConfig::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", file.display(), e)),
+        };
+        match store.get("config") {
+            Some(config) => {
+                serde_json::from_value(config.clone()).map_err(|e| format!("Invalid {}: {}", file.display(), e))
+            }
+            None => Ok(AppConfig::default()),
         }
     }
 }
@@ -73,8 +548,7 @@ impl Default for AppConfig {
 #[allow(dead_code)]
 impl AppConfig {
     pub fn load(app_handle: &AppHandle) -> Result<Self, Box<dyn std::error::Error>> {
-        let app_config_dir = get_app_config_dir(app_handle)?;
-        let store_path = app_config_dir.join("config.json");
+        let store_path = config_file(app_handle)?;
         
         let store = StoreBuilder::new(app_handle, store_path).build();
         
@@ -90,8 +564,7 @@ impl AppConfig {
     }
 
     pub fn save(&self, app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-        let app_config_dir = get_app_config_dir(app_handle)?;
-        let store_path = app_config_dir.join("config.json");
+        let store_path = config_file(app_handle)?;
         
         let store = StoreBuilder::new(app_handle, store_path).build()?;
         
@@ -117,6 +590,93 @@ impl AppConfig {
         Ok(())
     }
 
+    pub fn set_locale(&mut self, app_handle: &AppHandle, locale: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(locale) = locale {
+            if !crate::i18n::is_valid_locale(locale) {
+                return Err(format!("Invalid locale: {}", locale).into());
+            }
+        }
+        self.ui.locale = locale.map(|l| l.to_string());
+        crate::i18n::set_locale(locale);
+        self.save(app_handle)
+    }
+
+    pub fn set_theme(&mut self, app_handle: &AppHandle, theme: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.ui.theme = match theme.to_lowercase().as_str() {
+            "system" => ThemePreference::System,
+            "light" => ThemePreference::Light,
+            "dark" => ThemePreference::Dark,
+            _ => return Err("Invalid theme".into()),
+        };
+
+        self.save(app_handle)?;
+        Ok(())
+    }
+
+    pub fn set_always_on_top(&mut self, app_handle: &AppHandle, enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.ui.always_on_top = enabled;
+        self.save(app_handle)
+    }
+
+    pub fn set_log_sink_enabled(&mut self, app_handle: &AppHandle, name: &str, enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let sink = self
+            .logging
+            .sinks
+            .iter_mut()
+            .find(|sink| sink.name() == name)
+            .ok_or_else(|| format!("Unknown log sink: {}", name))?;
+        sink.enabled = enabled;
+        self.save(app_handle)
+    }
+
+    pub fn set_analytics_consent(&mut self, app_handle: &AppHandle, consent: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.analytics.enabled = consent;
+        self.save(app_handle)
+    }
+
+    pub fn set_protect_tokens(&mut self, app_handle: &AppHandle, enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.security.protect_tokens = enabled;
+        self.save(app_handle)
+    }
+
+    pub fn set_notification_prefs(&mut self, app_handle: &AppHandle, prefs: NotificationsConfig) -> Result<(), Box<dyn std::error::Error>> {
+        self.notifications = prefs;
+        self.save(app_handle)
+    }
+
+    pub fn set_preferred_factors(&mut self, app_handle: &AppHandle, factors: Vec<AuthFactor>) -> Result<(), Box<dyn std::error::Error>> {
+        self.auth.preferred_factors = factors;
+        self.save(app_handle)
+    }
+
+    pub fn set_idcs_url(&mut self, app_handle: &AppHandle, url: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        self.idcs.base_url = url.map(validate_idcs_url).transpose()?;
+        self.save(app_handle)
+    }
+
+    pub fn set_force_software_rendering(&mut self, app_handle: &AppHandle, forced: Option<bool>) -> Result<(), Box<dyn std::error::Error>> {
+        self.ui.force_software_rendering = forced;
+        self.save(app_handle)
+    }
+
+    pub fn set_proxy(
+        &mut self,
+        app_handle: &AppHandle,
+        proxy: Option<String>,
+        username: Option<String>,
+        no_proxy: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.network.proxy = proxy;
+        self.network.proxy_username = username;
+        self.network.no_proxy = no_proxy;
+        self.save(app_handle)
+    }
+
+    pub fn set_block_screen_capture(&mut self, app_handle: &AppHandle, blocked: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.platform.block_screen_capture = blocked;
+        self.save(app_handle)
+    }
+
     pub fn set_log_file_size(&mut self, app_handle: &AppHandle, size_mb: u64) -> Result<(), Box<dyn std::error::Error>> {
         if size_mb == 0 {
             return Err(Box::new(std::io::Error::new(
@@ -141,11 +701,44 @@ impl AppConfig {
 }
 
 #[allow(dead_code)]
+fn config_file(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(get_app_config_dir(app_handle)?.join("config.json"))
+}
+
 fn get_app_config_dir(app_handle: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let config_dir = app_handle
-        .path()
-        .app_data_dir()?;
-    
+    let config_dir = crate::paths::data_dir(app_handle)?;
+
     std::fs::create_dir_all(&config_dir)?;
     Ok(config_dir)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_idcs_url_trims_the_trailing_slash() {
+        assert_eq!(
+            validate_idcs_url(" https://idcs-abc.identity.oraclecloud.com/ "),
+            Ok("https://idcs-abc.identity.oraclecloud.com".to_string())
+        );
+    }
+
+    #[test]
+    fn validate_idcs_url_requires_https() {
+        assert!(validate_idcs_url("http://idcs-abc.identity.oraclecloud.com").is_err());
+    }
+
+    #[test]
+    fn validate_idcs_url_rejects_anything_but_the_tenant_address() {
+        for url in [
+            "https://idcs-abc.identity.oraclecloud.com/ui/v1/signin",
+            "https://idcs-abc.identity.oraclecloud.com?tenant=abc",
+            "https://idcs-abc.identity.oraclecloud.com#signin",
+            "idcs-abc.identity.oraclecloud.com",
+            "",
+        ] {
+            assert!(validate_idcs_url(url).is_err(), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,556 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::auth::{AuthResponse, CauseMessage};
+use crate::config::AuthFactor;
+use crate::security_questions::{self, SecurityAnswer, SecurityAnswersResult, SecurityQuestion, SecurityQuestionsPrompt};
+use crate::webauthn::{WebAuthnAssertion, WebAuthnChallenge};
+
+const MAX_FAILED_ATTEMPTS: u32 = 3;
+// Accepted in place of any demo user's second factor
+const DEMO_BYPASS_CODE: &str = "1122334455";
+// Polls before the simulated authenticator answers a push
+const PUSH_ANSWER_AFTER: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemoMfa {
+    None,
+    // Push notification that the simulated authenticator approves
+    PushApprove,
+    // Push notification that the simulated authenticator denies
+    PushDeny,
+    Totp { code: &'static str },
+    // Passcode "sent" by text message or email; resending keeps the same code
+    Otp { factor: AuthFactor, code: &'static str },
+    // FIDO2 security key; any assertion over the issued challenge is accepted
+    SecurityKey,
+    // Enrolled questions as (id, text, answer); answers are compared ignoring case
+    SecurityQuestions { questions: &'static [(&'static str, &'static str, &'static str)] },
+}
+
+#[derive(Debug, Clone)]
+pub struct DemoUser {
+    pub username: &'static str,
+    pub password: &'static str,
+    pub display_name: &'static str,
+    pub email: &'static str,
+    pub groups: &'static [&'static str],
+    pub mfa: DemoMfa,
+    pub locked: bool,
+}
+
+// Seeded identities covering the flows the UI has to handle
+const DEMO_USERS: &[DemoUser] = &[
+    DemoUser {
+        username: "demo.user",
+        password: "Welcome1!",
+        display_name: "Demo User",
+        email: "demo.user@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::None,
+        locked: false,
+    },
+    DemoUser {
+        username: "push.user",
+        password: "Welcome1!",
+        display_name: "Push Approver",
+        email: "push.user@example.com",
+        groups: &["Employees", "Approvers"],
+        mfa: DemoMfa::PushApprove,
+        locked: false,
+    },
+    DemoUser {
+        username: "push.denied",
+        password: "Welcome1!",
+        display_name: "Push Denied",
+        email: "push.denied@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::PushDeny,
+        locked: false,
+    },
+    DemoUser {
+        username: "totp.user",
+        password: "Welcome1!",
+        display_name: "TOTP User",
+        email: "totp.user@example.com",
+        groups: &["Employees", "Identity Domain Administrators"],
+        mfa: DemoMfa::Totp { code: "123456" },
+        locked: false,
+    },
+    DemoUser {
+        username: "sms.user",
+        password: "Welcome1!",
+        display_name: "SMS User",
+        email: "sms.user@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::Otp { factor: AuthFactor::Sms, code: "246810" },
+        locked: false,
+    },
+    DemoUser {
+        username: "email.user",
+        password: "Welcome1!",
+        display_name: "Email User",
+        email: "email.user@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::Otp { factor: AuthFactor::Email, code: "135790" },
+        locked: false,
+    },
+    DemoUser {
+        username: "fido.user",
+        password: "Welcome1!",
+        display_name: "Security Key User",
+        email: "fido.user@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::SecurityKey,
+        locked: false,
+    },
+    DemoUser {
+        username: "questions.user",
+        password: "Welcome1!",
+        display_name: "Security Questions User",
+        email: "questions.user@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::SecurityQuestions {
+            questions: &[
+                ("MaidenName", "What is your mother's maiden name?", "Smith"),
+                ("FirstCar", "What was the make of your first car?", "Volvo"),
+            ],
+        },
+        locked: false,
+    },
+    DemoUser {
+        username: "helpdesk.user",
+        password: "Welcome1!",
+        display_name: "Helpdesk Agent",
+        email: "helpdesk.user@example.com",
+        groups: &["Employees", "Help Desk Administrators"],
+        mfa: DemoMfa::None,
+        locked: false,
+    },
+    DemoUser {
+        username: "locked.user",
+        password: "Welcome1!",
+        display_name: "Locked User",
+        email: "locked.user@example.com",
+        groups: &["Employees"],
+        mfa: DemoMfa::None,
+        locked: true,
+    },
+];
+
+// Mock identity provider used when the app is started with `--demo`
+#[derive(Default)]
+pub struct DemoState {
+    pub enabled: bool,
+    pending: Mutex<HashMap<String, String>>,
+    failed_attempts: Mutex<HashMap<String, u32>>,
+    // Request states whose passcode was accepted, so `complete` can finish them
+    verified: Mutex<HashSet<String>>,
+    // Polls seen so far for each user's outstanding push notification
+    push_polls: Mutex<HashMap<String, u32>>,
+    // WebAuthn challenge issued for each request state
+    challenges: Mutex<HashMap<String, String>>,
+}
+
+impl DemoState {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    pub fn users() -> &'static [DemoUser] {
+        DEMO_USERS
+    }
+
+    // Demo profiles use `demo-<username>` as their id
+    pub fn profile_by_id(id: &str) -> Option<Value> {
+        let username = id.strip_prefix("demo-")?;
+        find_user(username).map(profile)
+    }
+
+    pub fn initiate(&self, username: &str, password: &str) -> Result<AuthResponse, String> {
+        let user = find_user(username)
+            .ok_or_else(|| "You entered an incorrect user name or password.".to_string())?;
+
+        let mut failed_attempts = self.failed_attempts.lock().map_err(|e| e.to_string())?;
+        let failures = failed_attempts.entry(user.username.to_string()).or_insert(0);
+        if user.locked || *failures >= MAX_FAILED_ATTEMPTS {
+            return Err("Your account is locked. Contact your system administrator.".to_string());
+        }
+        if user.password != password {
+            *failures += 1;
+            return Err("You entered an incorrect user name or password.".to_string());
+        }
+        *failures = 0;
+
+        let request_state = self.start_pending(user)?;
+        Ok(prompt(user, request_state))
+    }
+
+    // Checks a TOTP, SMS, email or bypass code; a wrong one keeps the request state usable, as IDCS does
+    pub fn submit_otp(&self, request_state: &str, factor: AuthFactor, otp_code: &str) -> Result<AuthResponse, String> {
+        let user = self.take_pending(request_state)?;
+        let code = match user.mfa {
+            _ if factor == AuthFactor::BypassCode && user.mfa != DemoMfa::None => DEMO_BYPASS_CODE,
+            DemoMfa::Totp { code } if factor == AuthFactor::Totp => code,
+            DemoMfa::Otp { factor: sent, code } if sent == factor => code,
+            _ => {
+                self.pending
+                    .lock()
+                    .map_err(|e| e.to_string())?
+                    .insert(request_state.to_string(), user.username.to_string());
+                return Err(format!("{} is not enrolled for this account.", factor.idcs_name()));
+            }
+        };
+
+        let mut failed_attempts = self.failed_attempts.lock().map_err(|e| e.to_string())?;
+        let failures = failed_attempts.entry(user.username.to_string()).or_insert(0);
+        if *failures >= MAX_FAILED_ATTEMPTS {
+            return Err("Your account is locked. Contact your system administrator.".to_string());
+        }
+        if otp_code.trim() != code {
+            *failures += 1;
+            self.pending
+                .lock()
+                .map_err(|e| e.to_string())?
+                .insert(request_state.to_string(), user.username.to_string());
+            return Err("The passcode you entered is incorrect.".to_string());
+        }
+        *failures = 0;
+
+        let request_state = self.start_pending(user)?;
+        self.verified
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_state.clone());
+        Ok(AuthResponse {
+            status: "success".to_string(),
+            next_auth_factors: vec![],
+            cause: cause("AUTH-1001", "Sign in successful. Press OK to continue."),
+            ..prompt(user, request_state)
+        })
+    }
+
+    // Demo users have a single factor, so selecting it repeats its prompt
+    pub fn select_factor(&self, request_state: &str, factor: AuthFactor) -> Result<AuthResponse, String> {
+        let user = self.pending_user(request_state)?;
+        let response = prompt(user, request_state.to_string());
+        if !response.next_auth_factors.iter().any(|offered| offered == factor.idcs_name()) {
+            return Err(format!("{} is not enrolled for this account.", factor.idcs_name()));
+        }
+        Ok(response)
+    }
+
+    // Pending for the first few polls, then approved or denied depending on the user
+    pub fn poll_push(&self, request_state: &str) -> Result<AuthResponse, String> {
+        let user = self.take_pending(request_state)?;
+        if !matches!(user.mfa, DemoMfa::PushApprove | DemoMfa::PushDeny) {
+            return Err("PUSH is not enrolled for this account.".to_string());
+        }
+        let polls = {
+            let mut push_polls = self.push_polls.lock().map_err(|e| e.to_string())?;
+            let polls = push_polls.entry(user.username.to_string()).or_insert(0);
+            *polls += 1;
+            let count = *polls;
+            if count >= PUSH_ANSWER_AFTER {
+                push_polls.remove(user.username);
+            }
+            count
+        };
+
+        if polls >= PUSH_ANSWER_AFTER && user.mfa == DemoMfa::PushDeny {
+            return Err("Authentication failed: the push notification was denied.".to_string());
+        }
+        let request_state = self.start_pending(user)?;
+        if polls < PUSH_ANSWER_AFTER {
+            return Ok(prompt(user, request_state));
+        }
+        self.verified
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_state.clone());
+        Ok(AuthResponse {
+            status: "success".to_string(),
+            next_auth_factors: vec![],
+            cause: cause("AUTH-1001", "Sign in successful. Press OK to continue."),
+            ..prompt(user, request_state)
+        })
+    }
+
+    pub fn webauthn_challenge(&self, request_state: &str) -> Result<WebAuthnChallenge, String> {
+        let user = self.pending_user(request_state)?;
+        if user.mfa != DemoMfa::SecurityKey {
+            return Err("FIDO_AUTHENTICATOR is not enrolled for this account.".to_string());
+        }
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+        let challenge = URL_SAFE_NO_PAD.encode(bytes);
+        self.challenges
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_state.to_string(), challenge.clone());
+        Ok(WebAuthnChallenge {
+            request_state: request_state.to_string(),
+            challenge,
+            rp_id: None,
+            allow_credentials: vec![],
+            timeout_ms: Some(60_000),
+            user_verification: Some("preferred".to_string()),
+        })
+    }
+
+    // Only checks that the browser signed the challenge this request state was given; there is no stored key
+    pub fn submit_webauthn(&self, request_state: &str, assertion: &WebAuthnAssertion) -> Result<AuthResponse, String> {
+        let user = self.pending_user(request_state)?;
+        let expected = self
+            .challenges
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get(request_state)
+            .cloned()
+            .ok_or_else(|| "Authentication failed: no security key challenge was issued.".to_string())?;
+        let client_data = URL_SAFE_NO_PAD
+            .decode(assertion.client_data_json.trim_end_matches('='))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            .unwrap_or_default();
+        if client_data["type"] != "webauthn.get" || client_data["challenge"] != expected.as_str() {
+            return Err("The security key response doesn't match the challenge.".to_string());
+        }
+
+        self.take_pending(request_state)?;
+        self.challenges.lock().map_err(|e| e.to_string())?.remove(request_state);
+        let request_state = self.start_pending(user)?;
+        self.verified
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_state.clone());
+        Ok(AuthResponse {
+            status: "success".to_string(),
+            next_auth_factors: vec![],
+            cause: cause("AUTH-1001", "Sign in successful. Press OK to continue."),
+            ..prompt(user, request_state)
+        })
+    }
+
+    pub fn security_questions(&self, request_state: &str) -> Result<SecurityQuestionsPrompt, String> {
+        let user = self.pending_user(request_state)?;
+        let DemoMfa::SecurityQuestions { questions } = user.mfa else {
+            return Err("SECURITY_QUESTIONS is not enrolled for this account.".to_string());
+        };
+        Ok(SecurityQuestionsPrompt {
+            request_state: request_state.to_string(),
+            questions: questions
+                .iter()
+                .map(|(id, text, _)| SecurityQuestion {
+                    id: id.to_string(),
+                    text: text.to_string(),
+                })
+                .collect(),
+        })
+    }
+
+    // Wrong answers are asked again under a new request state, as IDCS does; the others count as answered
+    pub fn submit_security_answers(
+        &self,
+        request_state: &str,
+        answers: &[SecurityAnswer],
+    ) -> Result<SecurityAnswersResult, String> {
+        let user = self.pending_user(request_state)?;
+        let DemoMfa::SecurityQuestions { questions } = user.mfa else {
+            return Err("SECURITY_QUESTIONS is not enrolled for this account.".to_string());
+        };
+        let asked = |answer: &&SecurityAnswer| questions.iter().any(|(id, _, _)| *id == answer.question_id);
+        if let Some(unknown) = answers.iter().find(|answer| !asked(answer)) {
+            return Err(format!("Unknown security question: {}", unknown.question_id));
+        }
+
+        let mut failed_attempts = self.failed_attempts.lock().map_err(|e| e.to_string())?;
+        let failures = failed_attempts.entry(user.username.to_string()).or_insert(0);
+        if *failures >= MAX_FAILED_ATTEMPTS {
+            return Err("Your account is locked. Contact your system administrator.".to_string());
+        }
+        let wrong: Vec<SecurityQuestion> = questions
+            .iter()
+            .filter(|(id, _, expected)| {
+                !answers
+                    .iter()
+                    .any(|answer| answer.question_id == *id && answer.answer.trim().eq_ignore_ascii_case(expected))
+            })
+            .map(|(id, text, _)| SecurityQuestion {
+                id: id.to_string(),
+                text: text.to_string(),
+            })
+            .collect();
+        if wrong.is_empty() {
+            *failures = 0;
+        } else {
+            *failures += 1;
+        }
+        drop(failed_attempts);
+
+        self.take_pending(request_state)?;
+        let request_state = self.start_pending(user)?;
+        if !wrong.is_empty() {
+            let response = AuthResponse {
+                cause: cause("AUTH-3021", "One or more of your answers is incorrect."),
+                ..prompt(user, request_state)
+            };
+            return Ok(security_questions::answers_result(response, wrong, answers));
+        }
+        self.verified
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_state.clone());
+        let response = AuthResponse {
+            status: "success".to_string(),
+            next_auth_factors: vec![],
+            cause: cause("AUTH-1001", "Sign in successful. Press OK to continue."),
+            ..prompt(user, request_state)
+        };
+        Ok(security_questions::answers_result(response, vec![], answers))
+    }
+
+    pub fn resend(&self, request_state: &str) -> Result<AuthResponse, String> {
+        let user = self.pending_user(request_state)?;
+        let message = match user.mfa {
+            DemoMfa::Otp { factor: AuthFactor::Email, .. } => "A new passcode was sent to your email address.",
+            DemoMfa::Otp { .. } => "A new passcode was sent to your phone.",
+            _ => return Err("Authentication failed: there is no passcode to resend.".to_string()),
+        };
+        Ok(AuthResponse {
+            cause: cause("AUTH-1120", message),
+            ..prompt(user, request_state.to_string())
+        })
+    }
+
+    pub fn complete(&self, request_state: &str) -> Result<Value, String> {
+        let user = self.take_pending(request_state)?;
+        // A verified second factor, or a bypass code in its place
+        if self.verified.lock().map_err(|e| e.to_string())?.remove(request_state) {
+            return Ok(profile(user));
+        }
+        match user.mfa {
+            DemoMfa::None => Ok(profile(user)),
+            // `poll_push` marks the request state verified once the simulated authenticator approves
+            DemoMfa::PushApprove => Err("Authentication failed: the push notification hasn't been approved yet.".to_string()),
+            DemoMfa::PushDeny => Err("Authentication failed: the push notification was denied.".to_string()),
+            DemoMfa::Totp { .. } | DemoMfa::Otp { .. } => Err("Authentication failed: a passcode is required for this account.".to_string()),
+            DemoMfa::SecurityKey => Err("Authentication failed: a security key is required for this account.".to_string()),
+            DemoMfa::SecurityQuestions { .. } => Err("Authentication failed: security answers are required for this account.".to_string()),
+        }
+    }
+
+    fn start_pending(&self, user: &DemoUser) -> Result<String, String> {
+        let request_state = format!("demo-{}-{}", user.username, chrono::Local::now().timestamp_millis());
+        self.pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(request_state.clone(), user.username.to_string());
+        Ok(request_state)
+    }
+
+    fn pending_user(&self, request_state: &str) -> Result<&'static DemoUser, String> {
+        let username = self
+            .pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .get(request_state)
+            .cloned()
+            .ok_or_else(|| "Authentication failed: unknown or expired request state.".to_string())?;
+        find_user(&username).ok_or_else(|| format!("Unknown demo user: {}", username))
+    }
+
+    fn take_pending(&self, request_state: &str) -> Result<&'static DemoUser, String> {
+        let username = self
+            .pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(request_state)
+            .ok_or_else(|| "Authentication failed: unknown or expired request state.".to_string())?;
+        find_user(&username).ok_or_else(|| format!("Unknown demo user: {}", username))
+    }
+}
+
+fn find_user(username: &str) -> Option<&'static DemoUser> {
+    DEMO_USERS.iter().find(|u| u.username.eq_ignore_ascii_case(username))
+}
+
+// The response to a correct password: success, or the prompt for the user's second factor
+fn prompt(user: &DemoUser, request_state: String) -> AuthResponse {
+    let (status, next_auth_factors, cause) = match user.mfa {
+        DemoMfa::None => ("success", vec![], cause("AUTH-1001", "Sign in successful. Press OK to continue.")),
+        DemoMfa::PushApprove | DemoMfa::PushDeny => (
+            "pending",
+            vec!["PUSH".to_string()],
+            cause("AUTH-1112", "A notification was sent to your Oracle Mobile Authenticator app."),
+        ),
+        DemoMfa::Totp { .. } => (
+            "pending",
+            vec!["TOTP".to_string()],
+            cause("AUTH-1108", "Enter the passcode generated by your authenticator app."),
+        ),
+        DemoMfa::Otp { factor: AuthFactor::Email, .. } => (
+            "pending",
+            vec!["EMAIL".to_string()],
+            cause("AUTH-1109", "A passcode was sent to your email address."),
+        ),
+        DemoMfa::Otp { factor, .. } => (
+            "pending",
+            vec![factor.idcs_name().to_string()],
+            cause("AUTH-1110", "A passcode was sent to your phone."),
+        ),
+        DemoMfa::SecurityKey => (
+            "pending",
+            vec!["FIDO_AUTHENTICATOR".to_string()],
+            cause("AUTH-1113", "Use your security key to sign in."),
+        ),
+        DemoMfa::SecurityQuestions { .. } => (
+            "pending",
+            vec!["SECURITY_QUESTIONS".to_string()],
+            cause("AUTH-1114", "Answer your security questions."),
+        ),
+    };
+
+    AuthResponse {
+        status: status.to_string(),
+        ec_id: format!("demo-ec-{}", user.username),
+        display_name: user.display_name.to_string(),
+        next_auth_factors,
+        cause,
+        next_op: vec!["credSubmit".to_string()],
+        scenario: "AUTHENTICATION".to_string(),
+        request_state,
+        authn_token: None,
+    }
+}
+
+fn cause(code: &str, message: &str) -> Vec<CauseMessage> {
+    vec![CauseMessage {
+        code: code.to_string(),
+        message: message.to_string(),
+    }]
+}
+
+// Shape matches the IDCS `/admin/v1/Me` response closely enough for the UI
+fn profile(user: &DemoUser) -> Value {
+    let (given_name, family_name) = user.display_name.split_once(' ').unwrap_or((user.display_name, ""));
+    json!({
+        "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+        "id": format!("demo-{}", user.username),
+        "userName": user.username,
+        "displayName": user.display_name,
+        "name": {
+            "givenName": given_name,
+            "familyName": family_name,
+            "formatted": user.display_name
+        },
+        "emails": [{ "value": user.email, "type": "work", "primary": true }],
+        "groups": user.groups.iter().map(|g| json!({ "display": g })).collect::<Vec<_>>(),
+        "active": true
+    })
+}
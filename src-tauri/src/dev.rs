@@ -0,0 +1,79 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::session::{AuthSession, AuthSessionState};
+
+const SNAPSHOT_FILE: &str = "dev-snapshot.json";
+
+// State captured before a webview reload so the UI can pick up where it left off. Settings aren't part of it: they
+// are already on disk, and an old snapshot must not put the app on settings its HTTP client wasn't built with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    // Opaque session view handed over by the frontend, e.g. the signed-in profile
+    pub session: Option<Value>,
+    // Sign-in in progress, so a reload mid-MFA continues from the same step
+    #[serde(default)]
+    pub auth_session: AuthSession,
+    pub taken_at: String,
+}
+
+#[derive(Default)]
+pub struct SnapshotState(pub Mutex<Option<StateSnapshot>>);
+
+#[command]
+pub fn debug_snapshot_state(
+    app_handle: AppHandle,
+    snapshot_state: State<SnapshotState>,
+    auth_session: State<AuthSessionState>,
+    session: Option<Value>,
+) -> Result<(), CommandError> {
+    let snapshot = StateSnapshot {
+        session,
+        auth_session: auth_session.0.lock().map_err(|e| e.to_string())?.clone(),
+        taken_at: Local::now().to_rfc3339(),
+    };
+
+    // Also write it to disk so the snapshot survives a full `tauri dev` rebuild
+    let path = snapshot_path(&app_handle)?;
+    let contents = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+    log::debug!("State snapshot written to {}", path.display());
+
+    *snapshot_state.0.lock().map_err(|e| e.to_string())? = Some(snapshot);
+    Ok(())
+}
+
+#[command]
+pub fn debug_restore_state(
+    app_handle: AppHandle,
+    snapshot_state: State<SnapshotState>,
+    auth_session: State<AuthSessionState>,
+) -> Result<Option<StateSnapshot>, CommandError> {
+    let mut snapshot = snapshot_state.0.lock().map_err(|e| e.to_string())?.clone();
+    if snapshot.is_none() {
+        let path = snapshot_path(&app_handle)?;
+        if path.exists() {
+            let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            snapshot = serde_json::from_str(&contents).ok();
+        }
+    }
+
+    if let Some(snapshot) = &snapshot {
+        *auth_session.0.lock().map_err(|e| e.to_string())? = snapshot.auth_session.clone();
+        log::debug!("State restored from snapshot taken at {}", snapshot.taken_at);
+    }
+
+    Ok(snapshot)
+}
+
+fn snapshot_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(SNAPSHOT_FILE))
+}
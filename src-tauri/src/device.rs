@@ -0,0 +1,167 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::Store;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::platform;
+use crate::schema::{self, Migration};
+
+// Sent with every request to IDCS, so the tenant's logs can tie sign-ins to this device
+pub const HEADER: &str = "X-Device-Thumbprint";
+// The private key lives in the OS keychain; this machine-local store only holds the public half and the record
+const DEVICE_STORE_FILE: &str = "device.json";
+// Format 1: `thumbprint`, `public_key`, `created_at` and `sessions`, oldest first
+const MIGRATIONS: &[Migration] = &[schema::add_version];
+pub(crate) const KEYCHAIN_ACCOUNT: &str = "device/binding-key";
+const MAX_SESSIONS: usize = 100;
+
+struct DeviceKey {
+    signing_key: SigningKey,
+    jwk: Value,
+    thumbprint: String,
+    created_at: DateTime<Utc>,
+}
+
+// Set by `init`; None when the keychain couldn't hold the key, in which case sign-ins aren't device bound
+static DEVICE_KEY: OnceLock<DeviceKey> = OnceLock::new();
+
+// A sign-in established from this device. `signature` is the device key's ES256 signature over
+// `<thumbprint>.<user>.<at>`, so a posture check can verify the entry against `public_key`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSession {
+    pub at: DateTime<Utc>,
+    pub profile: String,
+    pub user: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceBindingInfo {
+    pub bound: bool,
+    pub thumbprint: Option<String>,
+    // Public JWK of the device key
+    pub public_key: Option<Value>,
+    pub created_at: Option<DateTime<Utc>>,
+    // Where the private key is kept, e.g. "macOS Keychain"
+    pub key_storage: Option<String>,
+    // Newest first
+    pub sessions: Vec<DeviceSession>,
+}
+
+// The device key and the sign-ins made with it, for zero-trust posture reporting
+#[command]
+pub fn get_device_binding_info(app_handle: AppHandle) -> Result<DeviceBindingInfo, CommandError> {
+    let key = DEVICE_KEY.get();
+    let mut sessions = load_sessions(&app_handle)?;
+    sessions.reverse();
+    Ok(DeviceBindingInfo {
+        bound: key.is_some(),
+        thumbprint: key.map(|key| key.thumbprint.clone()),
+        public_key: key.map(|key| key.jwk.clone()),
+        created_at: key.map(|key| key.created_at),
+        key_storage: key.map(|_| platform::keychain_location(KEYCHAIN_ACCOUNT)),
+        sessions,
+    })
+}
+
+// Loads the device key, generating it on first run; call at startup before the HTTP client is built
+pub fn init(app_handle: &AppHandle) -> Result<(), String> {
+    let (signing_key, created) = match platform::keychain_get(KEYCHAIN_ACCOUNT)? {
+        Some(encoded) => {
+            let bytes = URL_SAFE_NO_PAD.decode(encoded.trim()).map_err(|e| e.to_string())?;
+            let key = SigningKey::from_slice(&bytes).map_err(|e| format!("Invalid device key: {}", e))?;
+            (key, false)
+        }
+        None => {
+            let key = crate::dpop::random_signing_key()?;
+            platform::keychain_set(KEYCHAIN_ACCOUNT, &URL_SAFE_NO_PAD.encode(key.to_bytes()))?;
+            (key, true)
+        }
+    };
+    let (jwk, thumbprint) = crate::dpop::public_jwk(&signing_key);
+
+    let store = open_store(app_handle)?;
+    // A key restored from the keychain after the store was deleted starts a new record
+    let created_at = store
+        .get("created_at")
+        .filter(|_| !created && store.get("thumbprint").as_ref().and_then(Value::as_str) == Some(thumbprint.as_str()))
+        .and_then(|value| serde_json::from_value(value).ok());
+    let created_at = match created_at {
+        Some(created_at) => created_at,
+        None => {
+            let now = Utc::now();
+            store.set("created_at", json!(now));
+            store.set("thumbprint", json!(thumbprint));
+            store.set("public_key", jwk.clone());
+            store.set("sessions", json!([]));
+            store.save().map_err(|e| e.to_string())?;
+            now
+        }
+    };
+    if created {
+        log::info!("Generated the device binding key {}", thumbprint);
+    }
+
+    let _ = DEVICE_KEY.set(DeviceKey {
+        signing_key,
+        jwk,
+        thumbprint,
+        created_at,
+    });
+    Ok(())
+}
+
+pub(crate) fn thumbprint() -> Option<&'static str> {
+    DEVICE_KEY.get().map(|key| key.thumbprint.as_str())
+}
+
+// Adds a signed entry for a sign-in that just completed; keeps the latest `MAX_SESSIONS`
+pub(crate) fn record_session(app_handle: &AppHandle, profile: &Value) -> Result<(), String> {
+    let Some(key) = DEVICE_KEY.get() else {
+        return Ok(());
+    };
+    let at = Utc::now();
+    let user = profile["userName"].as_str().unwrap_or_default().to_string();
+    let signature: Signature = key
+        .signing_key
+        .sign(format!("{}.{}.{}", key.thumbprint, user, at.to_rfc3339()).as_bytes());
+
+    let mut sessions = load_sessions(app_handle)?;
+    sessions.push(DeviceSession {
+        at,
+        profile: crate::profiles::active_profile(app_handle)?.name,
+        user,
+        signature: URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+    });
+    let excess = sessions.len().saturating_sub(MAX_SESSIONS);
+    sessions.drain(..excess);
+
+    let store = open_store(app_handle)?;
+    store.set("sessions", json!(sessions));
+    store.save().map_err(|e| e.to_string())
+}
+
+fn load_sessions(app_handle: &AppHandle) -> Result<Vec<DeviceSession>, String> {
+    let store = open_store(app_handle)?;
+    Ok(store
+        .get("sessions")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+fn open_store(app_handle: &AppHandle) -> Result<Arc<Store<Wry>>, String> {
+    schema::open(app_handle, &device_store_path(app_handle)?, MIGRATIONS)
+}
+
+fn device_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::local_data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(DEVICE_STORE_FILE))
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::ACCEPT_LANGUAGE;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, TokenResponse};
+use crate::auth_client::AuthClient;
+use crate::circuit;
+use crate::demo::DemoState;
+use crate::dpop::{self, DpopKey};
+use crate::error::{AuthError, CommandError};
+use crate::i18n;
+use crate::lifecycle::{self, AppState};
+use crate::middleware::command;
+use crate::policy::{self, PolicyState};
+use crate::profiles;
+use crate::session::{ScopeGrant, Session};
+
+const DEVICE_PATH: &str = "/oauth2/v1/device";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+// How often to poll when IDCS doesn't say, and how much to back off when it asks to slow down (RFC 8628)
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+const SLOW_DOWN: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+// What the user needs to approve the sign-in on another device
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceLogin {
+    pub user_code: String,
+    pub verification_uri: String,
+    // `verification_uri` with the code filled in, e.g. for a QR code
+    pub verification_uri_complete: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Counts device logins; a newer one stops the polling of the older ones
+#[derive(Default)]
+pub struct DeviceLoginState(AtomicU64);
+
+// Signs in where typing a password isn't practical, e.g. kiosks and headless machines, with the OAuth device
+// authorization grant. Returns the code to enter at the verification URL from another device; IDCS is polled in
+// the background until the user approves, then `device-login-approved` is emitted with the profile, or
+// `device-login-failed` with the error.
+#[command(configured)]
+pub async fn start_device_login(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    device_login: State<'_, DeviceLoginState>,
+) -> Result<DeviceLogin, CommandError> {
+    if app_handle.state::<DemoState>().enabled {
+        return Err("Demo mode signs in with a username and password".into());
+    }
+    policy::check_allowed_hours(&app_handle.state::<PolicyState>().0)?;
+
+    let authorization = request_device_code(&auth_client).await?;
+    let generation = device_login.0.fetch_add(1, Ordering::SeqCst) + 1;
+    let login = DeviceLogin {
+        user_code: authorization.user_code.clone(),
+        verification_uri: authorization.verification_uri.clone(),
+        verification_uri_complete: authorization.verification_uri_complete.clone(),
+        expires_at: Utc::now() + chrono::Duration::seconds(authorization.expires_in as i64),
+    };
+    crate::guest::set(&app_handle, false);
+    lifecycle::transition(&app_handle, AppState::Authenticating);
+    tauri::async_runtime::spawn(poll(app_handle.clone(), authorization, generation));
+    log::info!("Started a device login; waiting for approval at {}", login.verification_uri);
+    Ok(login)
+}
+
+async fn request_device_code(client: &AuthClient) -> Result<DeviceAuthorization, AuthError> {
+    let request = client
+        .http()
+        .post(client.url(DEVICE_PATH))
+        .header(ACCEPT_LANGUAGE, i18n::accept_language());
+    let scope = profiles::user_scope();
+    let response = circuit::send(auth::as_client(request, &[("scope", scope.as_str())])?).await?;
+
+    let status = response.status();
+    let response_text = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(auth::response_error(status, &response_text).context("Failed to start the device login"));
+    }
+    Ok(serde_json::from_str(&response_text)
+        .map_err(|e| format!("Failed to parse device authorization response: {}", e))?)
+}
+
+async fn poll(app_handle: AppHandle, authorization: DeviceAuthorization, generation: u64) {
+    let result = match wait_for_approval(&app_handle, &authorization, generation).await {
+        Ok(Some(session)) => auth::finish_sign_in(&app_handle, session).map_err(AuthError::from),
+        // Replaced by a newer device login
+        Ok(None) => return,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(profile) => {
+            log::info!("Device login approved");
+            crate::bus::emit(&app_handle, "device-login-approved", &profile);
+        }
+        Err(e) => {
+            auth::sign_in_error(&app_handle, &e);
+            crate::bus::emit(&app_handle, "device-login-failed", &e);
+        }
+    }
+}
+
+// The session once the user approves; None when a newer device login replaced this one
+async fn wait_for_approval(
+    app_handle: &AppHandle,
+    authorization: &DeviceAuthorization,
+    generation: u64,
+) -> Result<Option<Session>, AuthError> {
+    let deadline = Instant::now() + Duration::from_secs(authorization.expires_in);
+    let mut interval = authorization
+        .interval
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_INTERVAL)
+        .max(Duration::from_secs(1));
+    let dpop_key = if profiles::dpop_enabled() { Some(DpopKey::generate()?) } else { None };
+    let client = app_handle.state::<AuthClient>();
+
+    let token_response = loop {
+        tokio::time::sleep(interval).await;
+        if app_handle.state::<DeviceLoginState>().0.load(Ordering::SeqCst) != generation {
+            return Ok(None);
+        }
+        if Instant::now() >= deadline {
+            return Err(AuthError::Rejected {
+                code: Some("expired_token".to_string()),
+                message: "The device code expired before the sign-in was approved".to_string(),
+            });
+        }
+
+        let mut request = client
+            .http()
+            .post(client.url(&profiles::token_path()))
+            .header(ACCEPT_LANGUAGE, i18n::accept_language());
+        if let Some((name, value)) = dpop_key.as_ref().map(|key| key.token_request_header()) {
+            request = request.header(name, value);
+        }
+        let form = [("grant_type", DEVICE_CODE_GRANT), ("device_code", authorization.device_code.as_str())];
+        let response = match circuit::send(auth::as_client(request, &form)?).await {
+            Ok(response) => response,
+            // Keep polling through network trouble until the code expires
+            Err(e) => {
+                log::warn!("Failed to poll for the device login: {}", e);
+                continue;
+            }
+        };
+
+        let status = response.status();
+        let response_text = response.text().await.map_err(|e| e.to_string())?;
+        if status.is_success() {
+            break serde_json::from_str::<TokenResponse>(&response_text)
+                .map_err(|e| format!("Failed to parse token response: {}", e))?;
+        }
+        let body: Value = serde_json::from_str(&response_text).unwrap_or_default();
+        match body["error"].as_str() {
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += SLOW_DOWN,
+            _ => return Err(auth::response_error(status, &response_text).context("Device login failed")),
+        }
+    };
+
+    if let Some(key) = &dpop_key {
+        key.bind(&token_response.access_token);
+    }
+    let authorization = dpop::authorization(&token_response.access_token, dpop_key.as_deref());
+    let profile = auth::get_user_profile(app_handle, &client, &authorization).await?;
+    Ok(Some(Session {
+        profile,
+        expires_at: Some(Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64)),
+        access_token: Some(token_response.access_token),
+        client_ip: None,
+        dpop_key,
+        scopes: Some(ScopeGrant::new(&profiles::user_scope(), token_response.scope.as_deref())),
+        refresh_token: token_response.refresh_token,
+    }))
+}
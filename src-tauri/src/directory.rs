@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::State;
+
+use crate::admin::require_capability;
+use crate::auth_client::AuthClient;
+use crate::cache::{CacheState, DIRECTORY_PREFIX};
+use crate::demo::DemoState;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::session::SessionState;
+use crate::tenant::get_settings;
+
+const PAGE_SIZE: u32 = 20;
+// Keystrokes closer together than this only trigger one IDCS query
+const DEBOUNCE: Duration = Duration::from_millis(250);
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const ATTRIBUTES: &str = "userName,displayName,emails,active";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryEntry {
+    pub id: String,
+    pub user_name: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryPage {
+    pub results: Vec<DirectoryEntry>,
+    pub total: u32,
+    // Pass back as `cursor` to fetch the next page
+    pub next_cursor: Option<u32>,
+    // A newer search arrived while this one was waiting; the frontend should ignore it
+    pub superseded: bool,
+}
+
+#[derive(Default)]
+pub struct DirectoryState {
+    generation: AtomicU64,
+}
+
+#[command(session)]
+pub async fn search_directory(
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    demo: State<'_, DemoState>,
+    directory: State<'_, DirectoryState>,
+    cache: State<'_, CacheState>,
+    query: String,
+    cursor: Option<u32>,
+) -> Result<DirectoryPage, CommandError> {
+    require_capability(&session, |c| c.search_users, "search the directory")?;
+
+    let query = query.trim().to_lowercase();
+    let start_index = cursor.unwrap_or(1).max(1);
+    if query.is_empty() {
+        return Ok(DirectoryPage::default());
+    }
+
+    let key = format!("{}{}:{}", DIRECTORY_PREFIX, query, start_index);
+    if let Some(page) = cache.get::<DirectoryPage>(&key) {
+        return Ok(page);
+    }
+
+    // Only the latest keystroke's search goes to the server
+    let generation = directory.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    tokio::time::sleep(DEBOUNCE).await;
+    if directory.generation.load(Ordering::SeqCst) != generation {
+        return Ok(DirectoryPage {
+            superseded: true,
+            ..Default::default()
+        });
+    }
+
+    let page = if demo.enabled {
+        search_demo(&query, start_index)
+    } else {
+        search_idcs(&auth_client, &session.bearer_token()?, &query, start_index).await?
+    };
+
+    cache.insert(&key, &page, CACHE_TTL);
+    Ok(page)
+}
+
+// Prefix match on the user name, substring match on the display name and email
+async fn search_idcs(client: &AuthClient, bearer_token: &str, query: &str, start_index: u32) -> Result<DirectoryPage, String> {
+    let value = scim_string(query);
+    let filter = format!(
+        "userName sw {v} or displayName co {v} or emails.value sw {v}",
+        v = value
+    );
+    let path = format!(
+        "/admin/v1/Users?filter={}&attributes={}&count={}&startIndex={}&sortBy=displayName",
+        encode_query_value(&filter),
+        ATTRIBUTES,
+        PAGE_SIZE,
+        start_index
+    );
+    let response = get_settings(client, bearer_token, &path).await?;
+
+    let results: Vec<DirectoryEntry> = response["Resources"]
+        .as_array()
+        .map(|users| users.iter().filter_map(parse_user).collect())
+        .unwrap_or_default();
+    let total = response["totalResults"].as_u64().unwrap_or(results.len() as u64) as u32;
+    let next = start_index + results.len() as u32;
+
+    Ok(DirectoryPage {
+        next_cursor: (next <= total && !results.is_empty()).then_some(next),
+        results,
+        total,
+        superseded: false,
+    })
+}
+
+fn search_demo(query: &str, start_index: u32) -> DirectoryPage {
+    let matches: Vec<DirectoryEntry> = DemoState::users()
+        .iter()
+        .filter(|user| {
+            user.username.starts_with(query)
+                || user.display_name.to_lowercase().contains(query)
+                || user.email.starts_with(query)
+        })
+        .map(|user| DirectoryEntry {
+            id: format!("demo-{}", user.username),
+            user_name: user.username.to_string(),
+            display_name: Some(user.display_name.to_string()),
+            email: Some(user.email.to_string()),
+            active: !user.locked,
+        })
+        .collect();
+
+    let total = matches.len() as u32;
+    let results: Vec<DirectoryEntry> = matches
+        .into_iter()
+        .skip(start_index as usize - 1)
+        .take(PAGE_SIZE as usize)
+        .collect();
+    let next = start_index + results.len() as u32;
+
+    DirectoryPage {
+        next_cursor: (next <= total && !results.is_empty()).then_some(next),
+        results,
+        total,
+        superseded: false,
+    }
+}
+
+fn parse_user(user: &Value) -> Option<DirectoryEntry> {
+    Some(DirectoryEntry {
+        id: user["id"].as_str()?.to_string(),
+        user_name: user["userName"].as_str()?.to_string(),
+        display_name: user["displayName"].as_str().map(|s| s.to_string()),
+        email: user["emails"]
+            .as_array()
+            .and_then(|emails| emails.iter().find_map(|email| email["value"].as_str()))
+            .map(|s| s.to_string()),
+        active: user["active"].as_bool().unwrap_or(true),
+    })
+}
+
+// SCIM string literal with quotes and backslashes escaped
+fn scim_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,145 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+
+pub const HEADER: &str = "DPoP";
+
+// Ephemeral P-256 key a session's tokens are bound to; it lives in memory only, with the session
+pub struct DpopKey {
+    signing_key: SigningKey,
+    jwk: Value,
+    // RFC 7638 thumbprint of `jwk`
+    thumbprint: String,
+    // `ath` of the latest access token issued for this key, once there is one
+    token_hash: RwLock<Option<String>>,
+}
+
+impl std::fmt::Debug for DpopKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DpopKey").field("thumbprint", &self.thumbprint).finish()
+    }
+}
+
+// Keys still held by a session or a sign-in in progress, so `attach_proof` can find the one a request needs
+static KEYS: Mutex<Vec<Weak<DpopKey>>> = Mutex::new(Vec::new());
+
+impl DpopKey {
+    pub fn generate() -> Result<Arc<Self>, String> {
+        let signing_key = random_signing_key()?;
+        let (jwk, thumbprint) = public_jwk(&signing_key);
+
+        let key = Arc::new(Self {
+            signing_key,
+            jwk,
+            thumbprint,
+            token_hash: RwLock::new(None),
+        });
+        let mut keys = KEYS.lock().map_err(|e| e.to_string())?;
+        keys.retain(|key| key.strong_count() > 0);
+        keys.push(Arc::downgrade(&key));
+        Ok(key)
+    }
+
+    // Marks a token request as needing a proof from this key
+    pub fn token_request_header(&self) -> (&'static str, &str) {
+        (HEADER, &self.thumbprint)
+    }
+
+    // Records the access token issued for this key, so calls made with it get proofs; a renewed session's
+    // token replaces the previous one
+    pub fn bind(&self, access_token: &str) {
+        if let Ok(mut hash) = self.token_hash.write() {
+            *hash = Some(token_hash(access_token));
+        }
+    }
+
+    // Proof JWT for one request; `ath` ties it to the access token sent alongside
+    fn proof(&self, method: &str, url: &reqwest::Url, ath: Option<&str>) -> Result<String, String> {
+        let mut htu = url.clone();
+        htu.set_query(None);
+        htu.set_fragment(None);
+        let mut jti = [0u8; 16];
+        getrandom::getrandom(&mut jti).map_err(|e| e.to_string())?;
+
+        let header = json!({ "typ": "dpop+jwt", "alg": "ES256", "jwk": self.jwk });
+        let mut claims = json!({
+            "jti": URL_SAFE_NO_PAD.encode(jti),
+            "htm": method,
+            "htu": htu.as_str(),
+            "iat": chrono::Utc::now().timestamp()
+        });
+        if let Some(ath) = ath {
+            claims["ath"] = json!(ath);
+        }
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature: Signature = self.signing_key.sign(signing_input.as_bytes());
+        Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.to_bytes())))
+    }
+}
+
+// The Authorization header for a user access token, DPoP when it is bound to a key
+pub fn authorization(access_token: &str, key: Option<&DpopKey>) -> String {
+    match key {
+        Some(_) => format!("DPoP {}", access_token),
+        None => format!("Bearer {}", access_token),
+    }
+}
+
+// Adds a fresh proof to a request that needs one: token requests marked by `token_request_header`, and calls
+// made with a DPoP access token. Called for every attempt, since failover changes the URL the proof covers.
+pub(crate) fn attach_proof(request: &mut reqwest::Request) -> Result<(), String> {
+    let header = |name| request.headers().get(name).and_then(|value| value.to_str().ok());
+    let ath = header(AUTHORIZATION.as_str())
+        .and_then(|value| value.strip_prefix("DPoP "))
+        .map(token_hash);
+    let thumbprint = header(HEADER).map(str::to_string);
+    if ath.is_none() && thumbprint.is_none() {
+        return Ok(());
+    }
+
+    let key = KEYS.lock().map_err(|e| e.to_string())?.iter().filter_map(Weak::upgrade).find(|key| {
+        match &ath {
+            Some(ath) => key.token_hash.read().is_ok_and(|hash| hash.as_ref() == Some(ath)),
+            None => thumbprint.as_deref() == Some(key.thumbprint.as_str()),
+        }
+    });
+    let key = key.ok_or("The DPoP key of this session is gone; sign in again")?;
+    let proof = key.proof(request.method().as_str(), request.url(), ath.as_deref())?;
+    request
+        .headers_mut()
+        .insert(HEADER, HeaderValue::from_str(&proof).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+pub(crate) fn random_signing_key() -> Result<SigningKey, String> {
+    loop {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+        // Only fails for the vanishingly rare values outside the curve order
+        if let Ok(key) = SigningKey::from_slice(&bytes) {
+            return Ok(key);
+        }
+    }
+}
+
+// Public JWK of a P-256 key and its RFC 7638 thumbprint
+pub(crate) fn public_jwk(signing_key: &SigningKey) -> (Value, String) {
+    let point = signing_key.verifying_key().to_encoded_point(false);
+    let coordinate = |value: Option<&p256::FieldBytes>| value.map(|bytes| URL_SAFE_NO_PAD.encode(bytes));
+    let (x, y) = (coordinate(point.x()), coordinate(point.y()));
+    // Members in lexicographic order, as the thumbprint requires
+    let jwk = json!({ "crv": "P-256", "kty": "EC", "x": x, "y": y });
+    let thumbprint = URL_SAFE_NO_PAD.encode(Sha256::digest(jwk.to_string().as_bytes()));
+    (jwk, thumbprint)
+}
+
+fn token_hash(access_token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()))
+}
@@ -0,0 +1,226 @@
+use reqwest::{RequestBuilder, Response, Url};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::branding::DEFAULT_IDCS_URL;
+use crate::circuit::is_failure;
+use crate::config::{validate_idcs_url, AppConfig};
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::profiles::active_profile;
+
+// A URL that failed is tried after the healthy ones until this has passed
+const UNHEALTHY_FOR: Duration = Duration::from_secs(120);
+
+struct Endpoint {
+    url: String,
+    unhealthy_until: Option<Instant>,
+}
+
+struct Endpoints {
+    list: Vec<Endpoint>,
+    // Sticky: keeps being used first until it fails
+    selected: usize,
+}
+
+// The IDCS URLs in use; empty until `load` has run
+static ENDPOINTS: Mutex<Endpoints> = Mutex::new(Endpoints {
+    list: Vec::new(),
+    selected: 0,
+});
+
+// Picks up the tenant URLs: `OCI_IDCS_URL`, else the active profile's list, else `idcs.base_url`, else the
+// built-in URL. Call at startup and whenever one of them changes.
+pub fn load(app_handle: &AppHandle) -> Result<(), String> {
+    let urls = match std::env::var("OCI_IDCS_URL") {
+        Ok(url) => vec![validate_idcs_url(&url)?],
+        Err(_) => {
+            let urls = active_profile(app_handle)?.idcs_urls;
+            if urls.is_empty() {
+                let config = AppConfig::load(app_handle).map_err(|e| e.to_string())?;
+                vec![config.idcs.base_url.unwrap_or_else(|| DEFAULT_IDCS_URL.to_string())]
+            } else {
+                urls
+            }
+        }
+    };
+    let mut endpoints = ENDPOINTS.lock().map_err(|e| e.to_string())?;
+    let selected = endpoints
+        .list
+        .get(endpoints.selected)
+        .and_then(|current| urls.iter().position(|url| *url == current.url))
+        .unwrap_or(0);
+    *endpoints = Endpoints {
+        list: urls
+            .into_iter()
+            .map(|url| Endpoint { url, unhealthy_until: None })
+            .collect(),
+        selected,
+    };
+    Ok(())
+}
+
+// The URL requests currently go to
+#[command]
+pub fn get_idcs_endpoint() -> Result<String, CommandError> {
+    Ok(base_url())
+}
+
+// Every URL in use, in profile order
+pub fn urls() -> Vec<String> {
+    let endpoints = match ENDPOINTS.lock() {
+        Ok(endpoints) => endpoints,
+        Err(_) => return vec![base_url()],
+    };
+    if endpoints.list.is_empty() {
+        return vec![DEFAULT_IDCS_URL.to_string()];
+    }
+    endpoints.list.iter().map(|endpoint| endpoint.url.clone()).collect()
+}
+
+// The tenant URL to build IDCS request URLs from
+pub fn base_url() -> String {
+    ENDPOINTS
+        .lock()
+        .ok()
+        .and_then(|endpoints| {
+            endpoints
+                .list
+                .get(endpoints.selected)
+                .map(|endpoint| endpoint.url.clone())
+        })
+        .unwrap_or_else(|| DEFAULT_IDCS_URL.to_string())
+}
+
+// Sends the request to the selected IDCS URL, keeping its path and query, and fails over to the
+// profile's other URLs in order on connection errors, 5xx and 429
+pub async fn send(request: RequestBuilder) -> Result<Response, String> {
+    let candidates = candidates();
+    let (client, request) = request.build_split();
+    let mut request = request.map_err(|e| e.to_string())?;
+    if candidates.is_empty() {
+        crate::dpop::attach_proof(&mut request)?;
+        return client.execute(request).await.map_err(|e| e.to_string());
+    }
+
+    let mut last = Err("No IDCS URL to try".to_string());
+    for base in candidates {
+        // IDCS requests have JSON or form bodies, which can always be resent
+        let mut attempt = request
+            .try_clone()
+            .ok_or_else(|| "Request body can't be resent".to_string())?;
+        *attempt.url_mut() = rebase(request.url(), &base)?;
+        crate::dpop::attach_proof(&mut attempt)?;
+        match client.execute(attempt).await {
+            Ok(response) if !is_failure(response.status()) => {
+                select(&base);
+                return Ok(response);
+            }
+            result => {
+                mark_unhealthy(&base);
+                last = result.map_err(|e| e.to_string());
+            }
+        }
+    }
+    last
+}
+
+// Selected URL first, then the rest in profile order, with recently failed ones last
+fn candidates() -> Vec<String> {
+    let Ok(endpoints) = ENDPOINTS.lock() else {
+        return Vec::new();
+    };
+    let now = Instant::now();
+    let mut order: Vec<usize> = (0..endpoints.list.len()).collect();
+    order.sort_by_key(|index| {
+        let healthy = endpoints.list[*index]
+            .unhealthy_until
+            .map(|until| until <= now)
+            .unwrap_or(true);
+        (!healthy, *index != endpoints.selected, *index)
+    });
+    order
+        .into_iter()
+        .map(|index| endpoints.list[index].url.clone())
+        .collect()
+}
+
+// Looked up by URL since the profile may have been switched while the request was in flight
+fn select(url: &str) {
+    let Ok(mut endpoints) = ENDPOINTS.lock() else {
+        return;
+    };
+    let Some(index) = endpoints.list.iter().position(|endpoint| endpoint.url == url) else {
+        return;
+    };
+    if endpoints.selected != index {
+        log::warn!("Switched IDCS URL to {}", url);
+        endpoints.selected = index;
+    }
+    endpoints.list[index].unhealthy_until = None;
+}
+
+fn mark_unhealthy(url: &str) {
+    log::warn!("IDCS URL {} is failing", url);
+    if let Ok(mut endpoints) = ENDPOINTS.lock() {
+        if let Some(endpoint) = endpoints.list.iter_mut().find(|endpoint| endpoint.url == url) {
+            endpoint.unhealthy_until = Some(Instant::now() + UNHEALTHY_FOR);
+        }
+    }
+}
+
+// Tenant URLs are bare hosts, so the request's path is already the whole IDCS path
+fn rebase(url: &Url, base: &str) -> Result<Url, String> {
+    let mut rebased = Url::parse(base).map_err(|e| format!("Invalid IDCS URL {}: {}", base, e))?;
+    rebased.set_path(url.path());
+    rebased.set_query(url.query());
+    Ok(rebased)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str = "https://idcs-a.identity.oraclecloud.com";
+    const B: &str = "https://idcs-b.identity.oraclecloud.com";
+    const C: &str = "https://idcs-c.identity.oraclecloud.com";
+
+    #[test]
+    fn rebase_keeps_the_path_and_query() {
+        let url = Url::parse(&format!("{}/admin/v1/Users?filter=userName%20eq%20%22jdoe%22", A)).unwrap();
+        assert_eq!(
+            rebase(&url, B).unwrap().as_str(),
+            format!("{}/admin/v1/Users?filter=userName%20eq%20%22jdoe%22", B)
+        );
+    }
+
+    #[test]
+    fn rebase_rejects_an_invalid_base() {
+        let url = Url::parse(&format!("{}/oauth2/v1/token", A)).unwrap();
+        assert!(rebase(&url, "not a url").is_err());
+    }
+
+    // One test, since the endpoints are shared by the whole process
+    #[test]
+    fn candidates_put_the_selected_url_first_and_failing_ones_last() {
+        *ENDPOINTS.lock().unwrap() = Endpoints {
+            list: [A, B, C].map(|url| Endpoint { url: url.to_string(), unhealthy_until: None }).into(),
+            selected: 0,
+        };
+        assert_eq!(candidates(), [A, B, C]);
+
+        select(C);
+        assert_eq!(candidates(), [C, A, B]);
+        assert_eq!(base_url(), C);
+
+        // Among the failing ones, the selected URL still goes first
+        mark_unhealthy(C);
+        mark_unhealthy(A);
+        assert_eq!(candidates(), [B, C, A]);
+
+        // A failed URL that answers again is selected and healthy
+        select(A);
+        assert_eq!(candidates(), [A, B, C]);
+    }
+}
@@ -0,0 +1,230 @@
+use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::auth_client::AuthClient;
+use crate::circuit;
+use crate::config::AuthFactor;
+use crate::demo::DemoState;
+use crate::error::CommandError;
+use crate::i18n;
+use crate::middleware::command;
+use crate::session::SessionState;
+
+const ENROLLER_PATH: &str = "/admin/v1/MyAuthenticationFactorEnroller";
+const VALIDATOR_PATH: &str = "/admin/v1/MyAuthenticationFactorValidator";
+const ENROLLER_SCHEMA: &str = "urn:ietf:params:scim:schemas:oracle:idcs:AuthenticationFactorEnroller";
+const VALIDATOR_SCHEMA: &str = "urn:ietf:params:scim:schemas:oracle:idcs:AuthenticationFactorValidator";
+// Demo enrollments accept this passcode; nothing is stored
+const DEMO_SECRET: &str = "JBSWY3DPEHPK3PXP";
+const DEMO_PASSCODE: &str = "000000";
+
+// What the user needs to finish enrolling; pass `device_id` and `request_id` back with the passcode
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrollmentChallenge {
+    pub factor: AuthFactor,
+    pub device_id: String,
+    pub request_id: String,
+    // TOTP: `otpauth://` URI for authenticator apps, when the secret is known
+    pub provisioning_uri: Option<String>,
+    // TOTP: the secret, for typing into an authenticator app by hand
+    pub secret: Option<String>,
+    // TOTP: QR code image from IDCS, base64 encoded
+    pub qr_code: Option<String>,
+    pub qr_code_type: Option<String>,
+    // SMS and email: where the passcode was sent
+    pub sent_to: Option<String>,
+}
+
+// Starts enrolling the signed-in user in TOTP, SMS or email. TOTP returns the QR code or secret to add to an
+// authenticator app; SMS and email send a passcode. Confirm with `confirm_factor_enrollment`.
+#[command(session)]
+pub async fn start_factor_enrollment(
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    demo: State<'_, DemoState>,
+    factor: AuthFactor,
+    country_code: Option<String>,
+    phone_number: Option<String>,
+) -> Result<EnrollmentChallenge, CommandError> {
+    let phone = match factor {
+        AuthFactor::Totp | AuthFactor::Email => None,
+        AuthFactor::Sms => Some(validate_phone(country_code.as_deref(), phone_number.as_deref())?),
+        _ => return Err(format!("{} can't be enrolled from the app", factor.idcs_name()).into()),
+    };
+    let profile = session.profile()?;
+
+    let challenge = if demo.enabled {
+        demo_challenge(&profile, factor, phone)
+    } else {
+        idcs_enroll(&auth_client, &session.bearer_token()?, &profile, factor, phone).await?
+    };
+    log::info!("Started {} enrollment", factor.idcs_name());
+    Ok(challenge)
+}
+
+// Checks the first passcode from the new factor, which completes the enrollment
+#[command(session)]
+pub async fn confirm_factor_enrollment(
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    demo: State<'_, DemoState>,
+    factor: AuthFactor,
+    device_id: String,
+    request_id: String,
+    otp_code: String,
+) -> Result<(), CommandError> {
+    if demo.enabled {
+        session.profile()?;
+        if otp_code.trim() != DEMO_PASSCODE {
+            return Err("The passcode you entered is incorrect.".into());
+        }
+    } else {
+        let bearer_token = session.bearer_token()?;
+        idcs_validate(&auth_client, &bearer_token, factor, &device_id, &request_id, &otp_code).await?;
+    }
+    log::info!("Enrolled {}", factor.idcs_name());
+    Ok(())
+}
+
+// Country code such as "+1" and the rest of the number, digits only
+fn validate_phone(country_code: Option<&str>, phone_number: Option<&str>) -> Result<(String, String), String> {
+    let country_code = country_code.map(str::trim).unwrap_or_default();
+    let phone_number: String = phone_number
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    let digits = |value: &str| !value.is_empty() && value.chars().all(|c| c.is_ascii_digit());
+    if !country_code.strip_prefix('+').is_some_and(digits) {
+        return Err("Invalid country code: use the form +1".to_string());
+    }
+    if !digits(&phone_number) {
+        return Err("Invalid phone number: digits only, without the country code".to_string());
+    }
+    Ok((country_code.to_string(), phone_number))
+}
+
+async fn idcs_enroll(
+    client: &AuthClient,
+    bearer_token: &str,
+    profile: &Value,
+    factor: AuthFactor,
+    phone: Option<(String, String)>,
+) -> Result<EnrollmentChallenge, String> {
+    let mut body = json!({
+        "schemas": [ENROLLER_SCHEMA],
+        "authnFactors": [factor.idcs_name()],
+        "displayName": crate::branding::APP_NAME
+    });
+    if let Some((country_code, phone_number)) = &phone {
+        body["countryCode"] = json!(country_code);
+        body["phoneNumber"] = json!(phone_number);
+    }
+    let response = post_my(client, bearer_token, ENROLLER_PATH, &body).await?;
+
+    let text = |key: &str| response[key].as_str().map(str::to_string);
+    let secret = text("sharedSecret");
+    Ok(EnrollmentChallenge {
+        factor,
+        device_id: text("deviceId").ok_or("IDCS did not return a device id")?,
+        request_id: text("requestId").ok_or("IDCS did not return a request id")?,
+        provisioning_uri: secret.as_deref().map(|secret| provisioning_uri(profile, secret)),
+        secret,
+        qr_code: text("qrCodeImgContent"),
+        qr_code_type: text("qrCodeImgType"),
+        sent_to: sent_to(profile, factor, phone),
+    })
+}
+
+async fn idcs_validate(
+    client: &AuthClient,
+    bearer_token: &str,
+    factor: AuthFactor,
+    device_id: &str,
+    request_id: &str,
+    otp_code: &str,
+) -> Result<(), String> {
+    let response = post_my(
+        client,
+        bearer_token,
+        VALIDATOR_PATH,
+        &json!({
+            "schemas": [VALIDATOR_SCHEMA],
+            "authFactor": factor.idcs_name(),
+            "scenario": "ENROLLMENT",
+            "deviceId": device_id,
+            "requestId": request_id,
+            "otpCode": otp_code.trim()
+        }),
+    )
+    .await?;
+    if response["status"].as_str().is_some_and(|status| status.eq_ignore_ascii_case("failed")) {
+        return Err("The passcode you entered is incorrect.".to_string());
+    }
+    Ok(())
+}
+
+// Posts to one of the user's own `/admin/v1/My*` endpoints; SCIM errors carry the reason in `detail`
+async fn post_my(client: &AuthClient, bearer_token: &str, path: &str, body: &Value) -> Result<Value, String> {
+    let response = circuit::send(
+        client
+            .http()
+            .post(client.url(path))
+            .header(ACCEPT_LANGUAGE, i18n::accept_language())
+            .header(AUTHORIZATION, HeaderValue::from_str(bearer_token).map_err(|e| e.to_string())?)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/scim+json"))
+            .json(body),
+    )
+    .await?;
+
+    let status = response.status();
+    let response_text = response.text().await.map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&response_text).unwrap_or_default();
+    if !status.is_success() {
+        let detail = value["detail"].as_str().map(str::to_string).unwrap_or(response_text);
+        return Err(format!("Enrollment failed: {} {}", status, detail));
+    }
+    Ok(value)
+}
+
+fn demo_challenge(profile: &Value, factor: AuthFactor, phone: Option<(String, String)>) -> EnrollmentChallenge {
+    let totp = factor == AuthFactor::Totp;
+    EnrollmentChallenge {
+        factor,
+        device_id: format!("demo-device-{}", factor.idcs_name().to_lowercase()),
+        request_id: format!("demo-enroll-{}", chrono::Local::now().timestamp_millis()),
+        provisioning_uri: totp.then(|| provisioning_uri(profile, DEMO_SECRET)),
+        secret: totp.then(|| DEMO_SECRET.to_string()),
+        qr_code: None,
+        qr_code_type: None,
+        sent_to: sent_to(profile, factor, phone),
+    }
+}
+
+// Key URI format understood by authenticator apps
+fn provisioning_uri(profile: &Value, secret: &str) -> String {
+    let issuer = crate::branding::APP_NAME;
+    let account = profile["userName"].as_str().unwrap_or_default();
+    let mut uri = reqwest::Url::parse("otpauth://totp/").expect("static URI");
+    uri.set_path(&format!("{}:{}", issuer, account));
+    uri.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("digits", "6")
+        .append_pair("period", "30");
+    uri.to_string()
+}
+
+fn sent_to(profile: &Value, factor: AuthFactor, phone: Option<(String, String)>) -> Option<String> {
+    match factor {
+        AuthFactor::Sms => phone.map(|(country_code, phone_number)| format!("{} {}", country_code, phone_number)),
+        AuthFactor::Email => profile["emails"]
+            .as_array()
+            .and_then(|emails| emails.iter().find(|email| email["primary"] == true).or(emails.first()))
+            .and_then(|email| email["value"].as_str())
+            .map(str::to_string),
+        _ => None,
+    }
+}
@@ -0,0 +1,335 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::auth::CauseMessage;
+use crate::circuit::ServiceUnavailable;
+
+// IDCS cause codes for a wrong password, passcode or security answer
+const INVALID_CREDENTIAL_CODES: &[&str] = &["AUTH-3001", "AUTH-3021"];
+
+// Lets the frontend pick the right error UX without parsing messages
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    NotSignedIn,
+    // IDCS is down or the circuit breaker is open
+    Unavailable,
+    Network,
+    // IDCS rejected the credentials or token
+    Auth,
+    Policy,
+    Config,
+    NotIncluded,
+    // No client credentials yet; the frontend shows the onboarding wizard
+    NotConfigured,
+    // A command was called more often than its rate limit allows; `retry_after_secs` says when it works again
+    TooManyRequests,
+    Internal,
+}
+
+// Buttons the frontend offers next to the error
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    Retry,
+    Reauth,
+    OpenSettings,
+    ContactAdmin,
+    OpenSetup,
+}
+
+// What every command returns on failure
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandError {
+    pub kind: ErrorKind,
+    pub message: String,
+    // Also written to the log, so a screenshot of the error can be matched to it
+    pub correlation_id: String,
+    pub retryable: bool,
+    pub actions: Vec<RecoveryAction>,
+    // Set by the sign-in commands, which can tell e.g. a locked account from a wrong password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Box<AuthError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+// Why a step of the sign-in flow failed, with the IDCS cause code when IDCS sent one
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthError {
+    // IDCS couldn't be reached
+    #[error("{message}")]
+    Network { message: String },
+    // IDCS is down or the circuit breaker is open
+    #[error("{message}")]
+    Unavailable { message: String },
+    // Wrong user name, password, passcode or security answer
+    #[error("{message}")]
+    InvalidCredentials { code: Option<String>, message: String },
+    #[error("{message}")]
+    AccountLocked { code: Option<String>, message: String },
+    // The sign-in can't finish before a second factor is verified
+    #[error("{message}")]
+    MfaRequired { code: Option<String>, message: String },
+    // Any other refusal from IDCS or its token endpoint
+    #[error("{message}")]
+    Rejected { code: Option<String>, message: String },
+    // No client credentials for the active profile
+    #[error("{message}")]
+    NotConfigured { message: String },
+    #[error("{message}")]
+    Internal { message: String },
+}
+
+impl CommandError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let correlation_id = correlation_id();
+        log::warn!("Command failed [{}] {:?}: {}", correlation_id, kind, message);
+        let actions = kind.actions();
+        Self {
+            kind,
+            message,
+            correlation_id,
+            retryable: actions.contains(&RecoveryAction::Retry),
+            actions,
+            auth: None,
+            retry_after_secs: None,
+        }
+    }
+
+    pub fn too_many_requests(command: &str, retry_after: Duration) -> Self {
+        // Rounded up, so retrying after this many seconds always works
+        let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        let mut error = Self::new(
+            ErrorKind::TooManyRequests,
+            format!("{} was called too often; try again in {} seconds", command, retry_after_secs),
+        );
+        error.retry_after_secs = Some(retry_after_secs);
+        error
+    }
+}
+
+impl AuthError {
+    // From the first entry of an IDCS `cause` array
+    pub fn from_cause(cause: &CauseMessage) -> Self {
+        let code = Some(cause.code.clone());
+        let message = cause.message.clone();
+        if INVALID_CREDENTIAL_CODES.contains(&cause.code.as_str()) {
+            return Self::InvalidCredentials { code, message };
+        }
+        match Self::from(message) {
+            Self::InvalidCredentials { message, .. } => Self::InvalidCredentials { code, message },
+            Self::AccountLocked { message, .. } => Self::AccountLocked { code, message },
+            Self::MfaRequired { message, .. } => Self::MfaRequired { code, message },
+            other => Self::Rejected { code, message: other.to_string() },
+        }
+    }
+
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::InvalidCredentials { code, .. }
+            | Self::AccountLocked { code, .. }
+            | Self::MfaRequired { code, .. }
+            | Self::Rejected { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+
+    // Prefixes the message, e.g. with the step that failed, keeping the kind and code
+    pub fn context(mut self, prefix: &str) -> Self {
+        match &mut self {
+            Self::Network { message }
+            | Self::Unavailable { message }
+            | Self::InvalidCredentials { message, .. }
+            | Self::AccountLocked { message, .. }
+            | Self::MfaRequired { message, .. }
+            | Self::Rejected { message, .. }
+            | Self::NotConfigured { message }
+            | Self::Internal { message } => *message = format!("{}: {}", prefix, message),
+        }
+        self
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Network { .. } => ErrorKind::Network,
+            Self::Unavailable { .. } => ErrorKind::Unavailable,
+            Self::InvalidCredentials { .. } | Self::MfaRequired { .. } | Self::Rejected { .. } => ErrorKind::Auth,
+            // Only an administrator can unlock it
+            Self::AccountLocked { .. } => ErrorKind::Policy,
+            Self::NotConfigured { .. } => ErrorKind::NotConfigured,
+            Self::Internal { .. } => ErrorKind::Internal,
+        }
+    }
+}
+
+impl ErrorKind {
+    fn actions(self) -> Vec<RecoveryAction> {
+        use RecoveryAction::*;
+        match self {
+            ErrorKind::NotSignedIn | ErrorKind::Auth => vec![Reauth],
+            ErrorKind::Unavailable => vec![Retry, ContactAdmin],
+            ErrorKind::Network => vec![Retry, OpenSettings],
+            ErrorKind::Policy | ErrorKind::NotIncluded => vec![ContactAdmin],
+            ErrorKind::Config => vec![OpenSettings],
+            ErrorKind::NotConfigured => vec![OpenSetup],
+            ErrorKind::TooManyRequests => vec![Retry],
+            ErrorKind::Internal => vec![Retry, ContactAdmin],
+        }
+    }
+
+    // Errors below the command layer are plain strings; these are the messages they are built from. They often
+    // carry the IDCS URL, whose host can contain any digits, so statuses only match as reqwest formats them.
+    fn classify(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| lower.contains(needle));
+        if has(&["not signed in", "sign in again", "session expired"]) {
+            ErrorKind::NotSignedIn
+        } else if has(&["error sending request", "dns error", "connection", "timed out", "tcp connect"]) {
+            ErrorKind::Network
+        } else if has(&["service unavailable", "too many requests"]) {
+            ErrorKind::Unavailable
+        } else if has(&["unauthorized", "invalid_grant", "invalid_client", "authentication failed"]) {
+            ErrorKind::Auth
+        } else if has(&["policy", "not allowed", "outside the allowed"]) {
+            ErrorKind::Policy
+        } else if has(&["not included in this build"]) {
+            ErrorKind::NotIncluded
+        } else if has(&["not configured"]) {
+            ErrorKind::NotConfigured
+        } else if has(&["is not set", "invalid ", "must ", "unknown profile"]) {
+            ErrorKind::Config
+        } else {
+            ErrorKind::Internal
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorKind::classify(&message), message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+impl From<AuthError> for CommandError {
+    fn from(error: AuthError) -> Self {
+        let mut command_error = Self::new(error.kind(), error.to_string());
+        if let Some(code) = error.code() {
+            log::warn!("IDCS cause [{}]: {}", command_error.correlation_id, code);
+        }
+        command_error.auth = Some(Box::new(error));
+        command_error
+    }
+}
+
+// Messages from the demo provider and helpers that don't know the IDCS code
+impl From<String> for AuthError {
+    fn from(message: String) -> Self {
+        let lower = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|needle| lower.contains(needle));
+        if has(&["locked"]) {
+            Self::AccountLocked { code: None, message }
+        } else if has(&["incorrect", "invalid_grant", "wrong password"]) {
+            Self::InvalidCredentials { code: None, message }
+        } else if has(&["is required for this account", "are required for this account"]) {
+            Self::MfaRequired { code: None, message }
+        } else {
+            match ErrorKind::classify(&message) {
+                ErrorKind::Network => Self::Network { message },
+                ErrorKind::Unavailable => Self::Unavailable { message },
+                ErrorKind::NotConfigured => Self::NotConfigured { message },
+                ErrorKind::Auth | ErrorKind::NotSignedIn | ErrorKind::Policy => Self::Rejected { code: None, message },
+                _ => Self::Internal { message },
+            }
+        }
+    }
+}
+
+impl From<&str> for AuthError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+impl From<ServiceUnavailable> for AuthError {
+    fn from(error: ServiceUnavailable) -> Self {
+        Self::Unavailable { message: error.to_string() }
+    }
+}
+
+// For callers outside the sign-in flow, which still report plain strings
+impl From<AuthError> for String {
+    fn from(error: AuthError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<ServiceUnavailable> for CommandError {
+    fn from(error: ServiceUnavailable) -> Self {
+        Self::new(ErrorKind::Unavailable, error.to_string())
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for CommandError {
+    fn from(error: Box<dyn std::error::Error>) -> Self {
+        Self::from(error.to_string())
+    }
+}
+
+fn correlation_id() -> String {
+    let mut bytes = [0u8; 6];
+    if getrandom::getrandom(&mut bytes).is_err() {
+        // Still unique enough to find in the log
+        return format!("{:012x}", chrono::Utc::now().timestamp_micros() & 0xffff_ffff_ffff);
+    }
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_reads_the_kind_from_the_message() {
+        let cases = [
+            ("Not signed in to IDCS", ErrorKind::NotSignedIn),
+            ("error sending request for url (https://idcs-1.identity.oraclecloud.com)", ErrorKind::Network),
+            ("Failed to get /admin/v1/Me: 503 Service Unavailable", ErrorKind::Unavailable),
+            ("Failed to get /admin/v1/Me: 401 Unauthorized {}", ErrorKind::Auth),
+            ("Sign-in is not allowed outside the allowed hours", ErrorKind::Policy),
+            ("Impersonation is not included in this build", ErrorKind::NotIncluded),
+            ("Client credentials are not configured", ErrorKind::NotConfigured),
+            ("IDCS_URL is not set", ErrorKind::Config),
+            ("Failed to parse the backup", ErrorKind::Internal),
+        ];
+        for (message, kind) in cases {
+            assert_eq!(ErrorKind::classify(message), kind, "{}", message);
+        }
+    }
+
+    // The tenant host can contain any digits, so a status only counts with reqwest's reason phrase
+    #[test]
+    fn classify_ignores_status_digits_in_the_url() {
+        let message = "Failed to parse https://idcs-401503.identity.oraclecloud.com/admin/v1/Me";
+        assert_eq!(ErrorKind::classify(message), ErrorKind::Internal);
+    }
+
+    #[test]
+    fn not_signed_in_wins_over_auth() {
+        assert_eq!(ErrorKind::classify("Unauthorized: sign in again"), ErrorKind::NotSignedIn);
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::Local;
+#[cfg(feature = "export-formats")]
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfLayerReference};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "export-formats")]
+use std::fs::File;
+#[cfg(feature = "export-formats")]
+use std::io::BufWriter;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use crate::analytics;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::session::SessionState;
+
+#[cfg(feature = "export-formats")]
+const PAGE_WIDTH: f32 = 210.0;
+#[cfg(feature = "export-formats")]
+const PAGE_HEIGHT: f32 = 297.0;
+#[cfg(feature = "export-formats")]
+const MARGIN: f32 = 20.0;
+#[cfg(feature = "export-formats")]
+const LINE_HEIGHT: f32 = 7.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Pdf,
+}
+
+// Flattened view of the profile for access-review paperwork
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub user_name: String,
+    pub display_name: String,
+    pub email: Option<String>,
+    pub active: bool,
+    pub groups: Vec<String>,
+    pub generated_at: String,
+}
+
+#[command(unlocked, session)]
+pub fn export_profile(
+    app_handle: AppHandle,
+    session: State<SessionState>,
+    path: String,
+    format: ExportFormat,
+) -> Result<(), CommandError> {
+    let summary = ProfileSummary::from_profile(&session.profile()?);
+    let path = Path::new(&path);
+
+    match format {
+        ExportFormat::Json => write_json(&summary, path)?,
+        #[cfg(feature = "export-formats")]
+        ExportFormat::Csv => write_csv(&summary, path)?,
+        #[cfg(feature = "export-formats")]
+        ExportFormat::Pdf => write_pdf(&summary, path)?,
+        #[cfg(not(feature = "export-formats"))]
+        ExportFormat::Csv | ExportFormat::Pdf => {
+            return Err(CommandError::new(
+                crate::error::ErrorKind::NotIncluded,
+                format!("{:?} export is not included in this build", format),
+            ));
+        }
+    }
+    log::info!("Profile summary exported as {:?} to {}", format, path.display());
+    analytics::track(
+        &app_handle,
+        match format {
+            ExportFormat::Json => "export_json",
+            ExportFormat::Csv => "export_csv",
+            ExportFormat::Pdf => "export_pdf",
+        },
+    );
+    Ok(())
+}
+
+impl ProfileSummary {
+    pub fn from_profile(profile: &Value) -> Self {
+        let email = profile["emails"]
+            .as_array()
+            .and_then(|emails| {
+                emails
+                    .iter()
+                    .find(|email| email["primary"].as_bool().unwrap_or(false))
+                    .or_else(|| emails.first())
+            })
+            .and_then(|email| email["value"].as_str())
+            .map(|email| email.to_string());
+        let mut groups: Vec<String> = profile["groups"]
+            .as_array()
+            .map(|groups| {
+                groups
+                    .iter()
+                    .filter_map(|group| group["display"].as_str().or_else(|| group["value"].as_str()))
+                    .map(|group| group.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        groups.sort();
+
+        Self {
+            id: profile["id"].as_str().unwrap_or_default().to_string(),
+            user_name: profile["userName"].as_str().unwrap_or_default().to_string(),
+            display_name: profile["displayName"].as_str().unwrap_or_default().to_string(),
+            email,
+            active: profile["active"].as_bool().unwrap_or(true),
+            groups,
+            generated_at: Local::now().to_rfc3339(),
+        }
+    }
+}
+
+fn write_json(summary: &ProfileSummary, path: &Path) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(summary).map_err(|e| e.to_string())?;
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "export-formats")]
+// One row per group membership, which is what spreadsheet-based reviews expect
+fn write_csv(summary: &ProfileSummary, path: &Path) -> Result<(), String> {
+    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    writer
+        .write_record(["user_name", "display_name", "email", "active", "group", "generated_at"])
+        .map_err(|e| e.to_string())?;
+
+    let groups = if summary.groups.is_empty() {
+        vec![String::new()]
+    } else {
+        summary.groups.clone()
+    };
+    for group in groups {
+        writer
+            .write_record([
+                summary.user_name.as_str(),
+                summary.display_name.as_str(),
+                summary.email.as_deref().unwrap_or_default(),
+                if summary.active { "true" } else { "false" },
+                group.as_str(),
+                summary.generated_at.as_str(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "export-formats")]
+fn write_pdf(summary: &ProfileSummary, path: &Path) -> Result<(), String> {
+    let (doc, page, layer) = PdfDocument::new("Profile summary", Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| e.to_string())?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| e.to_string())?;
+
+    let mut layer = doc.get_page(page).get_layer(layer);
+    let mut y = PAGE_HEIGHT - MARGIN;
+    let line = |layer: &PdfLayerReference, y: &mut f32, text: &str, size: f32, font: &IndirectFontRef| {
+        layer.use_text(text, size, Mm(MARGIN), Mm(*y), font);
+        *y -= LINE_HEIGHT;
+    };
+
+    line(&layer, &mut y, "Profile summary", 18.0, &bold);
+    y -= LINE_HEIGHT / 2.0;
+    line(&layer, &mut y, &format!("Name: {}", summary.display_name), 11.0, &font);
+    line(&layer, &mut y, &format!("User name: {}", summary.user_name), 11.0, &font);
+    line(&layer, &mut y, &format!("Email: {}", summary.email.as_deref().unwrap_or("-")), 11.0, &font);
+    line(&layer, &mut y, &format!("Status: {}", if summary.active { "Active" } else { "Inactive" }), 11.0, &font);
+    line(&layer, &mut y, &format!("Generated: {}", summary.generated_at), 11.0, &font);
+    y -= LINE_HEIGHT;
+
+    line(&layer, &mut y, &format!("Group memberships ({})", summary.groups.len()), 14.0, &bold);
+    for group in &summary.groups {
+        if y < MARGIN {
+            let (page, page_layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            layer = doc.get_page(page).get_layer(page_layer);
+            y = PAGE_HEIGHT - MARGIN;
+        }
+        line(&layer, &mut y, &format!("- {}", group), 11.0, &font);
+    }
+
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    doc.save(&mut BufWriter::new(file)).map_err(|e| e.to_string())
+}
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreBuilder;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::policy::PolicyState;
+
+const FEATURES_STORE_FILE: &str = "features.json";
+const FEATURES_CACHE_FILE: &str = "features-cache.json";
+const DEFAULT_REFRESH_MINUTES: u64 = 30;
+
+// Known flags and their defaults; risky capabilities start off and are rolled out by flag
+const DEFAULT_FLAGS: &[(&str, bool)] = &[("browser_flow", false), ("broker", false)];
+
+// A flag is either on/off for everyone or on for a percentage of installs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FlagRule {
+    Enabled(bool),
+    Rollout { rollout: u8 },
+}
+
+impl FlagRule {
+    fn applies_to(self, bucket: u8) -> bool {
+        match self {
+            FlagRule::Enabled(enabled) => enabled,
+            FlagRule::Rollout { rollout } => bucket < rollout,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub enabled: bool,
+    // Set by policy or the remote flag source; the user can't override it
+    pub managed: bool,
+}
+
+struct Flags {
+    // Overrides from the local store
+    local: BTreeMap<String, bool>,
+    // Policy flags with the remote source layered on top
+    managed: BTreeMap<String, FlagRule>,
+    // Stable 0-99 value per install, so a rollout keeps the same installs enabled
+    bucket: u8,
+}
+
+static FLAGS: RwLock<Flags> = RwLock::new(Flags {
+    local: BTreeMap::new(),
+    managed: BTreeMap::new(),
+    bucket: 0,
+});
+
+// Backend check, e.g. `if features::enabled("broker") { ... }`
+pub fn enabled(name: &str) -> bool {
+    match FLAGS.read() {
+        Ok(flags) => is_enabled_in(&flags, name),
+        Err(_) => default_value(name),
+    }
+}
+
+#[command]
+pub fn is_feature_enabled(name: String) -> Result<bool, CommandError> {
+    Ok(enabled(&name))
+}
+
+#[command]
+pub fn list_feature_flags() -> Result<Vec<FeatureFlag>, CommandError> {
+    let flags = FLAGS.read().map_err(|e| e.to_string())?;
+    let mut names: Vec<String> = DEFAULT_FLAGS.iter().map(|(name, _)| name.to_string()).collect();
+    names.extend(flags.local.keys().cloned());
+    names.extend(flags.managed.keys().cloned());
+    names.sort();
+    names.dedup();
+
+    Ok(names
+        .into_iter()
+        .map(|name| FeatureFlag {
+            enabled: is_enabled_in(&flags, &name),
+            managed: flags.managed.contains_key(&name),
+            name,
+        })
+        .collect())
+}
+
+// Local override, e.g. to try a capability before it is rolled out
+#[command(unlocked)]
+pub fn set_feature_flag(app_handle: AppHandle, name: String, enabled: bool) -> Result<(), CommandError> {
+    let mut flags = FLAGS.write().map_err(|e| e.to_string())?;
+    if flags.managed.contains_key(&name) {
+        return Err(format!("The {} feature is managed by your administrator", name).into());
+    }
+    flags.local.insert(name.clone(), enabled);
+
+    let store = StoreBuilder::new(&app_handle, features_store_path(&app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    store.set("flags", json!(flags.local));
+    store.save().map_err(|e| e.to_string())?;
+    drop(flags);
+
+    log::info!("Feature {} {} locally", name, if enabled { "enabled" } else { "disabled" });
+    crate::bus::emit(&app_handle, "feature-flags-changed", list_feature_flags()?);
+    Ok(())
+}
+
+// Loads the local store and policy flags, then keeps the remote flag source current
+pub async fn refresh_feature_flags(app_handle: AppHandle) {
+    if let Err(e) = load_local(&app_handle) {
+        log::warn!("Failed to load local feature flags: {}", e);
+    }
+
+    let policy = app_handle.state::<PolicyState>().0.features.clone().unwrap_or_default();
+    let mut remote = policy
+        .url
+        .as_ref()
+        .and_then(|_| read_cache(&app_handle))
+        .unwrap_or_default();
+    set_managed(&policy.flags, &remote);
+
+    let Some(url) = policy.url else {
+        return;
+    };
+    let interval = Duration::from_secs(policy.refresh_minutes.unwrap_or(DEFAULT_REFRESH_MINUTES).max(1) * 60);
+    loop {
+        match fetch_remote(&url).await {
+            Ok(fetched) => {
+                if fetched != remote {
+                    remote = fetched;
+                    write_cache(&app_handle, &remote);
+                    set_managed(&policy.flags, &remote);
+                    log::info!("Feature flags updated from {}", url);
+                    if let Ok(flags) = list_feature_flags() {
+                        crate::bus::emit(&app_handle, "feature-flags-changed", flags);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Failed to fetch feature flags: {}", e),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn is_enabled_in(flags: &Flags, name: &str) -> bool {
+    if let Some(rule) = flags.managed.get(name) {
+        return rule.applies_to(flags.bucket);
+    }
+    flags.local.get(name).copied().unwrap_or_else(|| default_value(name))
+}
+
+fn default_value(name: &str) -> bool {
+    DEFAULT_FLAGS
+        .iter()
+        .find(|(flag, _)| *flag == name)
+        .map(|(_, enabled)| *enabled)
+        .unwrap_or(false)
+}
+
+fn set_managed(policy_flags: &BTreeMap<String, FlagRule>, remote: &BTreeMap<String, FlagRule>) {
+    if let Ok(mut flags) = FLAGS.write() {
+        flags.managed = policy_flags.clone();
+        flags.managed.extend(remote.clone());
+    }
+}
+
+fn load_local(app_handle: &AppHandle) -> Result<(), String> {
+    let store = StoreBuilder::new(app_handle, features_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let local: BTreeMap<String, bool> = store
+        .get("flags")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+    let bucket = match store.get("bucket").and_then(|value| value.as_u64()) {
+        Some(bucket) => bucket.min(99) as u8,
+        None => {
+            let bucket = new_bucket();
+            store.set("bucket", json!(bucket));
+            store.save().map_err(|e| e.to_string())?;
+            bucket
+        }
+    };
+
+    let mut flags = FLAGS.write().map_err(|e| e.to_string())?;
+    flags.local = local;
+    flags.bucket = bucket;
+    Ok(())
+}
+
+fn new_bucket() -> u8 {
+    let seed = format!(
+        "{}:{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id()
+    );
+    let digest = Sha256::digest(seed.as_bytes());
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+}
+
+// Expects a JSON object of flag names to `true`/`false` or `{"rollout": <percent>}`
+async fn fetch_remote(url: &str) -> Result<BTreeMap<String, FlagRule>, String> {
+    let response = reqwest::get(url).await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to get {}: {}", url, response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+fn read_cache(app_handle: &AppHandle) -> Option<BTreeMap<String, FlagRule>> {
+    features_cache_path(app_handle)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
+fn write_cache(app_handle: &AppHandle, remote: &BTreeMap<String, FlagRule>) {
+    if let Ok(path) = features_cache_path(app_handle) {
+        if let Ok(contents) = serde_json::to_string(remote) {
+            let _ = std::fs::write(path, contents);
+        }
+    }
+}
+
+fn features_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FEATURES_STORE_FILE))
+}
+
+fn features_cache_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::cache_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FEATURES_CACHE_FILE))
+}
@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, CloseRequestApi, Manager};
+
+use crate::auth_client::AuthClient;
+
+// A sign-in that leaves nothing behind, chosen per attempt with `initiate_auth`'s `guest` flag. Its tokens only
+// live in memory, no login history, audit entries or cached profile are written, and closing the window signs it
+// out.
+#[derive(Default)]
+pub struct GuestState(AtomicBool);
+
+pub fn is_active(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<GuestState>()
+        .is_some_and(|guest| guest.0.load(Ordering::SeqCst))
+}
+
+pub(crate) fn set(app_handle: &AppHandle, guest: bool) {
+    app_handle.state::<GuestState>().0.store(guest, Ordering::SeqCst);
+}
+
+// Holds the window open until the guest is signed out, so its tokens are revoked before the app goes away
+pub fn on_close_requested(app_handle: &AppHandle, api: &CloseRequestApi) {
+    if !is_active(app_handle) {
+        return;
+    }
+    api.prevent_close();
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let client = app_handle.state::<AuthClient>();
+        if let Err(e) = crate::auth::end_session(&app_handle, &client).await {
+            log::warn!("Failed to sign the guest out: {}", e);
+        }
+        // Also after a failed sign-out, so the close goes through this time
+        set(&app_handle, false);
+        log::info!("Guest session wiped");
+        if let Ok(window) = crate::window::main_window(&app_handle) {
+            if let Err(e) = window.close() {
+                log::warn!("Failed to close the window: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,162 @@
+use std::io::{Lines, StdinLock, Write};
+use tauri::{AppHandle, Manager};
+
+use crate::auth::{idcs_complete_session, idcs_initiate_auth, idcs_submit_otp, AuthResponse};
+use crate::auth_client::AuthClient;
+use crate::cache::CacheState;
+use crate::cli::AuthenticateOutput;
+use crate::config::{AppConfig, AuthFactor, ConfigState};
+use crate::oci_cli::{self, OciSessionProfile};
+use crate::policy::{self, Policy};
+use crate::profiles;
+use crate::session::Session;
+
+// Factors that come down to a code typed in; push, SMS, security keys and the rest need the window
+const PASSCODE_FACTORS: &[AuthFactor] = &[AuthFactor::Totp, AuthFactor::BypassCode];
+
+// What `--authenticate` prints on success, picked with `--print`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadlessOutput {
+    AccessToken,
+    Profile,
+}
+
+impl HeadlessOutput {
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            None | Some("token") => Ok(Self::AccessToken),
+            Some("profile") => Ok(Self::Profile),
+            Some(other) => Err(format!("Unknown --print value {}; use token or profile", other)),
+        }
+    }
+}
+
+// Signs in with the active profile without a window, for CI jobs and scripts, and returns what to print. Prompts
+// go to stderr so stdout carries only the result. With `password_stdin` the password is the first line of stdin and
+// a passcode, when IDCS asks for one, the next. Nothing is saved: the app's own session is left as it is.
+pub fn authenticate(
+    app_handle: &AppHandle,
+    username: Option<String>,
+    password_stdin: bool,
+    output: HeadlessOutput,
+) -> Result<AuthenticateOutput, String> {
+    let session = sign_in(app_handle, username, password_stdin)?;
+    Ok(match output {
+        HeadlessOutput::AccessToken => AuthenticateOutput {
+            access_token: Some(session.access_token.ok_or("IDCS returned no access token")?),
+            expires_at: session.expires_at,
+            profile: None,
+        },
+        HeadlessOutput::Profile => AuthenticateOutput {
+            access_token: None,
+            expires_at: None,
+            profile: Some(session.profile),
+        },
+    })
+}
+
+// Signs in the same way and writes an OCI CLI session profile for it, as `oci_cli::export_token` does
+pub fn export_token(
+    app_handle: &AppHandle,
+    username: Option<String>,
+    password_stdin: bool,
+    path: String,
+    profile: Option<String>,
+    region: Option<String>,
+) -> Result<OciSessionProfile, String> {
+    let session = sign_in(app_handle, username, password_stdin)?;
+    let access_token = session.access_token.ok_or("IDCS returned no access token")?;
+    tauri::async_runtime::block_on(oci_cli::export(app_handle, &access_token, Some(path), profile, region))
+}
+
+fn sign_in(app_handle: &AppHandle, username: Option<String>, password_stdin: bool) -> Result<Session, String> {
+    prepare(app_handle)?;
+    let mut input = std::io::stdin().lines();
+    let username = match username {
+        Some(username) => username,
+        None if password_stdin => return Err("--password-stdin needs --username".to_string()),
+        None => prompt(&mut input, "Username: ")?,
+    };
+    let password = if password_stdin {
+        read_line(&mut input)?
+    } else {
+        rpassword::prompt_password("Password: ").map_err(|e| format!("Failed to read the password: {}", e))?
+    };
+    if username.trim().is_empty() || password.is_empty() {
+        return Err("A user name and password are required".to_string());
+    }
+
+    let client = app_handle.state::<AuthClient>();
+    tauri::async_runtime::block_on(async {
+        let response = idcs_initiate_auth(&client, username.trim().to_string(), password)
+            .await
+            .map_err(|e| e.to_string())?;
+        let response = match passcode_factor(&response)? {
+            Some(factor) => {
+                let label = if factor == AuthFactor::Totp { "Passcode: " } else { "Bypass code: " };
+                let passcode = if password_stdin { read_line(&mut input)? } else { prompt(&mut input, label)? };
+                idcs_submit_otp(&client, factor, &response.request_state, passcode.trim())
+                    .await
+                    .map_err(|e| e.to_string())?
+            }
+            None => response,
+        };
+        idcs_complete_session(app_handle, &client, response.request_state)
+            .await
+            .map_err(|e| e.to_string())
+    })
+}
+
+// The part of the app's setup the sign-in flow relies on; the CLI runs before the rest of it
+fn prepare(app_handle: &AppHandle) -> Result<(), String> {
+    let config = AppConfig::load(app_handle).map_err(|e| e.to_string())?;
+    app_handle.manage(ConfigState::new(app_handle, config.clone()));
+    app_handle.manage(CacheState::default());
+    // Before the HTTP client, which sends the device key's thumbprint
+    if let Err(e) = crate::device::init(app_handle) {
+        eprintln!("Warning: the sign-in won't be bound to this device: {}", e);
+    }
+    app_handle.manage(AuthClient::from_config(&config.network));
+    profiles::activate(app_handle)?;
+    profiles::check_configured()?;
+    if profiles::is_public_client() {
+        return Err("This profile uses a public client, which signs in through the browser".to_string());
+    }
+    policy::check_allowed_hours(&Policy::load())
+}
+
+// The factor to ask a code for, None when IDCS asks for nothing more
+fn passcode_factor(response: &AuthResponse) -> Result<Option<AuthFactor>, String> {
+    if response.next_auth_factors.is_empty() {
+        return Ok(None);
+    }
+    PASSCODE_FACTORS
+        .iter()
+        .copied()
+        .find(|factor| {
+            response
+                .next_auth_factors
+                .iter()
+                .any(|offered| offered.eq_ignore_ascii_case(factor.idcs_name()))
+        })
+        .map(Some)
+        .ok_or_else(|| {
+            format!(
+                "IDCS asks for {}, which only the window can answer; a TOTP passcode or a bypass code works here",
+                response.next_auth_factors.join(", ")
+            )
+        })
+}
+
+fn prompt(input: &mut Lines<StdinLock<'static>>, label: &str) -> Result<String, String> {
+    eprint!("{}", label);
+    std::io::stderr().flush().map_err(|e| e.to_string())?;
+    read_line(input)
+}
+
+fn read_line(input: &mut Lines<StdinLock<'static>>) -> Result<String, String> {
+    match input.next() {
+        Some(line) => line.map_err(|e| format!("Failed to read stdin: {}", e)),
+        None => Err("stdin ended before all the input was read".to_string()),
+    }
+}
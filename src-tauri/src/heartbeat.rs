@@ -0,0 +1,87 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::CommandError;
+use crate::middleware::command;
+
+// How often the frontend calls `heartbeat`; it gets this back from the first call
+const BEAT_INTERVAL: Duration = Duration::from_secs(15);
+// Missed beats after which the webview is taken for dead, with room for a busy page or a slow machine
+const MISSED_BEATS: u32 = 6;
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+// When the webview last checked in, so a renderer that crashed or hung is noticed instead of leaving a window that
+// shows nothing while the process keeps holding the session
+#[derive(Default)]
+pub struct HeartbeatState {
+    last_beat: Mutex<Option<Instant>>,
+    unresponsive: AtomicBool,
+}
+
+// Called by the frontend every few seconds once it has loaded; returns the interval in seconds to keep to
+#[command]
+pub fn heartbeat(app_handle: AppHandle, state: State<HeartbeatState>) -> Result<u64, CommandError> {
+    *state.last_beat.lock().map_err(|e| e.to_string())? = Some(Instant::now());
+    if state.unresponsive.swap(false, Ordering::SeqCst) {
+        log::info!("The window is responding again");
+        crate::tray::set_window_unresponsive(&app_handle, false);
+    }
+    Ok(BEAT_INTERVAL.as_secs())
+}
+
+// Watches for the beats to stop while the window is on screen. Hidden and minimized windows are left alone, since
+// webviews throttle their timers there, and so is a machine that was asleep.
+pub async fn watch_heartbeat(app_handle: AppHandle) {
+    let timeout = BEAT_INTERVAL * MISSED_BEATS;
+    let mut last_check = Instant::now();
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+        let now = Instant::now();
+        let slept = now.duration_since(last_check) > CHECK_INTERVAL * 3;
+        last_check = now;
+
+        let state = app_handle.state::<HeartbeatState>();
+        let Ok(mut last_beat) = state.last_beat.lock() else {
+            continue;
+        };
+        // Nothing to go on until the page has loaded and beaten once
+        let Some(beat) = *last_beat else {
+            continue;
+        };
+        if slept || !is_on_screen(&app_handle) {
+            *last_beat = Some(now);
+            continue;
+        }
+        if now.duration_since(beat) < timeout || state.unresponsive.load(Ordering::SeqCst) {
+            continue;
+        }
+        drop(last_beat);
+
+        state.unresponsive.store(true, Ordering::SeqCst);
+        log::warn!(
+            "The window hasn't responded for {} seconds; its renderer may have crashed",
+            now.duration_since(beat).as_secs()
+        );
+        crate::tray::set_window_unresponsive(&app_handle, true);
+    }
+}
+
+// Replaces the unresponsive window with a new one; offered from the tray once a window stops responding
+pub fn recreate_window(app_handle: &AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<HeartbeatState>();
+    // The new page starts the beats over once it has loaded
+    *state.last_beat.lock().map_err(|e| e.to_string())? = None;
+    crate::window::recreate_main_window(app_handle)?;
+    state.unresponsive.store(false, Ordering::SeqCst);
+    crate::tray::set_window_unresponsive(app_handle, false);
+    Ok(())
+}
+
+fn is_on_screen(app_handle: &AppHandle) -> bool {
+    let Ok(window) = crate::window::main_window(app_handle) else {
+        return false;
+    };
+    window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+}
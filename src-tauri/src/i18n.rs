@@ -0,0 +1,73 @@
+use reqwest::header::HeaderValue;
+use std::sync::RwLock;
+
+use crate::auth::CauseMessage;
+
+// Locale used for IDCS requests, kept in sync with `ui.locale` in the config
+static CURRENT_LOCALE: RwLock<Option<String>> = RwLock::new(None);
+
+// Our own wording for IDCS cause codes, keyed by (language, code). Codes without an entry for
+// the active language are shown with the server's message, which IDCS already localized.
+const CATALOG: &[(&str, &str, &str)] = &[
+    ("en", "AUTH-3001", "The user name or password is incorrect."),
+    ("es", "AUTH-3001", "El nombre de usuario o la contraseña no son correctos."),
+];
+
+pub fn set_locale(locale: Option<&str>) {
+    if let Ok(mut current) = CURRENT_LOCALE.write() {
+        *current = locale.map(|l| l.to_string());
+    }
+}
+
+// Configured locale, or the OS locale when none is configured
+pub fn current_locale() -> String {
+    if let Some(locale) = CURRENT_LOCALE.read().ok().and_then(|l| l.clone()) {
+        return locale;
+    }
+    system_locale().unwrap_or_else(|| "en-US".to_string())
+}
+
+// e.g. "de-DE, de;q=0.9, en;q=0.8", with English as the final fallback
+pub fn accept_language() -> HeaderValue {
+    let locale = current_locale();
+    let language = language_of(&locale);
+
+    let mut value = locale.clone();
+    if language != locale {
+        value.push_str(&format!(", {};q=0.9", language));
+    }
+    if language != "en" {
+        value.push_str(", en;q=0.8");
+    }
+    HeaderValue::from_str(&value).unwrap_or_else(|_| HeaderValue::from_static("en"))
+}
+
+pub fn is_valid_locale(locale: &str) -> bool {
+    !locale.is_empty()
+        && locale.len() <= 35
+        && locale.split('-').all(|part| !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+pub fn localize_causes(causes: &mut [CauseMessage]) {
+    let locale = current_locale();
+    let language = language_of(&locale);
+    for cause in causes.iter_mut() {
+        if let Some((_, _, message)) = CATALOG.iter().find(|(lang, code, _)| *lang == language && *code == cause.code) {
+            cause.message = message.to_string();
+        }
+    }
+}
+
+fn language_of(locale: &str) -> String {
+    locale.split('-').next().unwrap_or(locale).to_lowercase()
+}
+
+// POSIX locales look like "de_DE.UTF-8"; IDCS expects BCP 47 tags like "de-DE"
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        .map(|value| value.split(['.', '@']).next().unwrap_or_default().replace('_', "-"))
+        .filter(|locale| is_valid_locale(locale))
+}
@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::admin::require_capability;
+use crate::audit;
+use crate::auth_client::AuthClient;
+use crate::cache::{CacheState, USER_PREFIX};
+use crate::demo::DemoState;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::policy::PolicyState;
+use crate::session::{Session, SessionState};
+use crate::tenant::get_settings;
+
+const MIN_JUSTIFICATION_LENGTH: usize = 10;
+const WINDOW_TITLE: &str = crate::branding::APP_NAME;
+const USER_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impersonation {
+    pub actor: String,
+    pub target: String,
+    pub target_display_name: String,
+    pub justification: String,
+    pub started_at: DateTime<Utc>,
+}
+
+pub struct ActiveImpersonation {
+    pub details: Impersonation,
+    // The helpdesk user's own session, restored when impersonation ends
+    original: Session,
+}
+
+#[derive(Default)]
+pub struct ImpersonationState(pub Mutex<Option<ActiveImpersonation>>);
+
+// Swaps the displayed profile for the target user's, a read-only view. This is not IDCS delegated
+// authentication: the tokens stay the helpdesk user's and nothing is issued for the target, so
+// nothing can be done on their behalf. The view ends with `end_view_as_user`.
+#[command(session)]
+pub async fn view_as_user(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    demo: State<'_, DemoState>,
+    impersonation: State<'_, ImpersonationState>,
+    cache: State<'_, CacheState>,
+    user_id: String,
+    justification: String,
+) -> Result<Value, CommandError> {
+    if !app_handle.state::<PolicyState>().0.allow_impersonation {
+        return Err("Impersonation is not enabled for this tenant".to_string().into());
+    }
+    if justification.trim().len() < MIN_JUSTIFICATION_LENGTH {
+        return Err(format!(
+            "A justification of at least {} characters is required",
+            MIN_JUSTIFICATION_LENGTH
+        ).into());
+    }
+    if impersonation.0.lock().map_err(|e| e.to_string())?.is_some() {
+        return Err("Stop viewing as the current user first".to_string().into());
+    }
+    require_capability(&session, |c| c.unlock_users, "view the app as another user")?;
+    // The id goes into the SCIM path, so it can't be allowed to reach another endpoint
+    if !is_scim_id(&user_id) {
+        return Err(format!("Invalid user id: {}", user_id).into());
+    }
+
+    let target_profile = if demo.enabled {
+        DemoState::profile_by_id(&user_id).ok_or_else(|| format!("User not found: {}", user_id))?
+    } else {
+        let bearer_token = session.bearer_token()?;
+        let path = format!("/admin/v1/Users/{}", user_id);
+        cache
+            .get_or_fetch(&format!("{}{}", USER_PREFIX, user_id), USER_TTL, || {
+                get_settings(&auth_client, &bearer_token, &path)
+            })
+            .await?
+    };
+
+    let original = session
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("Not signed in")?;
+    let details = Impersonation {
+        actor: original.profile["userName"].as_str().unwrap_or_default().to_string(),
+        target: target_profile["userName"].as_str().unwrap_or(&user_id).to_string(),
+        target_display_name: target_profile["displayName"].as_str().unwrap_or_default().to_string(),
+        justification: justification.trim().to_string(),
+        started_at: Utc::now(),
+    };
+
+    // No audit record, no impersonation
+    audit::record(
+        &app_handle,
+        "impersonation_started",
+        json!({
+            "actor": details.actor,
+            "target": details.target,
+            "target_id": user_id,
+            "justification": details.justification,
+        }),
+    )?;
+    log::warn!("{} started impersonating {}", details.actor, details.target);
+
+    *session.0.lock().map_err(|e| e.to_string())? = Some(Session {
+        profile: target_profile.clone(),
+        access_token: None,
+        client_ip: None,
+        expires_at: None,
+        dpop_key: None,
+        refresh_token: None,
+        scopes: None,
+    });
+    *impersonation.0.lock().map_err(|e| e.to_string())? = Some(ActiveImpersonation {
+        details: details.clone(),
+        original,
+    });
+
+    show_session_mode(&app_handle, Some(&details));
+    Ok(target_profile)
+}
+
+#[command]
+pub fn end_view_as_user(app_handle: AppHandle) -> Result<(), CommandError> {
+    Ok(restore_original(&app_handle)?)
+}
+
+// Ends impersonation, if active, and puts the helpdesk user's own session back; also used by sign-out
+pub(crate) fn restore_original(app_handle: &AppHandle) -> Result<(), String> {
+    let impersonation = app_handle.state::<ImpersonationState>();
+    let Some(active) = impersonation.0.lock().map_err(|e| e.to_string())?.take() else {
+        return Ok(());
+    };
+
+    let duration = Utc::now() - active.details.started_at;
+    if let Err(e) = audit::record(
+        app_handle,
+        "impersonation_ended",
+        json!({
+            "actor": active.details.actor,
+            "target": active.details.target,
+            "duration_seconds": duration.num_seconds(),
+        }),
+    ) {
+        log::error!("Failed to write impersonation audit record: {}", e);
+    }
+    log::warn!("{} stopped impersonating {}", active.details.actor, active.details.target);
+
+    *app_handle.state::<SessionState>().0.lock().map_err(|e| e.to_string())? = Some(active.original);
+    show_session_mode(app_handle, None);
+    Ok(())
+}
+
+#[command]
+pub fn get_view_as_user(impersonation: State<ImpersonationState>) -> Result<Option<Impersonation>, CommandError> {
+    Ok(impersonation
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|active| active.details.clone()))
+}
+
+// The window title marks the session as impersonated even if the frontend misses the event
+fn show_session_mode(app_handle: &AppHandle, details: Option<&Impersonation>) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let title = match details {
+            Some(details) => format!("{} - Viewing as {} ({})", WINDOW_TITLE, details.target_display_name, details.target),
+            None => WINDOW_TITLE.to_string(),
+        };
+        let _ = window.set_title(&title);
+    }
+    crate::bus::emit(app_handle, "impersonation-changed", details);
+}
+
+// SCIM resource ids are opaque but only ever use these characters
+fn is_scim_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
@@ -0,0 +1,126 @@
+use serde_json::Value;
+use std::path::Path;
+use tauri::{AppHandle, DragDropEvent, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::config::{AppConfig, ConfigState, LogLevel};
+
+const SESSION_BUNDLE_EXTENSION: &str = "ocisession";
+
+// Files dropped onto the main window are validated, then imported once the user confirms
+pub fn handle_drag_drop(app_handle: &AppHandle, event: &DragDropEvent) {
+    if crate::kiosk::is_enabled(app_handle) {
+        return;
+    }
+    if let DragDropEvent::Drop { paths, .. } = event {
+        for path in paths {
+            import_dropped_file(app_handle, path);
+        }
+    }
+}
+
+fn import_dropped_file(app_handle: &AppHandle, path: &Path) {
+    log::info!("File dropped for import: {}", path.display());
+
+    let config = match read_config_profile(path) {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Rejected dropped file {}: {}", path.display(), e);
+            app_handle
+                .dialog()
+                .message(format!("{} can't be imported.\n\n{}", file_name(path), e))
+                .title("Import failed")
+                .kind(MessageDialogKind::Error)
+                .buttons(MessageDialogButtons::Ok)
+                .show(|_| {});
+            return;
+        }
+    };
+
+    let summary = format!(
+        "Import settings from {}?\n\nLog level: {}\nLocale: {}\nTheme: {}\n\nYour current settings will be replaced.",
+        file_name(path),
+        config.logging.level,
+        config.ui.locale.as_deref().unwrap_or("system default"),
+        config.ui.theme,
+    );
+    let handle = app_handle.clone();
+    let source = path.to_path_buf();
+    app_handle
+        .dialog()
+        .message(summary)
+        .title("Import configuration")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom("Import".to_string(), "Cancel".to_string()))
+        .show(move |confirmed| {
+            if !confirmed {
+                log::info!("Import of {} cancelled", source.display());
+                return;
+            }
+            if let Err(e) = apply_config(&handle, config) {
+                log::error!("Failed to import {}: {}", source.display(), e);
+            }
+        });
+}
+
+// Accepts a bare config or the `{"config": ...}` layout of the app's own config.json
+fn read_config_profile(path: &Path) -> Result<AppConfig, String> {
+    if path.extension().and_then(|ext| ext.to_str()) == Some(SESSION_BUNDLE_EXTENSION) {
+        return Err("Encrypted session bundles aren't supported by this version.".to_string());
+    }
+
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&contents).map_err(|_| "The file is not a configuration profile.".to_string())?;
+    let value = match value.get("config") {
+        Some(config) => config.clone(),
+        None => value,
+    };
+    let config: AppConfig =
+        serde_json::from_value(value).map_err(|e| format!("The configuration profile is invalid: {}", e))?;
+
+    if config.logging.file_size_mb == 0 {
+        return Err("Log file size must be greater than 0 MB".to_string());
+    }
+    if config.logging.file_count == 0 {
+        return Err("Log file count must be greater than 0".to_string());
+    }
+    if let Some(locale) = &config.ui.locale {
+        if !crate::i18n::is_valid_locale(locale) {
+            return Err(format!("Invalid locale: {}", locale));
+        }
+    }
+    Ok(config)
+}
+
+fn apply_config(app_handle: &AppHandle, config: AppConfig) -> Result<(), String> {
+    config.save(app_handle).map_err(|e| e.to_string())?;
+
+    crate::i18n::set_locale(config.ui.locale.as_deref());
+    crate::sinks::register_from_config(&config.logging.sinks);
+    log::set_max_level(match config.logging.level {
+        LogLevel::Trace => log::LevelFilter::Trace,
+        LogLevel::Debug => log::LevelFilter::Debug,
+        LogLevel::Info => log::LevelFilter::Info,
+        LogLevel::Warn => log::LevelFilter::Warn,
+        LogLevel::Error => log::LevelFilter::Error,
+        LogLevel::Off => log::LevelFilter::Off,
+    });
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.set_theme(config.ui.theme.to_window_theme());
+        let _ = window.set_always_on_top(config.ui.always_on_top);
+    }
+
+    let state = app_handle.state::<ConfigState>();
+    *state.0.lock() = config.clone();
+    crate::tray::sync_menu(app_handle);
+
+    log::info!("Configuration imported");
+    crate::bus::emit(app_handle, "config-imported", config);
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
@@ -0,0 +1,62 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::{CommandError, ErrorKind};
+use crate::middleware::command;
+use crate::policy::PolicyState;
+use crate::profiles;
+
+// Shared terminals that only need the sign-in flow, turned on with `--kiosk` or the `kiosk` policy. Settings
+// can't be changed, the profile is fixed and the window fills the screen without decorations.
+#[derive(Default)]
+pub struct KioskState {
+    pub enabled: bool,
+}
+
+impl KioskState {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+pub fn is_enabled(app_handle: &AppHandle) -> bool {
+    app_handle.try_state::<KioskState>().is_some_and(|kiosk| kiosk.enabled)
+}
+
+// For commands that change settings, profiles or stored data
+pub fn require_unlocked(app_handle: &AppHandle) -> Result<(), CommandError> {
+    if is_enabled(app_handle) {
+        return Err(CommandError::new(
+            ErrorKind::Policy,
+            "Settings can't be changed on this computer because it runs in kiosk mode",
+        ));
+    }
+    Ok(())
+}
+
+// Lets the frontend hide settings, profile switching and other controls that kiosk mode refuses
+#[command]
+pub fn is_kiosk_mode(kiosk: State<KioskState>) -> Result<bool, CommandError> {
+    Ok(kiosk.enabled)
+}
+
+// Selects the profile the policy names, before the app loads the active one
+pub fn pin_profile(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(name) = app_handle
+        .state::<PolicyState>()
+        .0
+        .kiosk
+        .as_ref()
+        .and_then(|kiosk| kiosk.profile.clone())
+    else {
+        return Ok(());
+    };
+    profiles::select(app_handle, &name)?;
+    log::info!("Kiosk mode: using the {} profile", name);
+    Ok(())
+}
+
+pub fn apply_window(app_handle: &AppHandle) -> Result<(), String> {
+    let window = crate::window::main_window(app_handle)?;
+    window.set_decorations(false).map_err(|e| e.to_string())?;
+    window.set_fullscreen(true).map_err(|e| e.to_string())
+}
@@ -1,5 +1,101 @@
+// Lets code generated by `#[command]` name this crate the same way here and in the binary
+extern crate self as oci_auth_tauri;
+
+pub mod access;
+pub mod accessibility;
+pub mod admin;
+#[cfg(feature = "telemetry")]
+pub mod analytics;
+#[cfg(not(feature = "telemetry"))]
+#[path = "minimal/analytics.rs"]
+pub mod analytics;
+#[cfg(feature = "scim-admin")]
+pub mod approvals;
+#[cfg(not(feature = "scim-admin"))]
+#[path = "minimal/approvals.rs"]
+pub mod approvals;
+pub mod cache;
+pub mod canary;
+pub mod circuit;
+pub mod cli;
+pub mod client_assertion;
+pub mod clipboard;
 pub mod config;
+pub mod audit;
 pub mod auth;
+pub mod auth_client;
+pub mod backup;
+pub mod bench;
+pub mod branding;
+pub mod bus;
+pub mod demo;
+pub mod device;
+pub mod device_login;
+pub mod endpoints;
+pub mod enrollment;
+pub mod error;
+#[cfg(debug_assertions)]
+pub mod dev;
+#[cfg(feature = "scim-admin")]
+pub mod directory;
+#[cfg(not(feature = "scim-admin"))]
+#[path = "minimal/directory.rs"]
+pub mod directory;
+pub mod dpop;
+pub mod export;
+pub mod features;
+pub mod guest;
+pub mod headless;
+pub mod heartbeat;
+pub mod i18n;
+#[cfg(feature = "scim-admin")]
+pub mod impersonation;
+#[cfg(not(feature = "scim-admin"))]
+#[path = "minimal/impersonation.rs"]
+pub mod impersonation;
+pub mod import;
+pub mod kiosk;
+pub mod lifecycle;
+pub mod local_ipc;
+pub mod location;
+pub mod maintenance;
+pub mod menu;
+pub mod middleware;
+pub mod motd;
+pub mod my_sessions;
+pub mod network;
+pub mod notifications;
+pub mod oci_cli;
+pub mod paths;
+pub mod pkce;
+pub mod platform;
+pub mod policy;
+pub mod preflight;
+pub mod privacy;
+pub mod profiles;
+pub mod reachability;
+pub mod reauth;
+pub mod redact;
+pub mod renewal;
+pub mod runtime;
+#[cfg(debug_assertions)]
+pub mod scenario;
+pub mod schema;
+pub mod security_questions;
+pub mod session;
+pub mod sinks;
+pub mod supervisor;
+pub mod tenant;
+pub mod token_manager;
+#[cfg(feature = "tray")]
+pub mod tray;
+#[cfg(not(feature = "tray"))]
+#[path = "minimal/tray.rs"]
+pub mod tray;
+pub mod verification;
+pub mod warmup;
+pub mod webauthn;
+pub mod window;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -0,0 +1,116 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::bus::{CoreEvent, EventBus};
+use crate::error::{AuthError, CommandError};
+use crate::middleware::command;
+use crate::session::SessionState;
+
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+// Where the user is in the sign-in lifecycle; the tray, menu and frontend all derive their UI from this
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppState {
+    #[default]
+    LoggedOut,
+    // Started without client credentials; only settings and the onboarding wizard are usable
+    ConfigurationRequired,
+    // Credentials submitted, waiting for IDCS
+    Authenticating,
+    // IDCS asked for a second factor
+    MfaPending,
+    LoggedIn,
+    // IDCS or the demo provider reported the account as locked
+    Locked,
+    // Signed in, but the access token has run out
+    Expired,
+}
+
+#[derive(Default)]
+pub struct LifecycleState(pub Mutex<AppState>);
+
+impl LifecycleState {
+    // A panic elsewhere can't wedge the state: every value of it is valid
+    pub fn current(&self) -> AppState {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Moves to `to` and publishes `AppStateChanged` on `bus`, for the Tauri layer to rebuild the menu and tell the
+    // webview; a no-op when already there
+    pub fn transition(&self, to: AppState, bus: &EventBus) {
+        let from = std::mem::replace(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner), to);
+        if from == to {
+            return;
+        }
+        log::info!("App state changed from {:?} to {:?}", from, to);
+        bus.publish(CoreEvent::AppStateChanged(to));
+    }
+}
+
+#[command]
+pub fn get_app_state(lifecycle: State<LifecycleState>) -> Result<AppState, CommandError> {
+    Ok(lifecycle.current())
+}
+
+pub fn current(app_handle: &AppHandle) -> AppState {
+    app_handle
+        .try_state::<LifecycleState>()
+        .map(|lifecycle| lifecycle.current())
+        .unwrap_or_default()
+}
+
+pub fn transition(app_handle: &AppHandle, to: AppState) {
+    let (Some(lifecycle), Some(bus)) = (app_handle.try_state::<LifecycleState>(), app_handle.try_state::<EventBus>())
+    else {
+        return;
+    };
+    lifecycle.transition(to, &bus);
+}
+
+// Where a failed sign-in step leaves the app: Locked for a locked account, else back to the session's state
+pub fn sign_in_failed(app_handle: &AppHandle, error: &AuthError) {
+    let locked = matches!(error, AuthError::AccountLocked { .. });
+    let signed_in = app_handle
+        .state::<SessionState>()
+        .0
+        .lock()
+        .map(|session| session.is_some())
+        .unwrap_or(false);
+    transition(
+        app_handle,
+        match (locked, signed_in) {
+            (true, _) => AppState::Locked,
+            (false, true) => AppState::LoggedIn,
+            (false, false) => AppState::LoggedOut,
+        },
+    );
+}
+
+// A wrong passcode leaves the factor pending so it can be retried; a locked account ends the sign-in
+pub fn factor_failed(app_handle: &AppHandle, error: &AuthError) {
+    if matches!(error, AuthError::AccountLocked { .. }) {
+        transition(app_handle, AppState::Locked);
+    }
+}
+
+// Turns LoggedIn into Expired once the session's access token runs out
+pub async fn watch_session_expiry(app_handle: AppHandle) {
+    loop {
+        crate::supervisor::alive(&app_handle, EXPIRY_CHECK_INTERVAL);
+        let expired = app_handle
+            .state::<SessionState>()
+            .0
+            .lock()
+            .ok()
+            .and_then(|session| session.as_ref().and_then(|session| session.expires_at))
+            .is_some_and(|expires_at| expires_at <= Utc::now());
+        if expired && current(&app_handle) == AppState::LoggedIn {
+            transition(&app_handle, AppState::Expired);
+        }
+        tokio::time::sleep(EXPIRY_CHECK_INTERVAL).await;
+    }
+}
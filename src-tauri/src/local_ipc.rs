@@ -0,0 +1,188 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+// Signed nonces for local IPC. The app has no local IPC surface yet (no broker, deep links or single-instance
+// forwarding), so nothing calls `verify` today; whichever surface is added first must accept only messages it
+// passes. The key is written at startup so local clients can already sign.
+
+// Shared with local clients through a file in the local app data directory. On unix it is created with mode
+// 0600; on Windows it takes the directory's ACL, which is only user-only at the default location in the profile.
+const KEY_FILE: &str = "ipc.key";
+const NONCE_TTL: Duration = Duration::from_secs(60);
+
+type HmacSha256 = Hmac<Sha256>;
+
+// A request from another local process. `signature` is the hex HMAC-SHA256 of `nonce` and
+// `payload` joined by a newline, keyed with the per-boot secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMessage {
+    pub nonce: String,
+    pub payload: String,
+    pub signature: String,
+}
+
+// Per-boot secret and the nonces handed out under it. A nonce is accepted once, within a minute.
+pub struct LocalIpcState {
+    secret: [u8; 32],
+    nonces: Mutex<HashMap<String, Instant>>,
+}
+
+impl LocalIpcState {
+    pub fn new() -> Result<Self, String> {
+        Ok(Self {
+            secret: random_bytes()?,
+            nonces: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn issue_nonce(&self) -> Result<String, String> {
+        let nonce = hex(&random_bytes::<16>()?);
+        let mut nonces = self.nonces.lock().map_err(|e| e.to_string())?;
+        nonces.retain(|_, issued_at| issued_at.elapsed() < NONCE_TTL);
+        nonces.insert(nonce.clone(), Instant::now());
+        Ok(nonce)
+    }
+
+    pub fn sign(&self, nonce: &str, payload: &str) -> Result<String, String> {
+        Ok(hex(&self.mac(nonce, payload)?.finalize().into_bytes()))
+    }
+
+    // Checks the signature, then consumes the nonce; returns the payload when both are valid. A forged message
+    // leaves the nonce for the client it was issued to.
+    pub fn verify<'a>(&self, message: &'a SignedMessage) -> Result<&'a str, String> {
+        let signature = unhex(&message.signature).ok_or_else(|| "Invalid signature".to_string())?;
+        if self.mac(&message.nonce, &message.payload)?.verify_slice(&signature).is_err() {
+            log::warn!("Rejected local IPC message with an invalid signature");
+            return Err("Invalid signature".to_string());
+        }
+
+        let issued_at = self
+            .nonces
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&message.nonce)
+            .ok_or_else(|| "Unknown or already used nonce".to_string())?;
+        if issued_at.elapsed() >= NONCE_TTL {
+            return Err("Nonce expired".to_string());
+        }
+        Ok(&message.payload)
+    }
+
+    fn mac(&self, nonce: &str, payload: &str) -> Result<HmacSha256, String> {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).map_err(|e| e.to_string())?;
+        mac.update(format!("{}\n{}", nonce, payload).as_bytes());
+        Ok(mac)
+    }
+
+    // Writes the secret where local clients can read it, replacing the last boot's
+    pub fn write_key_file(&self, app_handle: &AppHandle) -> Result<PathBuf, String> {
+        let path = key_file_path(app_handle)?;
+        let _ = std::fs::remove_file(&path);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path).map_err(|e| e.to_string())?;
+        std::io::Write::write_all(&mut file, hex(&self.secret).as_bytes()).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+fn key_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::local_data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(KEY_FILE))
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("The OS random number generator is unavailable: {}", e))?;
+    Ok(bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(state: &LocalIpcState, payload: &str) -> SignedMessage {
+        let nonce = state.issue_nonce().unwrap();
+        let signature = state.sign(&nonce, payload).unwrap();
+        SignedMessage { nonce, payload: payload.to_string(), signature }
+    }
+
+    #[test]
+    fn verify_accepts_a_signed_message() {
+        let state = LocalIpcState::new().unwrap();
+        let message = signed(&state, "open");
+        assert_eq!(state.verify(&message), Ok("open"));
+    }
+
+    #[test]
+    fn verify_rejects_a_reused_nonce() {
+        let state = LocalIpcState::new().unwrap();
+        let message = signed(&state, "open");
+        state.verify(&message).unwrap();
+        assert!(state.verify(&message).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_a_tampered_payload_and_keeps_the_nonce() {
+        let state = LocalIpcState::new().unwrap();
+        let message = signed(&state, "open");
+        let tampered = SignedMessage { payload: "quit".to_string(), ..message.clone() };
+        assert!(state.verify(&tampered).is_err());
+        assert_eq!(state.verify(&message), Ok("open"));
+    }
+
+    #[test]
+    fn verify_rejects_another_boots_key() {
+        let state = LocalIpcState::new().unwrap();
+        let other = LocalIpcState::new().unwrap();
+        let nonce = state.issue_nonce().unwrap();
+        let message = SignedMessage {
+            signature: other.sign(&nonce, "open").unwrap(),
+            nonce,
+            payload: "open".to_string(),
+        };
+        assert!(state.verify(&message).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_a_nonce_it_did_not_issue() {
+        let state = LocalIpcState::new().unwrap();
+        let signature = state.sign("made-up", "open").unwrap();
+        let message = SignedMessage { nonce: "made-up".to_string(), payload: "open".to_string(), signature };
+        assert!(state.verify(&message).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_a_malformed_signature() {
+        let state = LocalIpcState::new().unwrap();
+        let message = SignedMessage { signature: "abc".to_string(), ..signed(&state, "open") };
+        assert!(state.verify(&message).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::audit;
+use crate::config::{ConfigState, NotificationEvent};
+use crate::notifications;
+
+const AUDIT_EVENT: &str = "login_location";
+// Further than this from the previous login counts as a drastic change
+const DRASTIC_CHANGE_KM: f64 = 1000.0;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// Field names cover the common geo-IP services (ipapi.co, ipwho.is, ip-api.com)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoginLocation {
+    #[serde(alias = "query")]
+    pub ip: Option<String>,
+    pub city: Option<String>,
+    #[serde(alias = "country_code", alias = "countryCode")]
+    pub country: Option<String>,
+    #[serde(alias = "lat")]
+    pub latitude: Option<f64>,
+    #[serde(alias = "lon")]
+    pub longitude: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocationChange {
+    pub previous: LoginLocation,
+    pub current: LoginLocation,
+    pub distance_km: Option<f64>,
+}
+
+impl LoginLocation {
+    fn is_empty(&self) -> bool {
+        self.ip.is_none() && self.country.is_none() && self.latitude.is_none()
+    }
+
+    fn distance_km(&self, other: &LoginLocation) -> Option<f64> {
+        let (lat1, lon1) = (self.latitude?.to_radians(), self.longitude?.to_radians());
+        let (lat2, lon2) = (other.latitude?.to_radians(), other.longitude?.to_radians());
+        let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+            + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+        Some(2.0 * EARTH_RADIUS_KM * a.sqrt().asin())
+    }
+
+    // A different country, or a long way from last time; a new IP alone is normal
+    fn is_drastic_change_from(&self, previous: &LoginLocation) -> bool {
+        let country_changed = matches!(
+            (&self.country, &previous.country),
+            (Some(current), Some(previous)) if !current.eq_ignore_ascii_case(previous)
+        );
+        country_changed
+            || self
+                .distance_km(previous)
+                .map(|distance| distance > DRASTIC_CHANGE_KM)
+                .unwrap_or(false)
+    }
+}
+
+// Records where this login came from in the audit log and warns when it is far from the previous one.
+// Runs after sign-in completes so a slow lookup never delays the login.
+pub async fn record_login_location(app_handle: AppHandle, client_ip: Option<String>) {
+    let lookup = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.network.location_lookup.clone());
+
+    let mut current = match lookup {
+        Some(url) => match fetch_location(&url).await {
+            Ok(location) => location,
+            Err(e) => {
+                log::warn!("Failed to look up the login location: {}", e);
+                LoginLocation::default()
+            }
+        },
+        None => LoginLocation::default(),
+    };
+    // IDCS saw the address the login actually came from; prefer it over the lookup's view
+    if client_ip.is_some() {
+        current.ip = client_ip;
+    }
+    if current.is_empty() {
+        return;
+    }
+
+    let previous: Option<LoginLocation> = audit::latest(&app_handle, AUDIT_EVENT)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read the previous login location: {}", e);
+            None
+        })
+        .and_then(|details| serde_json::from_value(details["location"].clone()).ok());
+    let change = previous
+        .filter(|previous| current.is_drastic_change_from(previous))
+        .map(|previous| LocationChange {
+            distance_km: current.distance_km(&previous),
+            previous,
+            current: current.clone(),
+        });
+
+    if let Err(e) = audit::record(
+        &app_handle,
+        AUDIT_EVENT,
+        json!({ "location": current, "changed": change.is_some() }),
+    ) {
+        log::warn!("Failed to record the login location: {}", e);
+    }
+
+    if let Some(change) = change {
+        log::warn!(
+            "Login from {} differs from the previous login from {}",
+            describe(&change.current),
+            describe(&change.previous)
+        );
+        crate::bus::emit(&app_handle, "login-location-changed", &change);
+        notifications::notify(
+            &app_handle,
+            NotificationEvent::LocationChanged,
+            &format!("You signed in from {}, far from your last sign-in", describe(&change.current)),
+        );
+    }
+}
+
+fn describe(location: &LoginLocation) -> String {
+    match (&location.city, &location.country) {
+        (Some(city), Some(country)) => format!("{}, {}", city, country),
+        (None, Some(country)) => country.clone(),
+        _ => location.ip.clone().unwrap_or_else(|| "an unknown location".to_string()),
+    }
+}
+
+async fn fetch_location(url: &str) -> Result<LoginLocation, String> {
+    let response = reqwest::get(url).await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to get {}: {}", url, response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
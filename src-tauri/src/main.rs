@@ -1,38 +1,154 @@
 use tauri::Manager;
-use tauri::menu::{Menu, MenuItem, Submenu, MenuId};
-use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
 use tauri_plugin_log::{Target, TargetKind, Builder as LogBuilder};
-use log::{debug, info, warn, LevelFilter, error};
+use log::{info, warn, LevelFilter, error};
 use tauri::State;
-use tauri_plugin_cli::CliExt;
+use tauri_plugin_cli::{CliExt, Matches};
 use tauri_plugin_store::Builder as StoreBuilder;
 use chrono::Local;
-use std::sync::Mutex;
-use oci_auth_tauri::config::{AppConfig, LogLevel};
+use std::io::Write;
+use oci_auth_tauri::access::{diff_access, list_access_snapshots};
+use oci_auth_tauri::accessibility::{get_accessibility_prefs, watch_accessibility_prefs};
+use oci_auth_tauri::cache::{get_cached_profile, invalidate_cache, CacheState};
+use oci_auth_tauri::canary::{get_canary_results, run_canary};
+use oci_auth_tauri::circuit::{get_circuit_status, install as install_circuit_breaker};
+use oci_auth_tauri::cli::{self, ConfigReport, OutputFormat, SettingChanged, WipeOutput};
+use oci_auth_tauri::clipboard::{
+    copy_access_token, get_access_token, get_token_preview, get_token_protection, set_token_protection,
+};
+use oci_auth_tauri::config::{AppConfig, ConfigState};
+use oci_auth_tauri::admin::get_admin_capabilities;
+use oci_auth_tauri::analytics::{flush_analytics, get_analytics_queue, set_analytics_consent, AnalyticsState};
+use oci_auth_tauri::approvals::{
+    approve_access_request, list_access_requests, poll_access_requests, reject_access_request, ApprovalsState,
+};
+use oci_auth_tauri::auth::{
+    complete_auth, initiate_auth, logout, request_otp, select_auth_factor, set_preferred_factors, start_push_auth,
+    submit_bypass_code, submit_otp, submit_totp,
+};
+use oci_auth_tauri::auth_client::AuthClient;
+use oci_auth_tauri::backup::{backup_app_data, restore_app_data};
+use oci_auth_tauri::bench;
+use oci_auth_tauri::branding;
+use oci_auth_tauri::bus::{forward as forward_events, EventBus};
+use oci_auth_tauri::demo::DemoState;
+use oci_auth_tauri::device::{self, get_device_binding_info};
+use oci_auth_tauri::device_login::{start_device_login, DeviceLoginState};
+use oci_auth_tauri::directory::{search_directory, DirectoryState};
+use oci_auth_tauri::endpoints::get_idcs_endpoint;
+use oci_auth_tauri::export::export_profile;
+use oci_auth_tauri::error::CommandError;
+use oci_auth_tauri::enrollment::{confirm_factor_enrollment, start_factor_enrollment};
+use oci_auth_tauri::features::{is_feature_enabled, list_feature_flags, refresh_feature_flags, set_feature_flag};
+use oci_auth_tauri::guest::GuestState;
+use oci_auth_tauri::headless::{self, HeadlessOutput};
+use oci_auth_tauri::heartbeat::{heartbeat, watch_heartbeat, HeartbeatState};
+use oci_auth_tauri::i18n;
+use oci_auth_tauri::impersonation::{end_view_as_user, get_view_as_user, view_as_user, ImpersonationState};
+use oci_auth_tauri::kiosk::{self, is_kiosk_mode, KioskState};
+use oci_auth_tauri::lifecycle::{get_app_state, watch_session_expiry, LifecycleState};
+use oci_auth_tauri::local_ipc::LocalIpcState;
+use oci_auth_tauri::maintenance::{get_maintenance_window, watch_maintenance, MaintenanceState};
+use oci_auth_tauri::menu::refresh_menu;
+use oci_auth_tauri::middleware::command;
+use oci_auth_tauri::my_sessions::{
+    list_my_sessions, terminate_other_sessions, terminate_session, watch_other_sessions, SessionGuardState,
+};
+use oci_auth_tauri::network::{check_tls_settings, get_network_settings, set_network_settings};
+use oci_auth_tauri::notifications::set_notification_prefs;
+use oci_auth_tauri::oci_cli::export_token;
+use oci_auth_tauri::motd::{dismiss_motd, get_motd, refresh_motd, MotdState};
+use oci_auth_tauri::paths;
+use oci_auth_tauri::pkce::{start_pkce_login, PkceState};
+use oci_auth_tauri::platform::{
+    self, get_launch_at_login, get_platform_capabilities, set_force_software_rendering, set_launch_at_login,
+    set_screen_capture_blocked,
+};
+use oci_auth_tauri::policy::{Policy, PolicyState};
+use oci_auth_tauri::preflight::{self, get_preflight_report};
+use oci_auth_tauri::privacy::{self, enforce_retention, get_local_data_inventory, wipe_all_local_data, WipeSummary};
+use oci_auth_tauri::profiles::{
+    activate as activate_profile, add_profile, delete_profile, get_active_profile, get_login_start,
+    list_profiles, save_client_certificate_passphrase, save_client_credentials, set_active_profile, set_idcs_urls,
+    set_maintenance_windows, set_profile_login_options,
+};
+use oci_auth_tauri::reachability::{check_at_startup as check_reachability, check_ipv6_reachability};
+use oci_auth_tauri::reauth::{confirm_reauth, get_reauth_reason, prompt_reauth, submit_reauth, ReauthState};
+use oci_auth_tauri::renewal::{refresh_session, renew_sessions, resume_saved_session, RenewalState};
+use oci_auth_tauri::runtime::{get_runtime_stats, monitor_memory};
+#[cfg(debug_assertions)]
+use oci_auth_tauri::scenario::run_scenario;
+use oci_auth_tauri::sinks::{self, list_log_sinks, set_log_sink_enabled};
+use oci_auth_tauri::supervisor::{supervise, SupervisorState};
+use oci_auth_tauri::security_questions::{get_security_questions, submit_security_answers};
+use oci_auth_tauri::session::{
+    get_profile, get_session_info, get_session_state, reset_session, AuthSessionState, SessionState,
+};
+use oci_auth_tauri::tray::{create_tray, watch_token_expiry};
+use oci_auth_tauri::verification::{get_verification_phrase, set_verification_phrase};
+use oci_auth_tauri::webauthn::{start_webauthn, submit_webauthn_assertion};
+use oci_auth_tauri::window::{
+    apply_theme, is_compact_mode, move_to_cursor_monitor, prepare_main_window, set_always_on_top, set_compact_mode,
+    CompactState,
+};
+use oci_auth_tauri::warmup::warm_up;
+use oci_auth_tauri::tenant::{get_login_hints, get_tenant_branding};
+use oci_auth_tauri::token_manager::TokenManager;
+#[cfg(debug_assertions)]
+use oci_auth_tauri::dev::{debug_restore_state, debug_snapshot_state, SnapshotState};
 use dotenvy::dotenv;
-mod config;
-mod auth;
 
-use auth::{complete_auth, initiate_auth};
+#[command(unlocked)]
+fn update_log_level(app_handle: tauri::AppHandle, state: tauri::State<ConfigState>, new_level: String) -> Result<(), CommandError> {
+    let mut config = state.0.lock();
+    config.set_log_level(&app_handle, &new_level)?;
+    // Applies right away, including to the sign-in spans
+    log::set_max_level(config.logging.level.filter());
+    Ok(())
+}
 
-#[derive(Default)]
-pub struct ConfigState(Mutex<AppConfig>);
+#[command]
+fn get_log_level(state: tauri::State<ConfigState>) -> Result<String, CommandError> {
+    let config = state.0.lock();
+    Ok(config.logging.level.to_string())
+}
 
-#[tauri::command]
-fn update_log_level(app_handle: tauri::AppHandle, state: tauri::State<ConfigState>, new_level: String) -> Result<(), String> {
-    let mut config = state.0.lock().map_err(|e| e.to_string())?;
-    config.set_log_level(&app_handle, &new_level).map_err(|e| e.to_string())
+#[command(unlocked)]
+fn update_locale(app_handle: tauri::AppHandle, state: tauri::State<ConfigState>, new_locale: Option<String>) -> Result<(), CommandError> {
+    let mut config = state.0.lock();
+    config.set_locale(&app_handle, new_locale.as_deref())?;
+    Ok(())
 }
 
-#[tauri::command]
-fn get_log_level(state: tauri::State<ConfigState>) -> Result<String, String> {
-    let config = state.0.lock().map_err(|e| e.to_string())?;
-    Ok(config.logging.level.to_string())
+#[command]
+fn get_locale() -> Result<String, CommandError> {
+    Ok(i18n::current_locale())
 }
 
-#[tauri::command]
-fn get_current_config(config_state: State<ConfigState>) -> Result<AppConfig, String> {
-    let config = config_state.0.lock().map_err(|e| e.to_string())?;
+#[command(unlocked)]
+fn set_theme(app_handle: tauri::AppHandle, state: tauri::State<ConfigState>, theme: String) -> Result<(), CommandError> {
+    let mut config = state.0.lock();
+    config.set_theme(&app_handle, &theme).map_err(|e| e.to_string())?;
+    apply_theme(&app_handle, config.ui.theme);
+    Ok(())
+}
+
+#[command(unlocked)]
+fn set_idcs_url(app_handle: tauri::AppHandle, state: tauri::State<ConfigState>, url: Option<String>) -> Result<(), CommandError> {
+    let mut config = state.0.lock();
+    config.set_idcs_url(&app_handle, url.as_deref()).map_err(|e| e.to_string())?;
+    activate_profile(&app_handle)?;
+    Ok(())
+}
+
+#[command]
+fn get_theme(state: tauri::State<ConfigState>) -> Result<String, CommandError> {
+    let config = state.0.lock();
+    Ok(config.ui.theme.to_string())
+}
+
+#[command]
+fn get_current_config(config_state: State<ConfigState>) -> Result<AppConfig, CommandError> {
+    let config = config_state.0.lock();
     Ok(config.clone())
 }
 
@@ -53,7 +169,7 @@ fn handle_cli_commands(app: &tauri::App) -> Result<bool, Box<dyn std::error::Err
     let our_args = matches.args.iter().any(|(k, v)| {
         let is_ours = matches!(k.as_str(), 
             "get-config" | "log-level" | "log-size" | 
-            "log-count" | "clear-config" | "help");
+            "log-count" | "clear-config" | "wipe" | "idcs-url" | "authenticate" | "export-token" | "help");
         let was_provided = v.occurrences > 0;
         //println!("  Checking arg '{}': is_ours = {}, was_provided = {}", k, is_ours, was_provided);
         is_ours && was_provided
@@ -64,30 +180,69 @@ fn handle_cli_commands(app: &tauri::App) -> Result<bool, Box<dyn std::error::Err
         return Ok(false);
     }
 
-    // Found provided arguments, handling CLI command
-    let app_handle = app.handle();
+    let format = OutputFormat::parse(matches.args.get("output").and_then(|v| v.value.as_str()))?;
+    if format == OutputFormat::Json {
+        // Stdout is for the JSON alone, and the log plugin writes there too
+        log::set_max_level(LevelFilter::Off);
+    }
+    match run_cli_command(app.handle(), &matches, format) {
+        Err(e) if format == OutputFormat::Json => {
+            cli::print_error(format, &e.to_string());
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
+
+fn run_cli_command(
+    app_handle: &tauri::AppHandle,
+    matches: &Matches,
+    format: OutputFormat,
+) -> Result<bool, Box<dyn std::error::Error>> {
     let mut config = AppConfig::load(&app_handle)?;
 
     // Handle each CLI command
     if matches.args.get("get-config").map(|v| v.occurrences > 0).unwrap_or(false) {
         let today = Local::now().format("%Y-%m-%d").to_string();
-        let log_dir = app.path().app_log_dir().unwrap_or_default();
+        let log_dir = paths::log_dir(app_handle).unwrap_or_default();
         let log_path = log_dir.join(format!("oci-auth-{}", today));
         
-        println!("Current configuration:");
-        println!("Log filename: {}", log_path.display());
-        println!("Store plugin config: {}/config.json", app.path().app_data_dir().unwrap().display());
-        println!("Log level: {}", config.logging.level);
-        println!("Max log file size: {}MB", config.logging.file_size_mb);
-        println!("Number of log files: {}", config.logging.file_count);
+        cli::print(format, &ConfigReport {
+            log_file: log_path.display().to_string(),
+            config_file: paths::data_dir(app_handle)?.join("config.json").display().to_string(),
+            portable_home: paths::portable_home().map(|home| home.display().to_string()),
+            log_level: config.logging.level.to_string(),
+            log_file_size_mb: config.logging.file_size_mb,
+            log_file_count: config.logging.file_count,
+            idcs_url: config.idcs.base_url.as_deref().unwrap_or(branding::DEFAULT_IDCS_URL).to_string(),
+        });
         return Ok(true);
     }
 
+    if let Some(url) = matches.args.get("idcs-url") {
+        if url.occurrences > 0 {
+            if let Some(value) = url.value.as_str() {
+                config.set_idcs_url(&app_handle, Some(value))?;
+                let url = config.idcs.base_url.as_deref().unwrap_or(value);
+                cli::print(format, &SettingChanged {
+                    setting: "idcs_url",
+                    value: Some(url.into()),
+                    message: format!("IDCS URL set to: {}", url),
+                });
+                return Ok(true);
+            }
+        }
+    }
+
     if let Some(level) = matches.args.get("log-level") {
         if level.occurrences > 0 {
             if let Some(value) = level.value.as_str() {
                 config.set_log_level(&app_handle, value)?;
-                println!("Log level set to: {}", value);
+                cli::print(format, &SettingChanged {
+                    setting: "log_level",
+                    value: Some(value.into()),
+                    message: format!("Log level set to: {}", value),
+                });
                 return Ok(true);
             }
         }
@@ -100,12 +255,15 @@ fn handle_cli_commands(app: &tauri::App) -> Result<bool, Box<dyn std::error::Err
                     if size_mb >= 1 {
                         config.logging.file_size_mb = size_mb;
                         config.save(&app_handle)?;
-                        println!("Log file size set to: {}MB", size_mb);
+                        cli::print(format, &SettingChanged {
+                            setting: "log_file_size_mb",
+                            value: Some(size_mb.into()),
+                            message: format!("Log file size set to: {}MB", size_mb),
+                        });
                         return Ok(true);
                     }
                 }
-                println!("Invalid log size value. Must be a number >= 1");
-                return Ok(true);
+                return Err("Invalid log size value. Must be a number >= 1".into());
             }
         }
     }
@@ -117,12 +275,15 @@ fn handle_cli_commands(app: &tauri::App) -> Result<bool, Box<dyn std::error::Err
                     if file_count >= 1 {
                         config.logging.file_count = file_count;
                         config.save(&app_handle)?;
-                        println!("Number of log files set to: {}", file_count);
+                        cli::print(format, &SettingChanged {
+                            setting: "log_file_count",
+                            value: Some(file_count.into()),
+                            message: format!("Number of log files set to: {}", file_count),
+                        });
                         return Ok(true);
                     }
                 }
-                println!("Invalid log count value. Must be a number >= 1");
-                return Ok(true);
+                return Err("Invalid log count value. Must be a number >= 1".into());
             }
         }
     }
@@ -130,7 +291,65 @@ fn handle_cli_commands(app: &tauri::App) -> Result<bool, Box<dyn std::error::Err
     if matches.args.get("clear-config").map(|v| v.occurrences > 0).unwrap_or(false) {
         config = AppConfig::default();
         config.save(&app_handle)?;
-        println!("Configuration reset to default values");
+        cli::print(format, &SettingChanged {
+            setting: "all",
+            value: None,
+            message: "Configuration reset to default values".to_string(),
+        });
+        return Ok(true);
+    }
+
+    if matches.args.get("authenticate").map(|v| v.occurrences > 0).unwrap_or(false) {
+        let username = matches.args.get("username").and_then(|v| v.value.as_str()).map(str::to_string);
+        let password_stdin = matches.args.get("password-stdin").map(|v| v.occurrences > 0).unwrap_or(false);
+        let print = HeadlessOutput::parse(matches.args.get("print").and_then(|v| v.value.as_str()))?;
+        // Stdout is for the token alone, and the log plugin writes there too
+        log::set_max_level(LevelFilter::Off);
+        match headless::authenticate(app_handle, username, password_stdin, print) {
+            Ok(result) => cli::print(format, &result),
+            Err(e) => {
+                cli::print_error(format, &format!("Authentication failed: {}", e));
+                std::process::exit(1);
+            }
+        }
+        return Ok(true);
+    }
+
+    if let Some(path) = matches.args.get("export-token").and_then(|v| v.value.as_str()) {
+        let username = matches.args.get("username").and_then(|v| v.value.as_str()).map(str::to_string);
+        let password_stdin = matches.args.get("password-stdin").map(|v| v.occurrences > 0).unwrap_or(false);
+        let profile = matches.args.get("oci-profile").and_then(|v| v.value.as_str()).map(str::to_string);
+        let region = matches.args.get("region").and_then(|v| v.value.as_str()).map(str::to_string);
+        log::set_max_level(LevelFilter::Off);
+        match headless::export_token(app_handle, username, password_stdin, path.to_string(), profile, region) {
+            Ok(exported) => cli::print(format, &exported),
+            Err(e) => {
+                cli::print_error(format, &format!("Token export failed: {}", e));
+                std::process::exit(1);
+            }
+        }
+        return Ok(true);
+    }
+
+    if matches.args.get("wipe").map(|v| v.occurrences > 0).unwrap_or(false) {
+        // On stderr, so with `--output json` stdout holds only the result
+        eprintln!(
+            "This revokes your tokens and deletes all settings, caches, history and logs stored by {}.",
+            branding::APP_NAME
+        );
+        eprint!("Continue? [y/N] ");
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            cli::print(format, &WipeOutput { wiped: false, summary: WipeSummary::default() });
+            return Ok(true);
+        }
+
+        // Tokens are revoked at the configured tenant
+        activate_profile(app_handle)?;
+        let summary = tauri::async_runtime::block_on(privacy::wipe_local_data(app_handle))?;
+        cli::print(format, &WipeOutput { wiped: true, summary });
         return Ok(true);
     }
 
@@ -150,35 +369,50 @@ fn main() {
     let today = Local::now().format("%Y-%m-%d").to_string();
     let log_filename = format!("oci-auth-{}", today);
 
-    // Set environment variable to suppress Mesa/OpenGL warnings
-    std::env::set_var("LIBGL_ALWAYS_SOFTWARE", "1");
-
-    // Check if required environment variables are set
-    let required_vars = ["OCI_CLIENT_ID", "OCI_CLIENT_SECRET"];
-    for var in required_vars.iter() {
-        if std::env::var(var).is_err() {
-            eprintln!("Error: Required environment variable {} is not set", var);
-            if cfg!(debug_assertions) {
-                eprintln!("In development mode, make sure these are set in your .env file");
-            } else {
-                eprintln!("In release mode, make sure to set these environment variables in your system");
-            }
+    // Portable mode keeps config, logs and caches in one folder instead of the user profile
+    let portable_home = match paths::init_portable() {
+        Ok(home) => home,
+        Err(e) => {
+            eprintln!("Failed to set up portable mode: {}", e);
             std::process::exit(1);
         }
+    };
+    let log_target = match &portable_home {
+        Some(home) => TargetKind::Folder {
+            path: home.join(paths::LOG_SUBDIR),
+            file_name: Some(log_filename.clone()),
+        },
+        None => TargetKind::LogDir { file_name: Some(log_filename.clone()) },
+    };
+
+    let context = tauri::generate_context!();
+    platform::apply_rendering_workaround(&context.config().identifier);
+
+    // Hidden flag: time the login pipeline against the mock provider and exit
+    if std::env::args().any(|arg| arg == "--bench-auth") {
+        bench::print_report(&bench::run_auth_benchmark(1000));
+        return;
     }
 
+    // Demo mode authenticates against seeded fake users, so no tenant credentials are needed
+    let demo_mode = std::env::args().any(|arg| arg == "--demo");
+    let kiosk_mode = std::env::args().any(|arg| arg == "--kiosk");
+
     let builder = tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_cli::init())
         .plugin(StoreBuilder::default().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(
             LogBuilder::new()
                 .targets([
                     Target::new(TargetKind::Stdout),
-                    Target::new(TargetKind::LogDir { file_name: Some(log_filename.clone()) }),
+                    Target::new(log_target),
                     Target::new(TargetKind::Webview),
+                    Target::new(TargetKind::Dispatch(sinks::dispatch())),
                 ])
-                .level(LevelFilter::Debug)  // Start with Debug level, will be updated in setup
+                .level(LevelFilter::Trace)  // Lets everything through; `logging.level` is applied with log::set_max_level
                 .build(),
         )
         .setup(move |app| {
@@ -194,7 +428,7 @@ fn main() {
                     info!("Starting in UI mode");
                     // Only print these messages in UI mode
                     println!("Log filename: {}", log_filename);
-                    println!("Store plugin config will be created at: {}/config.json", app.path().app_data_dir().unwrap().display());
+                    println!("Store plugin config will be created at: {}/config.json", paths::data_dir(app.handle())?.display());
                 }
                 Err(e) => {
                     error!("CLI command error: {}", e);
@@ -208,95 +442,233 @@ fn main() {
                 AppConfig::default()
             });
 
+            i18n::set_locale(config.ui.locale.as_deref());
+            sinks::register_from_config(&config.logging.sinks);
+
             // Store the config in app state
-            app.manage(ConfigState(Mutex::new(config.clone())));
-
-            // Convert the log level from the config
-            let log_level = match config.logging.level {
-                LogLevel::Trace => LevelFilter::Trace,
-                LogLevel::Debug => LevelFilter::Debug,
-                LogLevel::Info => LevelFilter::Info,
-                LogLevel::Warn => LevelFilter::Warn,
-                LogLevel::Error => LevelFilter::Error,
-                LogLevel::Off => LevelFilter::Off,
-            };
-
-            log::set_max_level(log_level);
-
-            if let Some(window) = app.get_webview_window("main") {
-                let handle_for_menu = app.handle().clone();
-                let quit_item = MenuItem::with_id(&handle_for_menu, MenuId::from("quit"), "Quit", true, None::<&str>)?;
-                let about_item = MenuItem::with_id(&handle_for_menu, MenuId::from("about"), "About", true, None::<&str>)?;
-
-                // Create submenus
-                let file = Submenu::with_items(
-                    &handle_for_menu,
-                    "File",
-                    true,
-                    &[&quit_item]
-                )?;
-
-                let help = Submenu::with_items(
-                    &handle_for_menu,
-                    "Help",
-                    true,
-                    &[&about_item]
-                )?;
-
-                // Create the menu
-                let menu = Menu::with_items(
-                    &handle_for_menu,
-                    &[&file, &help]
-                )?;
-
-                window.set_menu(menu)?;
-
-                // Handle menu events
-                let app_handle_clone = app.handle().clone();
-                window.on_menu_event(move |_window, event| {
-                    debug!("Menu event received: {}", event.id().0);
-                    
-                    match event.id().0.as_str() {
-                        "quit" => {
-                            debug!("Processing quit menu action");
-                            info!("Application exit requested via menu");
-                            app_handle_clone.exit(0);
-                        }
-                        "about" => {
-                            debug!("Processing about menu action");
-                            info!("About dialog opened");
-                            
-                            let window = app_handle_clone.get_webview_window("main").unwrap();
-                            window.dialog()
-                                .message("OCI Auth Tauri\nVersion 1.0.0\n\nA Tauri authentication app for Oracle Cloud Infrastructure.\n\n 2025 OCI Auth Team")
-                                .title("About OCI Auth Tauri")
-                                .buttons(MessageDialogButtons::Ok)
-                                .show(|_| {
-                                    debug!("About dialog shown to user");
-                                });
-                        }
-                        _ => {
-                            debug!("Received unknown menu action: {}", event.id().0);
-                            warn!("Unknown menu item clicked: {}", event.id().0);
-                        }
+            app.manage(ConfigState::new(app.handle(), config.clone()));
+            // Before anything that publishes on it; events wait in the channel until `forward` reads them
+            let (bus, events) = EventBus::new();
+            app.manage(bus.clone());
+            tauri::async_runtime::spawn(forward_events(app.handle().clone(), events));
+            // Before the HTTP client, which sends the device key's thumbprint
+            if let Err(e) = device::init(app.handle()) {
+                warn!("Sign-ins won't be bound to this device: {}", e);
+            }
+            app.manage(AuthClient::from_config(&config.network));
+            #[cfg(debug_assertions)]
+            app.manage(SnapshotState::default());
+            app.manage(DemoState::new(demo_mode));
+            let policy = Policy::load();
+            app.manage(KioskState::new(kiosk_mode || policy.kiosk.is_some()));
+            app.manage(PolicyState(policy));
+            app.manage(MotdState::default());
+            app.manage(MaintenanceState::default());
+            app.manage(CompactState::default());
+            app.manage(ReauthState::default());
+            app.manage(PkceState::default());
+            app.manage(DeviceLoginState::default());
+            app.manage(SessionState::default());
+            app.manage(AuthSessionState::default());
+            app.manage(GuestState::default());
+            app.manage(HeartbeatState::default());
+            app.manage(TokenManager::default());
+            app.manage(RenewalState::default());
+            app.manage(LifecycleState::default());
+            app.manage(SessionGuardState::default());
+            app.manage(ApprovalsState::default());
+            app.manage(ImpersonationState::default());
+            app.manage(DirectoryState::default());
+            app.manage(CacheState::default());
+            app.manage(AnalyticsState::default());
+            app.manage(SupervisorState::default());
+            install_circuit_breaker(&bus);
+            if let Err(e) = kiosk::pin_profile(app.handle()) {
+                warn!("Failed to select the kiosk profile: {}", e);
+            }
+            if let Err(e) = activate_profile(app.handle()) {
+                warn!("Failed to load the active profile: {}", e);
+            }
+            match LocalIpcState::new() {
+                Ok(local_ipc) => {
+                    if let Err(e) = local_ipc.write_key_file(app.handle()) {
+                        warn!("Failed to write the local IPC key: {}", e);
                     }
-                });
+                    app.manage(local_ipc);
+                }
+                Err(e) => warn!("Local IPC is unavailable: {}", e),
+            }
+            if demo_mode {
+                info!("Demo mode enabled: using the mock identity provider");
+            }
+            if kiosk::is_enabled(app.handle()) {
+                info!("Kiosk mode enabled: settings are locked");
+            }
+
+            log::set_max_level(config.logging.level.filter());
+
+            if app.get_webview_window("main").is_some() {
+                prepare_main_window(app.handle())?;
+                create_tray(app.handle())?;
             } else {
                 app.handle().exit(1);
                 return Ok(());
             }
 
+            // Without client credentials the app starts in the "configuration required" state instead of exiting;
+            // broken settings or unwritable folders get a dialog
+            let report = preflight::run(app.handle());
+            if !report.ready {
+                eprintln!("{}", preflight::format_report(&report));
+                warn!("{}", preflight::format_report(&report));
+                preflight::show_setup_dialog(app.handle(), &report);
+            }
+            preflight::sync_configured_state(app.handle());
+
+            // Fetch the admin announcement in the background so startup isn't blocked on the network
+            tauri::async_runtime::spawn(refresh_motd(app.handle().clone()));
+            tauri::async_runtime::spawn(warm_up(app.handle().clone()));
+            tauri::async_runtime::spawn(check_reachability(app.handle().clone()));
+            tauri::async_runtime::spawn(refresh_feature_flags(app.handle().clone()));
+            tauri::async_runtime::spawn(resume_saved_session(app.handle().clone()));
+
+            // The loops that run for as long as the app does are restarted if they panic or stall
+            supervise(app.handle(), "session renewal", renew_sessions);
+            supervise(app.handle(), "session expiry", watch_session_expiry);
+            supervise(app.handle(), "cache trimming", monitor_memory);
+            supervise(app.handle(), "maintenance windows", watch_maintenance);
+            supervise(app.handle(), "other sessions", watch_other_sessions);
+            supervise(app.handle(), "access requests", poll_access_requests);
+            supervise(app.handle(), "token expiry badge", watch_token_expiry);
+            supervise(app.handle(), "accessibility preferences", watch_accessibility_prefs);
+            supervise(app.handle(), "canary", run_canary);
+            supervise(app.handle(), "analytics", flush_analytics);
+            supervise(app.handle(), "data retention", enforce_retention);
+            supervise(app.handle(), "window heartbeat", watch_heartbeat);
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             update_log_level,
             get_log_level,
             get_current_config,
+            update_locale,
+            get_locale,
+            set_theme,
+            get_theme,
             initiate_auth,
-            complete_auth
+            complete_auth,
+            #[cfg(debug_assertions)]
+            debug_snapshot_state,
+            #[cfg(debug_assertions)]
+            debug_restore_state,
+            #[cfg(debug_assertions)]
+            run_scenario,
+            get_login_hints,
+            get_tenant_branding,
+            get_motd,
+            dismiss_motd,
+            get_accessibility_prefs,
+            set_always_on_top,
+            set_compact_mode,
+            is_compact_mode,
+            prompt_reauth,
+            get_reauth_reason,
+            submit_reauth,
+            confirm_reauth,
+            move_to_cursor_monitor,
+            get_profile,
+            export_profile,
+            list_access_snapshots,
+            diff_access,
+            list_access_requests,
+            approve_access_request,
+            reject_access_request,
+            get_admin_capabilities,
+            view_as_user,
+            end_view_as_user,
+            get_view_as_user,
+            search_directory,
+            invalidate_cache,
+            get_cached_profile,
+            get_runtime_stats,
+            list_log_sinks,
+            set_log_sink_enabled,
+            is_feature_enabled,
+            list_feature_flags,
+            set_feature_flag,
+            set_analytics_consent,
+            get_analytics_queue,
+            wipe_all_local_data,
+            get_local_data_inventory,
+            get_token_protection,
+            set_token_protection,
+            get_token_preview,
+            get_access_token,
+            copy_access_token,
+            get_verification_phrase,
+            set_verification_phrase,
+            refresh_menu,
+            set_notification_prefs,
+            get_canary_results,
+            set_preferred_factors,
+            get_active_profile,
+            get_login_start,
+            set_active_profile,
+            set_profile_login_options,
+            terminate_other_sessions,
+            list_my_sessions,
+            terminate_session,
+            get_circuit_status,
+            set_maintenance_windows,
+            get_maintenance_window,
+            set_idcs_urls,
+            get_idcs_endpoint,
+            set_idcs_url,
+            check_ipv6_reachability,
+            list_profiles,
+            add_profile,
+            delete_profile,
+            get_app_state,
+            submit_totp,
+            select_auth_factor,
+            request_otp,
+            submit_otp,
+            get_platform_capabilities,
+            get_launch_at_login,
+            set_launch_at_login,
+            set_screen_capture_blocked,
+            start_push_auth,
+            set_force_software_rendering,
+            start_webauthn,
+            submit_webauthn_assertion,
+            get_preflight_report,
+            save_client_credentials,
+            get_security_questions,
+            submit_security_answers,
+            submit_bypass_code,
+            start_pkce_login,
+            start_factor_enrollment,
+            confirm_factor_enrollment,
+            save_client_certificate_passphrase,
+            refresh_session,
+            get_device_binding_info,
+            logout,
+            get_session_info,
+            start_device_login,
+            get_session_state,
+            reset_session,
+            backup_app_data,
+            restore_app_data,
+            is_kiosk_mode,
+            heartbeat,
+            get_network_settings,
+            set_network_settings,
+            check_tls_settings,
+            export_token
         ]);
 
-    builder.run(tauri::generate_context!())
+    builder.run(context)
         .expect("error while running tauri application");
 }
 
@@ -313,6 +685,19 @@ OPTIONS:
     --log-size <SIZE>          Set maximum log file size in MB (minimum 1)
     --log-count <COUNT>        Set number of log files to keep (minimum 1)
     --clear-config            Reset configuration to default values
+    --wipe                     Revoke tokens and delete all local data
+    --idcs-url <URL>           Set the IDCS tenant URL (https; OCI_IDCS_URL overrides it)
+    --authenticate             Sign in without a window and print the access token
+    -u, --username <USER>      User name for --authenticate and --export-token (prompted for when left out)
+    --password-stdin           Read the password from stdin instead of a hidden prompt
+    -p, --print <WHAT>         What --authenticate prints: token (default) or profile
+    --export-token <PATH>      Sign in without a window and add an OCI CLI session profile to the config file PATH
+    --oci-profile <NAME>       Profile --export-token writes (the active profile's name by default)
+    --region <REGION>          OCI region for --export-token (the config file's DEFAULT region by default)
+    -o, --output <FORMAT>      Print the result as text (default) or json, with errors as {\"error\": ...}
+    --demo                     Start with the mock identity provider and seeded demo users
+    --portable                 Keep config, logs and caches next to the executable (OCI_AUTH_HOME picks the folder)
+    --kiosk                    Lock settings and profiles and show only the sign-in flow, fullscreen
 
 EXAMPLES:
     # Show current configuration
@@ -329,4 +714,29 @@ EXAMPLES:
 
     # Reset configuration to defaults
     oci-auth-tauri --clear-config
+
+    # Remove everything the app stores on this machine
+    oci-auth-tauri --wipe
+
+    # Point the app at your IDCS tenant
+    oci-auth-tauri --idcs-url https://idcs-0123456789abcdef.identity.oraclecloud.com
+
+    # Get an access token in a CI job; a TOTP passcode, if asked for, goes on the next line
+    echo \"$IDCS_PASSWORD\" | oci-auth-tauri --authenticate --username ci.runner --password-stdin
+
+    # Sign in once and use the OCI CLI with the session
+    oci-auth-tauri --export-token ~/.oci/config --oci-profile idcs --region us-ashburn-1
+    oci --profile idcs --auth security_token os ns get
+
+    # Read the configuration from a script
+    oci-auth-tauri --get-config --output json
+
+    # Try the app without an OCI tenant (sign in as demo.user / Welcome1!)
+    oci-auth-tauri --demo
+
+    # Run from a USB stick without touching the user profile
+    oci-auth-tauri --portable
+
+    # Run on a shared terminal
+    oci-auth-tauri --kiosk
 ";
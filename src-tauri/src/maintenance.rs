@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::config::NotificationEvent;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::notifications;
+use crate::profiles::active_profile;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// A known IDCS maintenance period, e.g. from an Oracle service announcement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        at >= self.start && at < self.end
+    }
+}
+
+// The window currently in progress, as last announced to the frontend
+#[derive(Default)]
+pub struct MaintenanceState(pub Mutex<Option<MaintenanceWindow>>);
+
+// Lets the frontend show the maintenance banner when it loads mid-window
+#[command]
+pub fn get_maintenance_window(app_handle: AppHandle) -> Result<Option<MaintenanceWindow>, CommandError> {
+    Ok(current_window(&app_handle)?)
+}
+
+// Background refreshes check this and skip their run rather than retrying against a tenant that is down
+pub fn in_progress(app_handle: &AppHandle) -> bool {
+    current_window(app_handle).ok().flatten().is_some()
+}
+
+// Emits `maintenance-started` and `maintenance-ended` as the active profile's windows open and close
+pub async fn watch_maintenance(app_handle: AppHandle) {
+    loop {
+        let window = current_window(&app_handle).unwrap_or_else(|e| {
+            log::warn!("Failed to read maintenance windows: {}", e);
+            None
+        });
+        let previous = app_handle
+            .state::<MaintenanceState>()
+            .0
+            .lock()
+            .map(|mut state| std::mem::replace(&mut *state, window.clone()))
+            .unwrap_or_default();
+
+        if window != previous {
+            if let Some(window) = &window {
+                log::info!("IDCS maintenance window in progress until {}", window.end);
+                notifications::notify(
+                    &app_handle,
+                    NotificationEvent::Maintenance,
+                    &format!(
+                        "IDCS maintenance until {}. Automatic refreshes are paused.",
+                        window.end.with_timezone(&chrono::Local).format("%H:%M")
+                    ),
+                );
+                crate::bus::emit(&app_handle, "maintenance-started", window);
+            } else if let Some(previous) = &previous {
+                log::info!("IDCS maintenance window ended, resuming automatic refreshes");
+                crate::bus::emit(&app_handle, "maintenance-ended", previous);
+            }
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+fn current_window(app_handle: &AppHandle) -> Result<Option<MaintenanceWindow>, String> {
+    let now = Utc::now();
+    Ok(active_profile(app_handle)?
+        .maintenance_windows
+        .into_iter()
+        .find(|window| window.contains(now)))
+}
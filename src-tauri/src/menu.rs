@@ -0,0 +1,136 @@
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+use crate::auth_client::AuthClient;
+use crate::branding;
+use crate::error::CommandError;
+use crate::lifecycle::{self, AppState};
+use crate::middleware::command;
+use crate::window::save_placement;
+
+// When an item is enabled, evaluated against the app state each time the menu is built
+#[derive(Debug, Clone, Copy)]
+enum When {
+    Always,
+    SignedIn,
+}
+
+enum Entry {
+    Item { id: &'static str, label: &'static str, when: When },
+    Separator,
+}
+
+struct SubmenuSpec {
+    label: &'static str,
+    entries: &'static [Entry],
+}
+
+// The window menu; ids are matched in `handle_menu_event`
+const MENU: &[SubmenuSpec] = &[
+    SubmenuSpec {
+        label: "File",
+        entries: &[
+            Entry::Item { id: "sign_out", label: "Sign out", when: When::SignedIn },
+            Entry::Separator,
+            Entry::Item { id: "quit", label: "Quit", when: When::Always },
+        ],
+    },
+    SubmenuSpec {
+        label: "Help",
+        entries: &[Entry::Item { id: "about", label: "About", when: When::Always }],
+    },
+];
+
+impl When {
+    fn applies(self, signed_in: bool) -> bool {
+        match self {
+            When::Always => true,
+            When::SignedIn => signed_in,
+        }
+    }
+}
+
+// Sets the menu on the main window and routes its events
+pub fn install(app_handle: &AppHandle) -> Result<(), String> {
+    let window = crate::window::main_window(app_handle)?;
+    window
+        .set_menu(build(app_handle).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+
+    let handle = app_handle.clone();
+    window.on_menu_event(move |_window, event| handle_menu_event(&handle, event));
+    Ok(())
+}
+
+// Rebuilds the menu so enabled states follow the app state; called for each `AppStateChanged` on the event bus
+#[command]
+pub fn refresh_menu(app_handle: AppHandle) -> Result<(), CommandError> {
+    let window = crate::window::main_window(&app_handle)?;
+    window
+        .set_menu(build(&app_handle).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn build(app_handle: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let signed_in = matches!(lifecycle::current(app_handle), AppState::LoggedIn | AppState::Expired);
+
+    let menu = Menu::new(app_handle)?;
+    for spec in MENU {
+        let submenu = Submenu::new(app_handle, spec.label, true)?;
+        for entry in spec.entries {
+            match entry {
+                Entry::Item { id, label, when } => submenu.append(&MenuItem::with_id(
+                    app_handle,
+                    *id,
+                    *label,
+                    when.applies(signed_in),
+                    None::<&str>,
+                )?)?,
+                Entry::Separator => submenu.append(&PredefinedMenuItem::separator(app_handle)?)?,
+            }
+        }
+        menu.append(&submenu)?;
+    }
+    Ok(menu)
+}
+
+fn handle_menu_event(app_handle: &AppHandle, event: MenuEvent) {
+    log::debug!("Menu event received: {}", event.id().0);
+
+    match event.id().0.as_str() {
+        "sign_out" => {
+            log::info!("Sign out requested via menu");
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let client = app_handle.state::<AuthClient>();
+                if let Err(e) = crate::auth::end_session(&app_handle, &client).await {
+                    log::warn!("Failed to sign out: {}", e);
+                }
+            });
+        }
+        "quit" => {
+            log::info!("Application exit requested via menu");
+            if let Err(e) = save_placement(app_handle) {
+                log::warn!("Failed to save window placement: {}", e);
+            }
+            app_handle.exit(0);
+        }
+        "about" => {
+            log::info!("About dialog opened");
+            let Ok(window) = crate::window::main_window(app_handle) else {
+                return;
+            };
+            window
+                .dialog()
+                .message(branding::about_text())
+                .title(format!("About {}", branding::APP_NAME))
+                .buttons(MessageDialogButtons::Ok)
+                .show(|_| {
+                    log::debug!("About dialog shown to user");
+                });
+        }
+        id => log::warn!("Unknown menu item clicked: {}", id),
+    }
+}
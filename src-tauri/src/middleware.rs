@@ -0,0 +1,246 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{CommandError, ErrorKind};
+use crate::session::SessionState;
+
+pub use oci_auth_macros::command;
+
+// Calls that take longer are logged at info rather than debug
+const SLOW_COMMAND: Duration = Duration::from_secs(2);
+
+// Limits every command without its own, high enough for any polling the frontend does and low enough to stop a
+// render loop from hammering IDCS or the keychain
+const DEFAULT_LIMIT: RateLimit = RateLimit::per_minute(120);
+// Commands that reach IDCS for a sign-in, send a passcode or hand out the token, where a runaway loop or a
+// compromised webview would do the most harm
+const RATE_LIMITS: &[(&str, RateLimit)] = &[
+    ("initiate_auth", RateLimit::per_minute(5)),
+    ("submit_totp", RateLimit::per_minute(10)),
+    ("submit_otp", RateLimit::per_minute(10)),
+    ("submit_bypass_code", RateLimit::per_minute(5)),
+    ("request_otp", RateLimit::per_minute(5)),
+    ("start_push_auth", RateLimit::per_minute(5)),
+    ("select_auth_factor", RateLimit::per_minute(10)),
+    ("submit_security_answers", RateLimit::per_minute(10)),
+    ("submit_webauthn_assertion", RateLimit::per_minute(10)),
+    ("complete_auth", RateLimit::per_minute(10)),
+    ("start_device_login", RateLimit::per_minute(5)),
+    ("start_pkce_login", RateLimit::per_minute(5)),
+    ("submit_reauth", RateLimit::per_minute(5)),
+    ("confirm_reauth", RateLimit::per_minute(10)),
+    ("get_access_token", RateLimit::per_minute(10)),
+    ("copy_access_token", RateLimit::per_minute(10)),
+    ("export_token", RateLimit::per_minute(5)),
+    ("start_factor_enrollment", RateLimit::per_minute(5)),
+    ("confirm_factor_enrollment", RateLimit::per_minute(10)),
+    ("view_as_user", RateLimit::per_minute(5)),
+    ("terminate_session", RateLimit::per_minute(20)),
+    ("terminate_other_sessions", RateLimit::per_minute(5)),
+    ("search_directory", RateLimit::per_minute(60)),
+    ("save_client_credentials", RateLimit::per_minute(10)),
+    ("export_profile", RateLimit::per_minute(10)),
+    ("backup_app_data", RateLimit::per_minute(5)),
+    ("restore_app_data", RateLimit::per_minute(5)),
+    ("wipe_all_local_data", RateLimit::per_minute(3)),
+];
+
+static STATS: Mutex<BTreeMap<&'static str, CommandStats>> = Mutex::new(BTreeMap::new());
+// When each command was last let through, up to its limit
+static CALLS: Mutex<BTreeMap<&'static str, VecDeque<Instant>>> = Mutex::new(BTreeMap::new());
+
+// At most `calls` in any `per`
+#[derive(Debug, Clone, Copy)]
+struct RateLimit {
+    calls: usize,
+    per: Duration,
+}
+
+impl RateLimit {
+    const fn per_minute(calls: usize) -> Self {
+        Self { calls, per: Duration::from_secs(60) }
+    }
+}
+
+// What a command needs before its body runs; named in `#[command(...)]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gate {
+    // `session`: a signed-in user
+    SignedIn,
+    // `unlocked`: not in kiosk mode
+    Unlocked,
+    // `configured`: client credentials for the active profile
+    Configured,
+}
+
+// Calls of one command since the app started
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandStats {
+    pub name: &'static str,
+    pub calls: u64,
+    pub errors: u64,
+    // Calls refused by the rate limit; also counted in `errors`
+    pub throttled: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    pub last_error: Option<String>,
+}
+
+pub fn stats() -> Vec<CommandStats> {
+    STATS.lock().map(|stats| stats.values().cloned().collect()).unwrap_or_default()
+}
+
+// The checks `#[command]` runs before a body, in the order named
+pub fn check(app_handle: &AppHandle, gates: &[Gate]) -> Result<(), CommandError> {
+    for gate in gates {
+        match gate {
+            Gate::SignedIn => require_session(app_handle)?,
+            Gate::Unlocked => crate::kiosk::require_unlocked(app_handle)?,
+            Gate::Configured => crate::preflight::require_configured(app_handle)?,
+        }
+    }
+    Ok(())
+}
+
+// Runs a synchronous command body once `checked` passed; called by the code `#[command]` generates
+pub fn run<T>(
+    name: &'static str,
+    checked: Result<(), CommandError>,
+    body: impl FnOnce() -> Result<T, CommandError>,
+) -> Result<T, CommandError> {
+    let started = Instant::now();
+    let result = checked.and_then(|()| throttle(name)).and_then(|()| body());
+    finish(name, started, &result);
+    result
+}
+
+// The same for async commands
+pub async fn run_async<T>(
+    name: &'static str,
+    checked: Result<(), CommandError>,
+    body: impl Future<Output = Result<T, CommandError>>,
+) -> Result<T, CommandError> {
+    let started = Instant::now();
+    let result = match checked.and_then(|()| throttle(name)) {
+        Ok(()) => body.await,
+        Err(e) => Err(e),
+    };
+    finish(name, started, &result);
+    result
+}
+
+fn require_session(app_handle: &AppHandle) -> Result<(), CommandError> {
+    let signed_in = app_handle
+        .try_state::<SessionState>()
+        .is_some_and(|session| session.0.lock().is_ok_and(|session| session.is_some()));
+    if !signed_in {
+        return Err(CommandError::new(ErrorKind::NotSignedIn, "Not signed in"));
+    }
+    Ok(())
+}
+
+// Counts the call against the command's rate limit, or refuses it once the limit is reached. Refused calls don't
+// count, so a loop that keeps calling gets through again as soon as the oldest call leaves the window.
+fn throttle(name: &'static str) -> Result<(), CommandError> {
+    let limit = RATE_LIMITS
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map_or(DEFAULT_LIMIT, |(_, limit)| *limit);
+    let Ok(mut calls) = CALLS.lock() else {
+        return Ok(());
+    };
+    admit(calls.entry(name).or_default(), limit, Instant::now())
+        .map_err(|retry_after| CommandError::too_many_requests(name, retry_after))
+}
+
+// Records a call at `now` in `calls`, the times of the calls let through so far, unless `limit` is reached; then
+// returns how long until the oldest call leaves the window
+fn admit(calls: &mut VecDeque<Instant>, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+    while calls.front().is_some_and(|call| now.duration_since(*call) >= limit.per) {
+        calls.pop_front();
+    }
+    if let Some(oldest) = calls.front().filter(|_| calls.len() >= limit.calls) {
+        return Err(limit.per - now.duration_since(*oldest));
+    }
+    calls.push_back(now);
+    Ok(())
+}
+
+// Failures are already logged with their correlation ID when the `CommandError` is made
+fn finish<T>(name: &'static str, started: Instant, result: &Result<T, CommandError>) {
+    let elapsed = started.elapsed();
+    let ms = elapsed.as_millis() as u64;
+    match result {
+        Ok(_) if elapsed >= SLOW_COMMAND => log::info!("Command {} took {} ms", name, ms),
+        Ok(_) => log::debug!("Command {} took {} ms", name, ms),
+        Err(e) => log::debug!("Command {} failed after {} ms [{}]", name, ms, e.correlation_id),
+    }
+
+    let Ok(mut stats) = STATS.lock() else {
+        return;
+    };
+    let stats = stats.entry(name).or_insert_with(|| CommandStats {
+        name,
+        calls: 0,
+        errors: 0,
+        throttled: 0,
+        total_ms: 0,
+        max_ms: 0,
+        last_error: None,
+    });
+    stats.calls += 1;
+    stats.total_ms += ms;
+    stats.max_ms = stats.max_ms.max(ms);
+    if let Err(e) = result {
+        stats.errors += 1;
+        if e.kind == ErrorKind::TooManyRequests {
+            stats.throttled += 1;
+        }
+        stats.last_error = Some(e.message.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn admit_refuses_calls_over_the_limit() {
+        let limit = RateLimit::per_minute(2);
+        let start = Instant::now();
+        let mut calls = VecDeque::new();
+        assert_eq!(admit(&mut calls, limit, start), Ok(()));
+        assert_eq!(admit(&mut calls, limit, start + 10 * SECOND), Ok(()));
+        assert_eq!(admit(&mut calls, limit, start + 15 * SECOND), Err(45 * SECOND));
+        // Refused calls don't count
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[test]
+    fn admit_lets_calls_through_once_the_oldest_leaves_the_window() {
+        let limit = RateLimit::per_minute(2);
+        let start = Instant::now();
+        let mut calls = VecDeque::new();
+        admit(&mut calls, limit, start).unwrap();
+        admit(&mut calls, limit, start + 10 * SECOND).unwrap();
+        assert_eq!(admit(&mut calls, limit, start + 60 * SECOND), Ok(()));
+        assert_eq!(admit(&mut calls, limit, start + 61 * SECOND), Err(9 * SECOND));
+        assert_eq!(admit(&mut calls, limit, start + 70 * SECOND), Ok(()));
+    }
+
+    #[test]
+    fn throttle_uses_the_commands_own_limit() {
+        for _ in 0..3 {
+            assert!(throttle("wipe_all_local_data").is_ok());
+        }
+        let error = throttle("wipe_all_local_data").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::TooManyRequests);
+        assert_eq!(error.retry_after_secs, Some(60));
+    }
+}
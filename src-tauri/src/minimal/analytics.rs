@@ -0,0 +1,32 @@
+// Stand-in used when the `telemetry` feature is disabled; nothing is counted or sent
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::error::{CommandError, ErrorKind};
+use crate::middleware::command;
+
+#[derive(Default)]
+pub struct AnalyticsState {}
+
+pub fn track(_app_handle: &AppHandle, _feature: &'static str) {}
+
+pub fn track_error(_app_handle: &AppHandle, _category: &'static str) {}
+
+pub fn clear(_app_handle: &AppHandle) -> Result<(), String> {
+    Ok(())
+}
+
+#[command]
+pub fn set_analytics_consent(consent: bool) -> Result<(), CommandError> {
+    if consent {
+        return Err(CommandError::new(ErrorKind::NotIncluded, "Usage analytics are not included in this build"));
+    }
+    Ok(())
+}
+
+#[command]
+pub fn get_analytics_queue() -> Result<Value, CommandError> {
+    Ok(json!({ "since": null, "features": {}, "errors": {} }))
+}
+
+pub async fn flush_analytics(_app_handle: AppHandle) {}
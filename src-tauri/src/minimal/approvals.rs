@@ -0,0 +1,30 @@
+// Stand-in used when the `scim-admin` feature is disabled; the commands stay registered
+// so the frontend gets a clear error instead of an unknown command
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::error::{CommandError, ErrorKind};
+use crate::middleware::command;
+
+const NOT_INCLUDED: &str = "Access request approvals are not included in this build";
+
+#[derive(Default)]
+pub struct ApprovalsState(pub Mutex<usize>);
+
+#[command]
+pub async fn list_access_requests() -> Result<Vec<Value>, CommandError> {
+    Err(CommandError::new(ErrorKind::NotIncluded, NOT_INCLUDED))
+}
+
+#[command]
+pub async fn approve_access_request() -> Result<(), CommandError> {
+    Err(CommandError::new(ErrorKind::NotIncluded, NOT_INCLUDED))
+}
+
+#[command]
+pub async fn reject_access_request() -> Result<(), CommandError> {
+    Err(CommandError::new(ErrorKind::NotIncluded, NOT_INCLUDED))
+}
+
+pub async fn poll_access_requests(_app_handle: AppHandle) {}
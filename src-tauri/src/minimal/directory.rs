@@ -0,0 +1,13 @@
+// Stand-in used when the `scim-admin` feature is disabled
+use serde_json::Value;
+
+use crate::error::{CommandError, ErrorKind};
+use crate::middleware::command;
+
+#[derive(Default)]
+pub struct DirectoryState {}
+
+#[command]
+pub async fn search_directory() -> Result<Value, CommandError> {
+    Err(CommandError::new(ErrorKind::NotIncluded, "Directory search is not included in this build"))
+}
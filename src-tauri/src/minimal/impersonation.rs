@@ -0,0 +1,27 @@
+// Stand-in used when the `scim-admin` feature is disabled
+use serde_json::Value;
+
+use crate::error::{CommandError, ErrorKind};
+use crate::middleware::command;
+
+#[derive(Default)]
+pub struct ImpersonationState {}
+
+#[command]
+pub async fn view_as_user() -> Result<Value, CommandError> {
+    Err(CommandError::new(ErrorKind::NotIncluded, "Viewing as another user is not included in this build"))
+}
+
+#[command]
+pub fn end_view_as_user() -> Result<(), CommandError> {
+    Ok(())
+}
+
+pub(crate) fn restore_original(_app_handle: &tauri::AppHandle) -> Result<(), String> {
+    Ok(())
+}
+
+#[command]
+pub fn get_view_as_user() -> Result<Option<Value>, CommandError> {
+    Ok(None)
+}
@@ -0,0 +1,16 @@
+// Stand-in used when the `tray` feature is disabled; the window is the only way in
+use tauri::AppHandle;
+
+pub const TRAY_ID: &str = "main-tray";
+
+pub fn create_tray(_app_handle: &AppHandle) -> tauri::Result<()> {
+    Ok(())
+}
+
+pub fn set_badge(_app_handle: &AppHandle, _count: usize) {}
+
+pub fn set_window_unresponsive(_app_handle: &AppHandle, _unresponsive: bool) {}
+
+pub fn sync_menu(_app_handle: &AppHandle) {}
+
+pub async fn watch_token_expiry(_app_handle: AppHandle) {}
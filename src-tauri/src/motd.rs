@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreBuilder;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::policy::{MotdPolicy, PolicyState};
+
+const MOTD_CACHE_FILE: &str = "motd-cache.json";
+const MOTD_STORE_FILE: &str = "motd.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MotdSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Motd {
+    pub id: String,
+    pub title: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub severity: MotdSeverity,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct MotdState(pub Mutex<Option<Motd>>);
+
+// Returns the current announcement unless it has expired or the user dismissed it
+#[command]
+pub fn get_motd(app_handle: AppHandle, motd_state: State<MotdState>) -> Result<Option<Motd>, CommandError> {
+    let motd = motd_state.0.lock().map_err(|e| e.to_string())?.clone();
+    let Some(motd) = motd else {
+        return Ok(None);
+    };
+
+    if motd.expires_at.map(|expires_at| expires_at <= Utc::now()).unwrap_or(false) {
+        return Ok(None);
+    }
+    if dismissed_ids(&app_handle)?.contains(&motd.id) {
+        return Ok(None);
+    }
+
+    Ok(Some(motd))
+}
+
+#[command]
+pub fn dismiss_motd(app_handle: AppHandle, id: String) -> Result<(), CommandError> {
+    let store = StoreBuilder::new(&app_handle, motd_store_path(&app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut dismissed = dismissed_ids(&app_handle)?;
+    if !dismissed.contains(&id) {
+        dismissed.push(id);
+    }
+    store.set("dismissed", serde_json::json!(dismissed));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Fetches the configured announcement, falling back to the last cached copy when offline
+pub async fn refresh_motd(app_handle: AppHandle) {
+    let policy = match app_handle.state::<PolicyState>().0.motd.clone() {
+        Some(policy) => policy,
+        None => return,
+    };
+
+    let motd = match fetch_motd(&policy).await {
+        Ok(Some(motd)) => {
+            if let Ok(path) = motd_cache_path(&app_handle) {
+                if let Ok(contents) = serde_json::to_string(&motd) {
+                    let _ = std::fs::write(path, contents);
+                }
+            }
+            Some(motd)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!("Failed to fetch message of the day: {}", e);
+            motd_cache_path(&app_handle)
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok())
+                .and_then(|contents| serde_json::from_str(&contents).ok())
+        }
+    };
+
+    if let Ok(mut state) = app_handle.state::<MotdState>().0.lock() {
+        *state = motd;
+    }
+}
+
+async fn fetch_motd(policy: &MotdPolicy) -> Result<Option<Motd>, String> {
+    let Some(url) = &policy.url else {
+        return Ok(policy.message.as_deref().map(|message| static_motd(message, None)));
+    };
+
+    let response = reqwest::get(url).await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to get {}: {}", url, response.status()));
+    }
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    if body.trim().is_empty() {
+        return Ok(None);
+    }
+
+    // JSON announcements carry their own metadata; anything else is shown as plain text
+    match serde_json::from_str::<Value>(&body) {
+        Ok(value) if value.is_object() => {
+            let message = value["message"].as_str().ok_or("Announcement has no message")?;
+            let mut motd = static_motd(message, value["title"].as_str());
+            if let Some(id) = value["id"].as_str() {
+                motd.id = id.to_string();
+            }
+            motd.severity = serde_json::from_value(value["severity"].clone()).unwrap_or_default();
+            motd.expires_at = value["expires_at"].as_str().and_then(|s| s.parse().ok());
+            Ok(Some(motd))
+        }
+        _ => Ok(Some(static_motd(body.trim(), None))),
+    }
+}
+
+// Content-derived id, so a changed announcement shows again after an earlier one was dismissed
+fn static_motd(message: &str, title: Option<&str>) -> Motd {
+    let mut hasher = DefaultHasher::new();
+    message.hash(&mut hasher);
+    title.hash(&mut hasher);
+
+    Motd {
+        id: format!("{:016x}", hasher.finish()),
+        title: title.map(|t| t.to_string()),
+        message: message.to_string(),
+        severity: MotdSeverity::Info,
+        expires_at: None,
+    }
+}
+
+fn dismissed_ids(app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let store = StoreBuilder::new(app_handle, motd_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(store
+        .get("dismissed")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+fn motd_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(MOTD_STORE_FILE))
+}
+
+fn motd_cache_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::cache_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(MOTD_CACHE_FILE))
+}
@@ -0,0 +1,248 @@
+use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth_client::AuthClient;
+use crate::circuit;
+use crate::config::{ConfigState, NotificationEvent};
+use crate::error::CommandError;
+use crate::i18n;
+use crate::maintenance;
+use crate::middleware::command;
+use crate::notifications;
+use crate::renewal;
+use crate::session::SessionState;
+use crate::tenant::{get_resource, ScimError};
+
+const MY_SESSIONS_PATH: &str = "/admin/v1/MySessions";
+const POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+// One of the signed-in user's IDCS sessions, from this app or any browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdcsSession {
+    pub id: String,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created: Option<String>,
+    pub last_accessed: Option<String>,
+    // This app's own session, as far as the guard can tell
+    #[serde(default)]
+    pub current: bool,
+}
+
+// What the guard has seen since this login; anything else showing up later is a new session elsewhere
+#[derive(Default)]
+pub struct GuardState {
+    // Newest session at login, taken to be this app's own
+    own_session: Option<String>,
+    known_sessions: HashSet<String>,
+    known_ips: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct SessionGuardState(pub Mutex<Option<GuardState>>);
+
+// Every IDCS session of the signed-in user, e.g. to find stale browser sessions
+#[command(session)]
+pub async fn list_my_sessions(
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    guard: State<'_, SessionGuardState>,
+) -> Result<Vec<IdcsSession>, CommandError> {
+    let own_session = own_session(&guard)?;
+    let mut sessions = fetch_my_sessions(&auth_client, &session.bearer_token()?).await?;
+    for session in &mut sessions {
+        session.current = Some(&session.id) == own_session.as_ref();
+    }
+    Ok(sessions)
+}
+
+#[command(session)]
+pub async fn terminate_session(
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    id: String,
+) -> Result<(), CommandError> {
+    delete_my_session(&auth_client, &session.bearer_token()?, &id).await?;
+    log::warn!("Terminated IDCS session {}", id);
+    Ok(())
+}
+
+// Signs out every other IDCS session of the current user, returning how many were ended
+#[command(session)]
+pub async fn terminate_other_sessions(
+    auth_client: State<'_, AuthClient>,
+    session: State<'_, SessionState>,
+    guard: State<'_, SessionGuardState>,
+) -> Result<usize, CommandError> {
+    let bearer_token = session.bearer_token()?;
+    let own_session = own_session(&guard)?;
+
+    let mut terminated = 0;
+    for other in fetch_my_sessions(&auth_client, &bearer_token)
+        .await?
+        .into_iter()
+        .filter(|other| Some(&other.id) != own_session.as_ref())
+    {
+        delete_my_session(&auth_client, &bearer_token, &other.id).await?;
+        terminated += 1;
+    }
+    log::warn!("Terminated {} other IDCS sessions", terminated);
+    Ok(terminated)
+}
+
+// Warns when a session from an unfamiliar IP appears for the signed-in user
+pub async fn watch_other_sessions(app_handle: AppHandle) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let enabled = app_handle
+            .state::<ConfigState>()
+            .0
+            .read(|config| config.security.watch_other_sessions);
+        let guard = app_handle.state::<SessionGuardState>();
+        let Ok(bearer_token) = app_handle.state::<SessionState>().bearer_token() else {
+            if let Ok(mut state) = guard.0.lock() {
+                *state = None;
+            }
+            continue;
+        };
+        if !enabled || maintenance::in_progress(&app_handle) {
+            continue;
+        }
+
+        let client = app_handle.state::<AuthClient>();
+        let mut sessions = fetch_my_sessions(&client, &bearer_token).await;
+        if let Err(e) = &sessions {
+            if renewal::renew_after_unauthorized(&app_handle, e).await {
+                if let Ok(bearer_token) = app_handle.state::<SessionState>().bearer_token() {
+                    sessions = fetch_my_sessions(&client, &bearer_token).await;
+                }
+            }
+        }
+        let sessions = match sessions {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                log::warn!("Failed to list IDCS sessions: {}", e);
+                continue;
+            }
+        };
+        let unfamiliar = match guard.0.lock() {
+            Ok(mut state) => observe(&mut state, sessions),
+            Err(_) => continue,
+        };
+
+        for other in unfamiliar {
+            let from = other.client_ip.as_deref().unwrap_or("an unknown address");
+            log::warn!("New IDCS session {} from {}", other.id, from);
+            notifications::notify(
+                &app_handle,
+                NotificationEvent::OtherSession,
+                &format!("Your account was just signed in from {}. If this wasn't you, end your other sessions.", from),
+            );
+            crate::bus::emit(&app_handle, "other-session-detected", &other);
+        }
+    }
+}
+
+// Ends this app's own IDCS session at sign-out: the one the guard took as its own, else the newest, as the guard
+// would have picked. Returns its id, or None when the user has no session.
+pub(crate) async fn end_own_session(
+    client: &AuthClient,
+    guard: &SessionGuardState,
+    bearer_token: &str,
+) -> Result<Option<String>, String> {
+    let id = match own_session(guard)? {
+        Some(id) => Some(id),
+        None => fetch_my_sessions(client, bearer_token)
+            .await?
+            .into_iter()
+            .max_by(|a, b| a.created.cmp(&b.created))
+            .map(|session| session.id),
+    };
+    if let Some(id) = &id {
+        delete_my_session(client, bearer_token, id).await?;
+    }
+    Ok(id)
+}
+
+fn own_session(guard: &SessionGuardState) -> Result<Option<String>, String> {
+    Ok(guard
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|state| state.own_session.clone()))
+}
+
+// Takes a baseline on the first poll after login, then returns sessions that are new and from an unseen IP
+fn observe(state: &mut Option<GuardState>, sessions: Vec<IdcsSession>) -> Vec<IdcsSession> {
+    let Some(guard) = state else {
+        *state = Some(GuardState {
+            own_session: sessions
+                .iter()
+                .max_by(|a, b| a.created.cmp(&b.created))
+                .map(|session| session.id.clone()),
+            known_sessions: sessions.iter().map(|session| session.id.clone()).collect(),
+            known_ips: sessions.iter().filter_map(|session| session.client_ip.clone()).collect(),
+        });
+        return Vec::new();
+    };
+
+    let mut unfamiliar = Vec::new();
+    for session in sessions {
+        if !guard.known_sessions.insert(session.id.clone()) {
+            continue;
+        }
+        let new_ip = match &session.client_ip {
+            Some(ip) => guard.known_ips.insert(ip.clone()),
+            None => true,
+        };
+        if new_ip {
+            unfamiliar.push(session);
+        }
+    }
+    unfamiliar
+}
+
+pub(crate) async fn fetch_my_sessions(client: &AuthClient, bearer_token: &str) -> Result<Vec<IdcsSession>, ScimError> {
+    let response = get_resource(client, bearer_token, MY_SESSIONS_PATH).await?;
+    Ok(response["Resources"]
+        .as_array()
+        .map(|resources| resources.iter().filter_map(parse_session).collect())
+        .unwrap_or_default())
+}
+
+pub(crate) async fn delete_my_session(client: &AuthClient, bearer_token: &str, id: &str) -> Result<(), String> {
+    let response = circuit::send(
+        client
+            .http()
+            .delete(client.url(&format!("{}/{}", MY_SESSIONS_PATH, id)))
+            .header(ACCEPT_LANGUAGE, i18n::accept_language())
+            .header(AUTHORIZATION, HeaderValue::from_str(bearer_token).map_err(|e| e.to_string())?),
+    )
+    .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let response_text = response.text().await.map_err(|e| e.to_string())?;
+        return Err(format!("Failed to end session {}: {} {}", id, status, response_text));
+    }
+    Ok(())
+}
+
+fn parse_session(resource: &Value) -> Option<IdcsSession> {
+    let text = |value: &Value| value.as_str().map(|s| s.to_string());
+    Some(IdcsSession {
+        id: resource["id"].as_str()?.to_string(),
+        client_ip: text(&resource["clientIp"]).or_else(|| text(&resource["ipAddress"])),
+        user_agent: text(&resource["userAgent"]),
+        created: text(&resource["meta"]["created"]),
+        last_accessed: text(&resource["lastAccessedTime"]).or_else(|| text(&resource["meta"]["lastModified"])),
+        current: false,
+    })
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::auth_client::AuthClient;
+use crate::config::{ConfigState, NetworkConfig, TlsVersion};
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::platform;
+
+// Keychain account of the proxy's basic auth password
+pub(crate) const PROXY_PASSWORD_ACCOUNT: &str = "network/proxy-password";
+// Read when `network.proxy` is unset; IDCS is only reached over HTTPS
+const PROXY_ENV_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkSettings {
+    pub proxy: Option<String>,
+    pub proxy_username: Option<String>,
+    // The password itself never goes back to the webview
+    pub has_proxy_password: bool,
+    pub no_proxy: Vec<String>,
+    // Proxy requests go through: `proxy`, or else `HTTPS_PROXY`, with any credentials in the URL masked
+    pub effective_proxy: Option<String>,
+}
+
+impl NetworkSettings {
+    fn of(network: &NetworkConfig) -> Self {
+        NetworkSettings {
+            proxy: network.proxy.clone(),
+            proxy_username: network.proxy_username.clone(),
+            has_proxy_password: matches!(platform::keychain_get(PROXY_PASSWORD_ACCOUNT), Ok(Some(_))),
+            no_proxy: network.no_proxy.clone(),
+            effective_proxy: proxy_url(network).map(|url| without_credentials(&url)),
+        }
+    }
+}
+
+#[command]
+pub fn get_network_settings(config_state: State<ConfigState>) -> Result<NetworkSettings, CommandError> {
+    let config = config_state.0.lock();
+    Ok(NetworkSettings::of(&config.network))
+}
+
+// Sets the proxy for IDCS requests and applies it right away. `proxy_password` is saved to the keychain: left
+// out it stays as it is, and an empty one removes it. Clearing the user name also removes the password.
+#[command(unlocked)]
+pub fn set_network_settings(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    client: State<AuthClient>,
+    proxy: Option<String>,
+    proxy_username: Option<String>,
+    proxy_password: Option<String>,
+    no_proxy: Option<Vec<String>>,
+) -> Result<NetworkSettings, CommandError> {
+    let proxy = proxy.map(|proxy| proxy.trim().to_string()).filter(|proxy| !proxy.is_empty());
+    let proxy_username = proxy_username
+        .map(|username| username.trim().to_string())
+        .filter(|username| !username.is_empty());
+    let no_proxy: Vec<String> = no_proxy
+        .unwrap_or_default()
+        .iter()
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .collect();
+    // Before anything is saved, so a typo doesn't leave sign-in without a working client
+    if let Some(proxy) = &proxy {
+        reqwest::Proxy::all(proxy).map_err(|e| format!("Invalid proxy {}: {}", without_credentials(proxy), e))?;
+    }
+
+    match (&proxy_username, proxy_password.as_deref()) {
+        (None, _) | (Some(_), Some("")) => platform::keychain_delete(PROXY_PASSWORD_ACCOUNT)?,
+        (Some(_), Some(password)) => platform::keychain_set(PROXY_PASSWORD_ACCOUNT, password)?,
+        (Some(_), None) => {}
+    }
+    let mut config = config_state.0.lock();
+    config.set_proxy(&app_handle, proxy, proxy_username, no_proxy)?;
+    let network = config.network.clone();
+    drop(config);
+    client.set_network(&network)?;
+
+    let settings = NetworkSettings::of(&network);
+    log::info!(
+        "Proxy set to {}",
+        settings.effective_proxy.as_deref().unwrap_or("the system default")
+    );
+    Ok(settings)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsCheck {
+    // The HTTP client can be built with these settings; when it can't, it uses the defaults instead
+    pub ok: bool,
+    // Certificates loaded from `network.ca_certificate`
+    pub ca_certificates: usize,
+    pub use_system_roots: bool,
+    pub min_tls_version: Option<TlsVersion>,
+    pub errors: Vec<String>,
+}
+
+// Checks `network.ca_certificate`, `use_system_roots` and `min_tls_version`, so a mistake in them shows up here
+// rather than as sign-ins failing with a certificate error
+#[command]
+pub fn check_tls_settings(config_state: State<ConfigState>) -> Result<TlsCheck, CommandError> {
+    let network = config_state.0.lock().network.clone();
+    Ok(check_tls(&network))
+}
+
+pub fn check_tls(network: &NetworkConfig) -> TlsCheck {
+    let mut errors = Vec::new();
+    let ca_certificates = match root_certificates(network) {
+        Ok(certificates) => certificates.len(),
+        Err(e) => {
+            errors.push(e);
+            0
+        }
+    };
+    if !network.use_system_roots && network.ca_certificate.is_none() {
+        errors.push(
+            "System roots are turned off but no CA certificate is set, so no server would be trusted".to_string(),
+        );
+    }
+    // Catches what only the TLS library knows, such as a minimum version it can't enforce
+    if errors.is_empty() {
+        if let Err(e) = tls(reqwest::Client::builder(), network)
+            .and_then(|builder| builder.build().map_err(|e| format!("The TLS settings can't be used: {}", e)))
+        {
+            errors.push(e);
+        }
+    }
+
+    TlsCheck {
+        ok: errors.is_empty(),
+        ca_certificates,
+        use_system_roots: network.use_system_roots,
+        min_tls_version: network.min_tls_version,
+        errors,
+    }
+}
+
+// Adds the extra roots and the minimum version to an HTTP client being built
+pub(crate) fn tls(
+    mut builder: reqwest::ClientBuilder,
+    network: &NetworkConfig,
+) -> Result<reqwest::ClientBuilder, String> {
+    for certificate in root_certificates(network)? {
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder = builder.tls_built_in_root_certs(network.use_system_roots);
+    if let Some(version) = network.min_tls_version {
+        builder = builder.min_tls_version(match version {
+            TlsVersion::Tls10 => reqwest::tls::Version::TLS_1_0,
+            TlsVersion::Tls11 => reqwest::tls::Version::TLS_1_1,
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::Tls13 => reqwest::tls::Version::TLS_1_3,
+        });
+    }
+    Ok(builder)
+}
+
+// Every certificate in `network.ca_certificate`; a bundle may hold a whole chain
+fn root_certificates(network: &NetworkConfig) -> Result<Vec<reqwest::Certificate>, String> {
+    let Some(file) = &network.ca_certificate else {
+        return Ok(Vec::new());
+    };
+    let pem = std::fs::read(file).map_err(|e| format!("Failed to read CA certificate {}: {}", file, e))?;
+    let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+        .map_err(|e| format!("Invalid CA certificate {}: {}", file, e))?;
+    if certificates.is_empty() {
+        return Err(format!("No PEM certificates found in {}", file));
+    }
+    Ok(certificates)
+}
+
+// The proxy for IDCS requests with the saved credentials and no-proxy list, or None to leave it to reqwest, which
+// then uses the system's proxy settings
+pub(crate) fn proxy(network: &NetworkConfig) -> Result<Option<reqwest::Proxy>, String> {
+    let Some(url) = proxy_url(network) else {
+        return Ok(None);
+    };
+    let mut proxy =
+        reqwest::Proxy::all(&url).map_err(|e| format!("Invalid proxy {}: {}", without_credentials(&url), e))?;
+    if let Some(username) = &network.proxy_username {
+        let password = platform::keychain_get(PROXY_PASSWORD_ACCOUNT)?.unwrap_or_default();
+        proxy = proxy.basic_auth(username, &password);
+    }
+    let no_proxy = if !network.no_proxy.is_empty() {
+        reqwest::NoProxy::from_string(&network.no_proxy.join(","))
+    } else if network.proxy.is_none() {
+        // Goes with the proxy taken from the environment
+        reqwest::NoProxy::from_env()
+    } else {
+        None
+    };
+    Ok(Some(proxy.no_proxy(no_proxy)))
+}
+
+// Read here rather than left to reqwest, so the saved credentials and no-proxy list also apply to it
+fn proxy_url(network: &NetworkConfig) -> Option<String> {
+    network.proxy.clone().or_else(|| {
+        PROXY_ENV_VARS
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|url| !url.trim().is_empty())
+    })
+}
+
+// For the log and the settings screen; a proxy URL may carry a user and password
+fn without_credentials(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.to_string()
+        }
+        Err(_) => crate::redact::REDACTED.to_string(),
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::Local;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::branding;
+use crate::config::{ConfigState, NotificationEvent, NotificationsConfig};
+use crate::error::CommandError;
+use crate::middleware::command;
+
+#[cfg(target_os = "windows")]
+const SOUND: &str = "Default";
+#[cfg(target_os = "macos")]
+const SOUND: &str = "Ping";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const SOUND: &str = "message-new-instant";
+
+#[command(unlocked)]
+pub fn set_notification_prefs(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    prefs: NotificationsConfig,
+) -> Result<(), CommandError> {
+    let mut config = config_state.0.lock();
+    config
+        .set_notification_prefs(&app_handle, prefs)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Shows an OS notification for `event` unless the user turned it off or it is do-not-disturb time
+pub fn notify(app_handle: &AppHandle, event: NotificationEvent, body: &str) {
+    let prefs = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.notifications.clone());
+
+    let enabled = prefs
+        .events
+        .get(&event)
+        .copied()
+        .unwrap_or_else(|| event.enabled_by_default());
+    if !enabled {
+        return;
+    }
+    if let Some(quiet) = &prefs.do_not_disturb {
+        if quiet.contains(Local::now().time()) {
+            log::debug!("Notification for {:?} held back by do-not-disturb", event);
+            return;
+        }
+    }
+
+    let mut notification = app_handle.notification().builder().title(branding::APP_NAME).body(body);
+    if prefs.sound {
+        notification = notification.sound(SOUND);
+    }
+    if let Err(e) = notification.show() {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
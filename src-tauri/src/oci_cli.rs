@@ -0,0 +1,247 @@
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use chrono::{DateTime, Utc};
+use md5::{Digest, Md5};
+use rand_core::OsRng;
+use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::RsaPrivateKey;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::exchange_for_upst;
+use crate::auth_client::AuthClient;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::profiles;
+use crate::session::SessionState;
+
+// What `oci session authenticate` generates
+const KEY_BITS: usize = 2048;
+// Under `sessions/<profile>` next to the config file, where the OCI CLI keeps its own session profiles
+const TOKEN_FILE: &str = "token";
+const KEY_FILE: &str = "oci_api_key.pem";
+const PUBLIC_KEY_FILE: &str = "oci_api_key_public.pem";
+
+// A session profile written to an OCI CLI config file, for `--profile <profile> --auth security_token`
+#[derive(Debug, Clone, Serialize)]
+pub struct OciSessionProfile {
+    pub profile: String,
+    pub config_file: String,
+    pub token_file: String,
+    pub key_file: String,
+    pub fingerprint: String,
+    pub tenancy: String,
+    pub region: String,
+    // When the session token stops working
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// Hands the signed-in session to the OCI CLI and SDKs: exchanges the access token for an OCI session token and
+// writes it, with the key it is bound to, as a session profile. `path` is the config file, `~/.oci/config` (or
+// OCI_CLI_CONFIG_FILE) when left out; the profile is named after the active app profile unless `profile` is
+// given, and `region` defaults to the one the profile or DEFAULT already has.
+#[command(session, unlocked)]
+pub async fn export_token(
+    app_handle: AppHandle,
+    session: State<'_, SessionState>,
+    path: Option<String>,
+    profile: Option<String>,
+    region: Option<String>,
+) -> Result<OciSessionProfile, CommandError> {
+    let access_token = session
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .and_then(|session| session.access_token.clone())
+        .ok_or("Not signed in to IDCS")?;
+    Ok(export(&app_handle, &access_token, path, profile, region).await?)
+}
+
+pub async fn export(
+    app_handle: &AppHandle,
+    access_token: &str,
+    path: Option<String>,
+    profile: Option<String>,
+    region: Option<String>,
+) -> Result<OciSessionProfile, String> {
+    let config_file = match path {
+        Some(path) => expand_home(&path)?,
+        None => default_config_file()?,
+    };
+    let profile = match profile {
+        Some(profile) => profile,
+        None => profiles::active_profile(app_handle)?.name,
+    };
+    if profile.is_empty() || profile.contains(['[', ']', '\n', '\r', '/', '\\']) {
+        return Err(format!("{} can't be used as an OCI CLI profile name", profile));
+    }
+    let existing = match std::fs::read_to_string(&config_file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", config_file.display(), e)),
+    };
+    let region = region
+        .or_else(|| section_value(&existing, &profile, "region"))
+        .or_else(|| section_value(&existing, "DEFAULT", "region"))
+        .ok_or("Pick the OCI region the profile is for, such as us-ashburn-1")?;
+
+    let key = tauri::async_runtime::spawn_blocking(|| RsaPrivateKey::new(&mut OsRng, KEY_BITS))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Failed to generate the API key: {}", e))?;
+    let public_der = key.to_public_key().to_public_key_der().map_err(|e| e.to_string())?;
+    let client = app_handle.state::<AuthClient>();
+    let token = exchange_for_upst(&client, access_token, &STANDARD.encode(public_der.as_bytes())).await?;
+    let claims = claims(&token)?;
+    let tenancy = claims["tenant"]
+        .as_str()
+        .ok_or("The OCI session token names no tenancy")?
+        .to_string();
+
+    let dir = config_file
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("sessions")
+        .join(&profile);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let key_file = dir.join(KEY_FILE);
+    let token_file = dir.join(TOKEN_FILE);
+    let private_pem = key.to_pkcs8_pem(LineEnding::LF).map_err(|e| e.to_string())?;
+    let public_pem = public_der
+        .to_pem("PUBLIC KEY", LineEnding::LF)
+        .map_err(|e| e.to_string())?;
+    write_private(&key_file, private_pem.as_bytes())?;
+    write_private(&dir.join(PUBLIC_KEY_FILE), public_pem.as_bytes())?;
+    write_private(&token_file, token.as_bytes())?;
+
+    let exported = OciSessionProfile {
+        profile,
+        config_file: config_file.display().to_string(),
+        token_file: token_file.display().to_string(),
+        key_file: key_file.display().to_string(),
+        fingerprint: fingerprint(public_der.as_bytes()),
+        tenancy,
+        region,
+        expires_at: claims["exp"].as_i64().and_then(|exp| DateTime::from_timestamp(exp, 0)),
+    };
+    let section = [
+        ("fingerprint", exported.fingerprint.as_str()),
+        ("key_file", exported.key_file.as_str()),
+        ("tenancy", exported.tenancy.as_str()),
+        ("region", exported.region.as_str()),
+        ("security_token_file", exported.token_file.as_str()),
+    ];
+    write_private(&config_file, set_section(&existing, &exported.profile, &section).as_bytes())?;
+    log::info!(
+        "Exported an OCI session token to profile {} in {}",
+        exported.profile,
+        exported.config_file
+    );
+    Ok(exported)
+}
+
+// Where the OCI CLI looks when it isn't given `--config-file`
+fn default_config_file() -> Result<PathBuf, String> {
+    match std::env::var("OCI_CLI_CONFIG_FILE") {
+        Ok(path) if !path.is_empty() => expand_home(&path),
+        _ => Ok(home_dir()?.join(".oci").join("config")),
+    }
+}
+
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => Ok(home_dir()?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    dirs::home_dir().ok_or_else(|| "No home folder to put the OCI CLI config in".to_string())
+}
+
+// The JWT's claims; the token is IDCS's to verify, so only its payload is read
+fn claims(token: &str) -> Result<Value, String> {
+    let payload = token.split('.').nth(1).ok_or("The OCI session token is not a JWT")?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| format!("Invalid OCI session token: {}", e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid OCI session token: {}", e))
+}
+
+// MD5 of the DER public key as colon-separated hex, which OCI shows as the API key's fingerprint
+fn fingerprint(public_der: &[u8]) -> String {
+    Md5::digest(public_der)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn is_header(line: &str, name: &str) -> bool {
+    line.trim().strip_prefix('[').and_then(|line| line.strip_suffix(']')) == Some(name)
+}
+
+fn section_value(contents: &str, name: &str, key: &str) -> Option<String> {
+    contents
+        .lines()
+        .skip_while(|line| !is_header(line, name))
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+// Replaces the profile's section, or adds it at the end, leaving every other section as it was
+fn set_section(contents: &str, name: &str, values: &[(&str, &str)]) -> String {
+    let mut section = vec![format!("[{}]", name)];
+    section.extend(values.iter().map(|(key, value)| format!("{}={}", key, value)));
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_section = false;
+    let mut replaced = false;
+    for line in contents.lines() {
+        if line.trim_start().starts_with('[') {
+            in_section = is_header(line, name);
+            if in_section && !replaced {
+                lines.append(&mut section);
+                lines.push(String::new());
+                replaced = true;
+            }
+        }
+        if !in_section {
+            lines.push(line.to_string());
+        }
+    }
+    if !replaced {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.append(&mut section);
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n") + "\n"
+}
+
+// The OCI CLI warns about key and config files other users can read
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    std::io::Write::write_all(&mut file, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+pub const HOME_VAR: &str = "OCI_AUTH_HOME";
+// Portable home subfolders that are needed before the app exists, for the log plugin and the rendering flag
+pub const DATA_SUBDIR: &str = "data";
+pub const LOG_SUBDIR: &str = "logs";
+
+// Where everything is stored in portable mode; unset means the OS locations for the user
+static PORTABLE_HOME: OnceLock<PathBuf> = OnceLock::new();
+
+// Picks the portable home from `OCI_AUTH_HOME`, else `--portable` (next to the executable).
+// Must run before the log plugin is built, since the log folder depends on it.
+pub fn init_portable() -> Result<Option<PathBuf>, String> {
+    let home = match std::env::var_os(HOME_VAR).filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None if std::env::args().any(|arg| arg == "--portable") => std::env::current_exe()
+            .map_err(|e| e.to_string())?
+            .parent()
+            .ok_or("The executable has no parent directory")?
+            .to_path_buf(),
+        None => return Ok(None),
+    };
+    let home = std::path::absolute(&home).map_err(|e| format!("Invalid {}: {}", home.display(), e))?;
+    std::fs::create_dir_all(&home).map_err(|e| format!("Failed to create {}: {}", home.display(), e))?;
+    let _ = PORTABLE_HOME.set(home.clone());
+    Ok(Some(home))
+}
+
+pub fn portable_home() -> Option<&'static Path> {
+    PORTABLE_HOME.get().map(|home| home.as_path())
+}
+
+// Settings, stores and history
+pub fn data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    resolve(DATA_SUBDIR, app_handle.path().app_data_dir())
+}
+
+// Machine-specific state that shouldn't roam
+pub fn local_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    resolve("local", app_handle.path().app_local_data_dir())
+}
+
+pub fn config_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    resolve(DATA_SUBDIR, app_handle.path().app_config_dir())
+}
+
+pub fn cache_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    resolve("cache", app_handle.path().app_cache_dir())
+}
+
+pub fn log_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    resolve(LOG_SUBDIR, app_handle.path().app_log_dir())
+}
+
+fn resolve(subdir: &str, default: tauri::Result<PathBuf>) -> Result<PathBuf, String> {
+    match PORTABLE_HOME.get() {
+        Some(home) => Ok(home.join(subdir)),
+        None => default.map_err(|e| e.to_string()),
+    }
+}
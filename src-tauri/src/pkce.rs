@@ -0,0 +1,250 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::header::ACCEPT_LANGUAGE;
+use reqwest::Url;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tokio::sync::oneshot;
+
+use crate::auth::{self, TokenResponse};
+use crate::auth_client::AuthClient;
+use crate::circuit;
+use crate::demo::DemoState;
+use crate::dpop::{self, DpopKey};
+use crate::endpoints::base_url;
+use crate::error::{AuthError, CommandError};
+use crate::i18n;
+use crate::lifecycle::{self, AppState};
+use crate::middleware::command;
+use crate::policy::{self, PolicyState};
+use crate::profiles;
+use crate::session::{ScopeGrant, Session};
+
+pub const LOGIN_WINDOW: &str = "pkce-login";
+
+// Resolved with the redirect URL IDCS sent the login window to, or None when the window was closed
+#[derive(Default)]
+pub struct PkceState(pub Mutex<Option<oneshot::Sender<Option<Url>>>>);
+
+// Signs in with a public client: the IDCS login page opens in its own window and the authorization code it
+// redirects with is exchanged using the PKCE verifier, so no client secret is involved. Returns the profile.
+#[command(configured)]
+pub async fn start_pkce_login(app_handle: AppHandle, pkce: State<'_, PkceState>) -> Result<Value, CommandError> {
+    if app_handle.state::<DemoState>().enabled {
+        return Err("Demo mode signs in with a username and password".into());
+    }
+    policy::check_allowed_hours(&app_handle.state::<PolicyState>().0)?;
+
+    let client_id = profiles::client_id()?;
+    let redirect_uri = profiles::redirect_uri();
+    let verifier = random_token()?;
+    let csrf_state = random_token()?;
+    let authorize_url = authorize_url(&client_id, &redirect_uri, &challenge(&verifier), &csrf_state)?;
+
+    // A newer login replaces the sender, which cancels the older one
+    let (sender, receiver) = oneshot::channel();
+    *pkce.0.lock().map_err(|e| e.to_string())? = Some(sender);
+    crate::guest::set(&app_handle, false);
+    lifecycle::transition(&app_handle, AppState::Authenticating);
+    if let Err(e) = open_login_window(&app_handle, authorize_url, redirect_uri.clone()) {
+        lifecycle::sign_in_failed(&app_handle, &AuthError::from(e.as_str()));
+        return Err(e.into());
+    }
+
+    let callback = receiver.await.ok().flatten();
+    if let Some(window) = app_handle.get_webview_window(LOGIN_WINDOW) {
+        let _ = window.close();
+    }
+    let Some(callback) = callback else {
+        lifecycle::sign_in_failed(&app_handle, &AuthError::from("Sign-in was cancelled"));
+        return Err("Sign-in was cancelled".into());
+    };
+
+    let result = match authorization_code(&callback, &csrf_state) {
+        Ok(code) => exchange_code(&app_handle, &client_id, &code, &verifier, &redirect_uri).await,
+        Err(e) => Err(e),
+    };
+    let signed_in = result
+        .map_err(AuthError::from)
+        .inspect_err(|e| auth::sign_in_error(&app_handle, e))?;
+    Ok(auth::finish_sign_in(&app_handle, signed_in)?)
+}
+
+fn authorize_url(client_id: &str, redirect_uri: &str, challenge: &str, csrf_state: &str) -> Result<Url, String> {
+    Url::parse_with_params(
+        &format!("{}/oauth2/v1/authorize", base_url()),
+        &[
+            ("client_id", client_id),
+            ("response_type", "code"),
+            ("redirect_uri", redirect_uri),
+            ("scope", profiles::user_scope().as_str()),
+            ("code_challenge", challenge),
+            ("code_challenge_method", "S256"),
+            ("state", csrf_state),
+        ],
+    )
+    .map_err(|e| format!("Invalid authorize URL: {}", e))
+}
+
+fn open_login_window(app_handle: &AppHandle, url: Url, redirect_uri: String) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(LOGIN_WINDOW) {
+        let _ = window.destroy();
+    }
+
+    // The redirect never loads: it is caught here and handed to the waiting command
+    let handle = app_handle.clone();
+    let window = WebviewWindowBuilder::new(app_handle, LOGIN_WINDOW, WebviewUrl::External(url))
+        .title("Sign in")
+        .inner_size(480.0, 640.0)
+        .content_protected(crate::platform::screen_capture_blocked(app_handle))
+        .focused(true)
+        .center()
+        .on_navigation(move |url| {
+            if !url.as_str().starts_with(&redirect_uri) {
+                return true;
+            }
+            resolve(&handle, Some(url.clone()));
+            false
+        })
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let handle = app_handle.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            resolve(&handle, None);
+        }
+    });
+    Ok(())
+}
+
+fn resolve(app_handle: &AppHandle, callback: Option<Url>) {
+    let sender = app_handle
+        .state::<PkceState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take());
+    if let Some(sender) = sender {
+        let _ = sender.send(callback);
+    }
+}
+
+// The code from the redirect, once its state matches the one this login sent
+fn authorization_code(callback: &Url, csrf_state: &str) -> Result<String, String> {
+    let param = |name: &str| {
+        callback
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+    if let Some(error) = param("error") {
+        return Err(format!("Authentication failed: {}", param("error_description").unwrap_or(error)));
+    }
+    if param("state").as_deref() != Some(csrf_state) {
+        return Err("Authentication failed: the sign-in response doesn't belong to this login".to_string());
+    }
+    param("code").ok_or_else(|| "Authentication failed: IDCS returned no authorization code".to_string())
+}
+
+async fn exchange_code(
+    app_handle: &AppHandle,
+    client_id: &str,
+    code: &str,
+    verifier: &str,
+    redirect_uri: &str,
+) -> Result<Session, String> {
+    log::debug!("Exchanging authorization code for access token");
+    let dpop_key = if profiles::dpop_enabled() { Some(DpopKey::generate()?) } else { None };
+    let client = app_handle.state::<AuthClient>();
+    let mut request = client
+        .http()
+        .post(client.url(&profiles::token_path()))
+        .header(ACCEPT_LANGUAGE, i18n::accept_language());
+    if let Some((name, value)) = dpop_key.as_ref().map(|key| key.token_request_header()) {
+        request = request.header(name, value);
+    }
+    let response = circuit::send(request.form(&[
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", client_id),
+        ("code_verifier", verifier),
+    ]))
+    .await?;
+
+    let status = response.status();
+    let response_text = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("Failed to get token: {}", response_text));
+    }
+    let token_response: TokenResponse = serde_json::from_str(&response_text)
+        .map_err(|e| format!("Failed to parse token response: {}", e))?;
+
+    if let Some(key) = &dpop_key {
+        key.bind(&token_response.access_token);
+    }
+    let authorization = dpop::authorization(&token_response.access_token, dpop_key.as_deref());
+    let profile = auth::get_user_profile(app_handle, &client, &authorization).await?;
+    Ok(Session {
+        profile,
+        expires_at: Some(chrono::Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64)),
+        access_token: Some(token_response.access_token),
+        client_ip: None,
+        dpop_key,
+        scopes: Some(ScopeGrant::new(&profiles::user_scope(), token_response.scope.as_deref())),
+        refresh_token: token_response.refresh_token,
+    })
+}
+
+// 32 random bytes, base64url encoded: a valid PKCE verifier and an unguessable state
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn callback(query: &str) -> Url {
+        Url::parse(&format!("http://127.0.0.1/callback?{}", query)).unwrap()
+    }
+
+    // The example in RFC 7636, appendix B
+    #[test]
+    fn challenge_is_the_s256_of_the_verifier() {
+        assert_eq!(
+            challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbkRH9ueY-cM"
+        );
+    }
+
+    #[test]
+    fn authorization_code_returns_the_code_for_this_login() {
+        assert_eq!(authorization_code(&callback("code=abc&state=s1"), "s1"), Ok("abc".to_string()));
+    }
+
+    #[test]
+    fn authorization_code_rejects_another_logins_state() {
+        assert!(authorization_code(&callback("code=abc&state=s2"), "s1").is_err());
+        assert!(authorization_code(&callback("code=abc"), "s1").is_err());
+    }
+
+    #[test]
+    fn authorization_code_reports_the_idcs_error() {
+        let error = authorization_code(&callback("error=access_denied&error_description=Denied&state=s1"), "s1");
+        assert_eq!(error, Err("Authentication failed: Denied".to_string()));
+    }
+
+    #[test]
+    fn authorization_code_requires_a_code() {
+        assert!(authorization_code(&callback("state=s1"), "s1").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use auto_launch::{AutoLaunch, AutoLaunchBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, State};
+
+use crate::branding;
+use crate::config::ConfigState;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::paths;
+
+// Keychain entries are stored under this service, one per account name
+const KEYCHAIN_SERVICE: &str = "oci-auth-tauri";
+
+// Whether `apply_rendering_workaround` turned software rendering on for this run
+static SOFTWARE_RENDERING: AtomicBool = AtomicBool::new(false);
+
+// What the current OS supports, so the frontend only offers settings that work
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformCapabilities {
+    pub os: &'static str,
+    // Name of the OS secret store; None where there is no supported one
+    pub keychain: Option<&'static str>,
+    pub autostart: bool,
+    pub screen_capture_blocking: bool,
+    // The WebView renders without the GPU this run
+    pub software_rendering: bool,
+}
+
+#[command]
+pub fn get_platform_capabilities() -> Result<PlatformCapabilities, CommandError> {
+    Ok(PlatformCapabilities {
+        os: std::env::consts::OS,
+        keychain: keychain_backend(),
+        autostart: cfg!(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+        screen_capture_blocking: cfg!(any(target_os = "windows", target_os = "macos")),
+        software_rendering: SOFTWARE_RENDERING.load(Ordering::Relaxed),
+    })
+}
+
+// GPU drivers whose WebKitGTK output is blank or corrupted; software rendering is used by default with these
+const SOFTWARE_RENDERING_DRIVERS: &[&str] = &["nvidia", "vmwgfx", "vboxvideo", "qxl", "cirrus", "hyperv_drm"];
+
+// Turns off GPU rendering when `ui.force_software_rendering` is true, or when it is unset and a blacklisted
+// Linux GPU is found. The WebView starts before the app's setup runs, so the flag is read straight from the
+// config file.
+pub fn apply_rendering_workaround(identifier: &str) {
+    let forced = config_file(identifier)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        .and_then(|store| store["config"]["ui"]["force_software_rendering"].as_bool());
+    let enabled = match forced {
+        Some(enabled) => enabled,
+        None => match blacklisted_gpu_driver() {
+            Some(driver) => {
+                // The logger isn't up yet
+                println!("Using software rendering for the {} GPU driver", driver);
+                true
+            }
+            None => false,
+        },
+    };
+    if !enabled {
+        return;
+    }
+
+    if cfg!(target_os = "linux") {
+        // Also silences Mesa's warnings about missing GPU drivers
+        std::env::set_var("LIBGL_ALWAYS_SOFTWARE", "1");
+        std::env::set_var("WEBKIT_DISABLE_DMABUF_RENDERER", "1");
+    } else if cfg!(target_os = "windows") {
+        std::env::set_var("WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS", "--disable-gpu");
+    } else {
+        return;
+    }
+    SOFTWARE_RENDERING.store(true, Ordering::Relaxed);
+}
+
+// Kernel driver of the first blacklisted GPU under /sys/class/drm; always None off Linux
+fn blacklisted_gpu_driver() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            // card0 is a GPU; card0-HDMI-A-1 and friends are its connectors
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("card").is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .filter_map(|entry| std::fs::read_link(entry.path().join("device/driver")).ok())
+        .filter_map(|driver| driver.file_name().map(|name| name.to_string_lossy().to_string()))
+        .find(|driver| SOFTWARE_RENDERING_DRIVERS.contains(&driver.as_str()))
+}
+
+// Same location as `paths::data_dir`, resolved without an app handle
+fn config_file(identifier: &str) -> Option<PathBuf> {
+    let dir = match paths::portable_home() {
+        Some(home) => home.join(paths::DATA_SUBDIR),
+        None => dirs::data_dir()?.join(identifier),
+    };
+    Some(dir.join("config.json"))
+}
+
+fn keychain_backend() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("Keychain")
+    } else if cfg!(target_os = "windows") {
+        Some("Credential Manager")
+    } else if cfg!(target_os = "linux") {
+        Some("Secret Service")
+    } else {
+        None
+    }
+}
+
+// Secrets live in the OS store rather than the app's folder; None when nothing is stored for `account`
+pub fn keychain_get(account: &str) -> Result<Option<String>, String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from the {}: {}", account, keychain_name(), e)),
+    }
+}
+
+pub fn keychain_set(account: &str, secret: &str) -> Result<(), String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, account)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(|e| format!("Failed to save {} to the {}: {}", account, keychain_name(), e))
+}
+
+// Deleting an entry that doesn't exist is not an error
+pub fn keychain_delete(account: &str) -> Result<(), String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|e| e.to_string())?;
+    match entry.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete {} from the {}: {}", account, keychain_name(), e)),
+    }
+}
+
+// Where an entry lives, as shown to the user, e.g. "Keychain: oci-auth-tauri/default/client-secret"
+pub fn keychain_location(account: &str) -> String {
+    format!("{}: {}/{}", keychain_name(), KEYCHAIN_SERVICE, account)
+}
+
+fn keychain_name() -> &'static str {
+    keychain_backend().unwrap_or("keychain")
+}
+
+#[command]
+pub fn get_launch_at_login() -> Result<bool, CommandError> {
+    Ok(auto_launch()?.is_enabled().map_err(|e| e.to_string())?)
+}
+
+// Registers the app as a login item (macOS), Run key (Windows) or autostart entry (Linux)
+#[command(unlocked)]
+pub fn set_launch_at_login(app_handle: AppHandle, enabled: bool) -> Result<(), CommandError> {
+    let auto_launch = auto_launch()?;
+    if enabled {
+        auto_launch.enable().map_err(|e| e.to_string())?;
+    } else {
+        auto_launch.disable().map_err(|e| e.to_string())?;
+    }
+    log::info!("Launch at login {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+fn auto_launch() -> Result<AutoLaunch, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut builder = AutoLaunchBuilder::new();
+    builder
+        .set_app_name(branding::APP_NAME)
+        .set_app_path(&exe.to_string_lossy())
+        .set_use_launch_agent(true);
+    // A portable copy has to come back up portable
+    if paths::portable_home().is_some() {
+        builder.set_args(&["--portable"]);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+// Takes effect at the next start; None goes back to using software rendering only for blacklisted GPUs
+#[command(unlocked)]
+pub fn set_force_software_rendering(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    forced: Option<bool>,
+) -> Result<(), CommandError> {
+    let mut config = config_state.0.lock();
+    config.set_force_software_rendering(&app_handle, forced)?;
+    Ok(())
+}
+
+#[command(unlocked)]
+pub fn set_screen_capture_blocked(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    blocked: bool,
+) -> Result<(), CommandError> {
+    let mut config = config_state.0.lock();
+    config.set_block_screen_capture(&app_handle, blocked)?;
+    drop(config);
+    apply_screen_capture_blocking(&app_handle);
+    Ok(())
+}
+
+// Applies `platform.block_screen_capture` to every open window; new windows call `screen_capture_blocked`
+pub fn apply_screen_capture_blocking(app_handle: &AppHandle) {
+    let blocked = screen_capture_blocked(app_handle);
+    for (label, window) in app_handle.webview_windows() {
+        if let Err(e) = window.set_content_protected(blocked) {
+            log::warn!("Failed to update screen capture blocking for {}: {}", label, e);
+        }
+    }
+}
+
+pub fn screen_capture_blocked(app_handle: &AppHandle) -> bool {
+    app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.platform.block_screen_capture)
+}
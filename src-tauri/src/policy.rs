@@ -0,0 +1,171 @@
+use chrono::{DateTime, Datelike, Local, NaiveTime, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::features::FlagRule;
+
+// Admin-managed policy file, read-only to the app. Users can't change it through the UI or CLI.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub motd: Option<MotdPolicy>,
+    // Lets help desk admins view the app as another user; off unless the tenant opts in
+    pub allow_impersonation: bool,
+    pub features: Option<FeaturesPolicy>,
+    // Forces `security.protect_tokens` on for every user
+    pub protect_tokens: bool,
+    // Blocks interactive login outside these hours, e.g. on kiosks and shared workstations
+    pub allowed_hours: Option<AllowedHours>,
+    // Background check that the client registration still works; off unless configured
+    pub canary: Option<CanaryPolicy>,
+    // Turns on kiosk mode, as `--kiosk` does
+    pub kiosk: Option<KioskPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotdPolicy {
+    // Endpoint returning a JSON announcement or plain text
+    pub url: Option<String>,
+    // Static announcement used when no URL is configured or it can't be reached
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeaturesPolicy {
+    // JSON object of flag rules, polled so rollouts can change without a new policy file
+    pub url: Option<String>,
+    pub refresh_minutes: Option<u64>,
+    // Flag rules applied before the remote source answers; remote rules win
+    pub flags: BTreeMap<String, FlagRule>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CanaryPolicy {
+    pub interval_minutes: Option<u64>,
+    // Each result is also posted here as JSON, for fleet dashboards
+    pub report_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KioskPolicy {
+    // The only profile the kiosk signs in with; the one active at launch when unset
+    pub profile: Option<String>,
+}
+
+// Local time. An `end` before `start` spans midnight and belongs to the day it starts on;
+// `start` equal to `end` allows the whole day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowedHours {
+    #[serde(default = "every_day")]
+    pub days: Vec<Weekday>,
+    #[serde(serialize_with = "serialize_hhmm", deserialize_with = "deserialize_hhmm")]
+    pub start: NaiveTime,
+    #[serde(serialize_with = "serialize_hhmm", deserialize_with = "deserialize_hhmm")]
+    pub end: NaiveTime,
+}
+
+impl AllowedHours {
+    pub fn permits(&self, now: DateTime<Local>) -> bool {
+        let (today, time) = (now.weekday(), now.time());
+        if self.start == self.end {
+            self.days.contains(&today)
+        } else if self.start < self.end {
+            self.days.contains(&today) && time >= self.start && time < self.end
+        } else if time >= self.start {
+            self.days.contains(&today)
+        } else {
+            time < self.end && self.days.contains(&today.pred())
+        }
+    }
+
+    // e.g. "Mon, Tue, Wed, Thu, Fri from 08:00 to 18:00"
+    pub fn describe(&self) -> String {
+        let days: Vec<String> = self.days.iter().map(|day| day.to_string()).collect();
+        if self.start == self.end {
+            return format!("on {}", days.join(", "));
+        }
+        format!("{} from {} to {}", days.join(", "), self.start.format("%H:%M"), self.end.format("%H:%M"))
+    }
+}
+
+fn every_day() -> Vec<Weekday> {
+    vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+}
+
+pub(crate) fn serialize_hhmm<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.format("%H:%M").to_string())
+}
+
+pub(crate) fn deserialize_hhmm<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&value, "%H:%M").map_err(serde::de::Error::custom)
+}
+
+#[derive(Default)]
+pub struct PolicyState(pub Policy);
+
+impl Policy {
+    pub fn load() -> Self {
+        let path = policy_path();
+        if !path.exists() {
+            return Policy::default();
+        }
+
+        match std::fs::read_to_string(&path).map(|contents| serde_json::from_str(&contents)) {
+            Ok(Ok(policy)) => {
+                log::info!("Loaded policy from {}", path.display());
+                policy
+            }
+            Ok(Err(e)) => {
+                log::error!("Ignoring invalid policy file {}: {}", path.display(), e);
+                Policy::default()
+            }
+            Err(e) => {
+                log::error!("Failed to read policy file {}: {}", path.display(), e);
+                Policy::default()
+            }
+        }
+    }
+}
+
+// Checked before every interactive login, including re-authentication prompts
+pub fn check_allowed_hours(policy: &Policy) -> Result<(), String> {
+    match &policy.allowed_hours {
+        Some(hours) if !hours.permits(Local::now()) => {
+            log::warn!("Login blocked outside the allowed hours");
+            Err(format!(
+                "Sign-in on this computer is only allowed {}. Contact your administrator if you need access outside these hours.",
+                hours.describe()
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+// `OCI_AUTH_POLICY_FILE` overrides the machine-wide location
+pub fn policy_path() -> PathBuf {
+    if let Ok(path) = std::env::var("OCI_AUTH_POLICY_FILE") {
+        return PathBuf::from(path);
+    }
+
+    if cfg!(target_os = "windows") {
+        let program_data = std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+        PathBuf::from(program_data).join("oci-auth-tauri").join("policy.json")
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/oci-auth-tauri/policy.json")
+    } else {
+        PathBuf::from("/etc/oci-auth-tauri/policy.json")
+    }
+}
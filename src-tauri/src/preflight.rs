@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::config::{validate_idcs_url, AppConfig};
+use crate::demo::DemoState;
+use crate::error::{CommandError, ErrorKind};
+use crate::lifecycle::{self, AppState};
+use crate::middleware::command;
+use crate::paths;
+use crate::profiles::{self, CredentialSource};
+
+// Written and removed again to prove a directory is writable
+const PROBE_FILE: &str = ".preflight";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    // "credentials", "config" or "storage"
+    pub category: &'static str,
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+// Everything the app needs before it can sign anyone in, for the setup screen and the startup log
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    // Every check passed
+    pub ready: bool,
+    pub credential_sources: Vec<CredentialSource>,
+    pub checks: Vec<PreflightCheck>,
+}
+
+#[command]
+pub fn get_preflight_report(app_handle: AppHandle) -> Result<PreflightReport, CommandError> {
+    Ok(run(&app_handle))
+}
+
+pub fn run(app_handle: &AppHandle) -> PreflightReport {
+    let mut checks = vec![credentials_check(is_demo(app_handle))];
+    checks.extend(config_checks(app_handle));
+    checks.extend(storage_checks(app_handle));
+
+    PreflightReport {
+        ready: checks.iter().all(|check| check.ok),
+        credential_sources: profiles::credential_sources(),
+        checks,
+    }
+}
+
+// Enters the "configuration required" state while the active profile has no client credentials, and leaves it
+// once they are found; call at startup and whenever the credentials or the profile change
+pub fn sync_configured_state(app_handle: &AppHandle) {
+    let configured = is_demo(app_handle) || profiles::check_configured().is_ok();
+    match (lifecycle::current(app_handle), configured) {
+        (AppState::LoggedOut, false) => lifecycle::transition(app_handle, AppState::ConfigurationRequired),
+        (AppState::ConfigurationRequired, true) => lifecycle::transition(app_handle, AppState::LoggedOut),
+        _ => {}
+    }
+}
+
+// For commands that need the tenant; settings and CLI commands work without credentials
+pub fn require_configured(app_handle: &AppHandle) -> Result<(), CommandError> {
+    if is_demo(app_handle) {
+        return Ok(());
+    }
+    profiles::check_configured().map_err(|e| CommandError::new(ErrorKind::NotConfigured, e))?;
+    Ok(())
+}
+
+fn is_demo(app_handle: &AppHandle) -> bool {
+    app_handle.try_state::<DemoState>().is_some_and(|demo| demo.enabled)
+}
+
+fn credentials_check(demo: bool) -> PreflightCheck {
+    let (ok, detail) = match profiles::check_configured() {
+        Ok(()) if profiles::is_public_client() => (true, "Client id found; public client, no secret needed".to_string()),
+        Ok(()) if profiles::client_assertion_key().is_some() => (true, "Client id and private key found".to_string()),
+        Ok(()) => (true, "Client id and secret found".to_string()),
+        // The mock provider doesn't use them
+        Err(e) if demo => (true, format!("Not needed in demo mode ({})", e)),
+        Err(e) => (false, e),
+    };
+    PreflightCheck {
+        category: "credentials",
+        name: "Client credentials".to_string(),
+        ok,
+        detail,
+    }
+}
+
+fn config_checks(app_handle: &AppHandle) -> Vec<PreflightCheck> {
+    let check = |name: &str, result: Result<String, String>| PreflightCheck {
+        category: "config",
+        name: name.to_string(),
+        ok: result.is_ok(),
+        detail: result.unwrap_or_else(|e| e),
+    };
+
+    let config = AppConfig::load(app_handle).map_err(|e| e.to_string());
+    let settings = config.as_ref().map(|_| "Settings are valid".to_string()).map_err(|e| {
+        format!("Settings can't be read, defaults are used: {}", e)
+    });
+    let idcs_url = match config.as_ref().ok().and_then(|config| config.idcs.base_url.clone()) {
+        Some(url) => validate_idcs_url(&url).map(|url| format!("Using {}", url)),
+        None => Ok(format!("Using {}", crate::endpoints::base_url())),
+    };
+    let network = config.as_ref().map(|config| config.network.clone()).unwrap_or_default();
+    let tls = crate::network::check_tls(&network);
+    let tls = if tls.ok {
+        Ok(match network.ca_certificate {
+            Some(file) => format!("Trusting {} extra certificates from {}", tls.ca_certificates, file),
+            None => "Using the system's trusted certificates".to_string(),
+        })
+    } else {
+        Err(tls.errors.join("; "))
+    };
+    vec![check("Settings", settings), check("IDCS URL", idcs_url), check("TLS", tls)]
+}
+
+fn storage_checks(app_handle: &AppHandle) -> Vec<PreflightCheck> {
+    [
+        ("Data folder", paths::data_dir(app_handle)),
+        ("Local data folder", paths::local_data_dir(app_handle)),
+        ("Cache folder", paths::cache_dir(app_handle)),
+        ("Log folder", paths::log_dir(app_handle)),
+    ]
+    .into_iter()
+    .map(|(name, dir)| {
+        let result = dir.and_then(|dir| writable(&dir).map(|()| dir.display().to_string()));
+        PreflightCheck {
+            category: "storage",
+            name: name.to_string(),
+            ok: result.is_ok(),
+            detail: result.unwrap_or_else(|e| e),
+        }
+    })
+    .collect()
+}
+
+fn writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(PROBE_FILE);
+    std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&probe, b""))
+        .and_then(|()| std::fs::remove_file(&probe))
+        .map_err(|e| format!("{} is not writable: {}", dir.display(), e))
+}
+
+// Plain-text version for stderr and the log
+pub fn format_report(report: &PreflightReport) -> String {
+    let mut lines = vec!["Preflight report:".to_string()];
+    for check in &report.checks {
+        let status = if check.ok { "ok" } else { "FAILED" };
+        lines.push(format!("  [{}] {}: {}", status, check.name, check.detail));
+    }
+    lines.push("  Credential sources checked:".to_string());
+    for source in &report.credential_sources {
+        let found = match (source.has_client_id, source.has_client_secret, &source.error) {
+            (_, _, Some(e)) => format!("unavailable ({})", e),
+            (true, true, _) => "client id and secret".to_string(),
+            (true, false, _) => "client id".to_string(),
+            (false, true, _) => "client secret".to_string(),
+            (false, false, _) => "nothing".to_string(),
+        };
+        lines.push(format!("    {} ({}): {}", source.source, source.detail, found));
+    }
+    lines.join("\n")
+}
+
+// Shown instead of exiting when settings or storage are broken. Missing credentials don't need it: the
+// frontend shows the onboarding wizard in the "configuration required" state.
+pub fn show_setup_dialog(app_handle: &AppHandle, report: &PreflightReport) {
+    let failed: Vec<String> = report
+        .checks
+        .iter()
+        .filter(|check| !check.ok && check.category != "credentials")
+        .map(|check| format!("• {}: {}", check.name, check.detail))
+        .collect();
+    if failed.is_empty() {
+        return;
+    }
+    let hint = "Details are in the log. You can continue, but some features may not work.";
+    let handle = app_handle.clone();
+    app_handle
+        .dialog()
+        .message(format!(
+            "{} found problems with its setup.\n\n{}\n\n{}",
+            crate::branding::APP_NAME,
+            failed.join("\n"),
+            hint
+        ))
+        .title("Setup required")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Continue".to_string(), "Quit".to_string()))
+        .show(move |continue_anyway| {
+            if !continue_anyway {
+                handle.exit(1);
+            }
+        });
+}
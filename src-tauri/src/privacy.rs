@@ -0,0 +1,331 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::auth_client::AuthClient;
+use crate::cache::CacheState;
+use crate::config::{AppConfig, ConfigState, LogSinkKind, RetentionConfig};
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::paths;
+use crate::session::SessionState;
+
+// Under the app data dir; anything that writes flow traces or HAR captures puts them here
+pub const FLOW_TRACE_DIR: &str = "traces";
+pub const HAR_DIR: &str = "captures";
+
+const RETENTION_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+
+// What each file the app writes is for; keep in sync when a module adds a new file
+const PURPOSES: &[(&str, &str)] = &[
+    ("config.json", "Your settings"),
+    ("profiles.json", "Your sign-in profiles and which one is selected"),
+    ("window-state.json", "Window position and size"),
+    ("motd.json", "Announcements you dismissed"),
+    ("motd-cache.json", "Last announcement, shown when offline"),
+    ("access-snapshots.json", "Login history: your groups and app roles at each sign-in"),
+    ("features.json", "Feature flags you changed and your rollout group"),
+    ("features-cache.json", "Last feature flag rules from your administrator"),
+    ("analytics-queue.json", "Usage counts waiting to be sent, if you opted in"),
+    ("canary.json", "Results of the scheduled sign-in health check, if your administrator enabled it"),
+    ("audit.json", "Administrator actions such as viewing another user's session, and where you signed in from"),
+    ("dev-snapshot.json", "Developer state snapshot"),
+    ("verification.json", "Your anti-phishing verification phrase"),
+    ("device.json", "Public half of this device's binding key and the sign-ins made from this device"),
+    ("ipc.key", "Key local tools use to sign requests to the app; replaced at every start"),
+    ("cache", "Cached profile, branding and tenant metadata"),
+    (FLOW_TRACE_DIR, "Sign-in flow traces"),
+    (HAR_DIR, "Network captures (HAR)"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedItem {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryItem {
+    pub path: String,
+    pub is_dir: bool,
+    pub bytes: u64,
+    pub purpose: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WipeSummary {
+    pub revoked_tokens: usize,
+    pub removed: Vec<RemovedItem>,
+    pub bytes: u64,
+    // Paths that could not be deleted, e.g. a log file held open on Windows
+    pub failed: Vec<String>,
+}
+
+// Every file the app keeps on this machine, for the "Your data" settings page
+#[command]
+pub fn get_local_data_inventory(app_handle: AppHandle) -> Result<Vec<InventoryItem>, CommandError> {
+    let mut items = Vec::new();
+    for dir in data_dirs(&app_handle) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            items.push(InventoryItem {
+                path: path.display().to_string(),
+                is_dir: path.is_dir(),
+                bytes: size_of(&path),
+                purpose: purpose_of(&name).to_string(),
+            });
+        }
+    }
+
+    // Credentials saved from the setup screen live in the OS keychain
+    for (account, purpose) in crate::profiles::saved_keychain_entries(&app_handle) {
+        items.push(InventoryItem {
+            path: crate::platform::keychain_location(&account),
+            is_dir: false,
+            bytes: 0,
+            purpose,
+        });
+    }
+
+    // Log sinks can write outside the app directories
+    let config = app_handle.state::<ConfigState>();
+    let sinks = config.0.lock().logging.sinks.clone();
+    for sink in sinks {
+        if let LogSinkKind::File { path } = sink.kind {
+            if path.exists() {
+                items.push(InventoryItem {
+                    path: path.display().to_string(),
+                    is_dir: false,
+                    bytes: size_of(&path),
+                    purpose: format!("Log file written by the {} log sink", sink.name.unwrap_or_else(|| "file".to_string())),
+                });
+            }
+        }
+    }
+
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(items)
+}
+
+fn purpose_of(name: &str) -> &'static str {
+    if let Some((_, purpose)) = PURPOSES.iter().find(|(file, _)| *file == name) {
+        return purpose;
+    }
+    if name.starts_with("oci-auth-") && name.ends_with(".log") {
+        return "Application log";
+    }
+    "Unknown file in the app's folder"
+}
+
+// Asks for confirmation, then removes everything the app stores on this machine.
+// Returns None when the user cancels.
+#[command(unlocked)]
+pub async fn wipe_all_local_data(app_handle: AppHandle) -> Result<Option<WipeSummary>, CommandError> {
+    let confirmed = app_handle
+        .dialog()
+        .message(format!(
+            "This signs you out, revokes your tokens and deletes all settings, caches, history and logs \
+             stored by {} on this computer.\n\nThis can't be undone.",
+            crate::branding::APP_NAME
+        ))
+        .title("Delete all local data")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Delete".to_string(), "Cancel".to_string()))
+        .blocking_show();
+    if !confirmed {
+        log::info!("Local data wipe cancelled");
+        return Ok(None);
+    }
+
+    let summary = wipe_local_data(&app_handle).await?;
+    crate::lifecycle::transition(&app_handle, crate::lifecycle::AppState::LoggedOut);
+    // Credentials saved in the keychain are gone too
+    crate::preflight::sync_configured_state(&app_handle);
+    crate::bus::emit(&app_handle, "local-data-wiped", &summary);
+    Ok(Some(summary))
+}
+
+// Also used by `--wipe`, where none of the managed state exists yet
+pub async fn wipe_local_data(app_handle: &AppHandle) -> Result<WipeSummary, String> {
+    let mut summary = WipeSummary::default();
+
+    let session = app_handle
+        .try_state::<SessionState>()
+        .and_then(|session| session.0.lock().ok().and_then(|mut session| session.take()));
+    let user_tokens = session
+        .map(|session| session.access_token.into_iter().chain(session.refresh_token).collect())
+        .unwrap_or_default();
+    let client = match app_handle.try_state::<AuthClient>() {
+        Some(client) => client.inner().clone(),
+        None => AuthClient::from_config(&AppConfig::load(app_handle).map(|config| config.network).unwrap_or_default()),
+    };
+    match crate::auth::revoke_tokens(&client, user_tokens).await {
+        Ok(count) => summary.revoked_tokens = count,
+        Err(e) => log::warn!("Failed to revoke tokens: {}", e),
+    }
+
+    if let Some(cache) = app_handle.try_state::<CacheState>() {
+        cache.invalidate_all();
+    }
+    if let Err(e) = crate::analytics::clear(app_handle) {
+        log::warn!("Failed to clear the analytics queue: {}", e);
+    }
+    if let Some(config) = app_handle.try_state::<ConfigState>() {
+        *config.0.lock() = AppConfig::default();
+    }
+
+    // Before the profiles store goes, since it lists the profiles that may have entries
+    for (account, _) in crate::profiles::saved_keychain_entries(app_handle) {
+        let location = crate::platform::keychain_location(&account);
+        match crate::platform::keychain_delete(&account) {
+            Ok(()) => summary.removed.push(RemovedItem { path: location, bytes: 0 }),
+            Err(e) => {
+                log::warn!("{}", e);
+                summary.failed.push(location);
+            }
+        }
+    }
+    for dir in data_dirs(app_handle) {
+        remove_contents(&dir, &mut summary);
+    }
+    summary.bytes = summary.removed.iter().map(|item| item.bytes).sum();
+
+    log::warn!(
+        "Local data wiped: {} items, {} bytes, {} tokens revoked",
+        summary.removed.len(),
+        summary.bytes,
+        summary.revoked_tokens
+    );
+    Ok(summary)
+}
+
+// Applies `privacy.retention` at startup and every few hours after that
+pub async fn enforce_retention(app_handle: AppHandle) {
+    loop {
+        let retention = app_handle
+            .state::<ConfigState>()
+            .0
+            .read(|config| config.privacy.retention.clone());
+        apply_retention(&app_handle, &retention);
+        tokio::time::sleep(RETENTION_INTERVAL).await;
+    }
+}
+
+pub fn apply_retention(app_handle: &AppHandle, retention: &RetentionConfig) {
+    let cutoff = |days: u32| Utc::now() - ChronoDuration::days(days as i64);
+
+    if let Some(days) = retention.audit_days {
+        match crate::audit::prune(app_handle, cutoff(days)) {
+            Ok(0) => {}
+            Ok(count) => log::info!("Removed {} audit events older than {} days", count, days),
+            Err(e) => log::warn!("Failed to prune the audit log: {}", e),
+        }
+    }
+    if let Some(days) = retention.login_history_days {
+        match crate::access::prune(app_handle, cutoff(days)) {
+            Ok(0) => {}
+            Ok(count) => log::info!("Removed {} login history entries older than {} days", count, days),
+            Err(e) => log::warn!("Failed to prune login history: {}", e),
+        }
+    }
+
+    let Ok(data_dir) = paths::data_dir(app_handle) else {
+        return;
+    };
+    for (dir, days) in [(FLOW_TRACE_DIR, retention.flow_trace_days), (HAR_DIR, retention.har_days)] {
+        let Some(days) = days else {
+            continue;
+        };
+        let max_age = Duration::from_secs(days as u64 * 86400);
+        let count = prune_files(&data_dir.join(dir), max_age);
+        if count > 0 {
+            log::info!("Removed {} files from {} older than {} days", count, dir, days);
+        }
+    }
+}
+
+// Deletes files in `dir` last modified more than `max_age` ago
+fn prune_files(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .map(|age| age > max_age)
+                .unwrap_or(false)
+        })
+        .filter(|entry| match std::fs::remove_file(entry.path()) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Failed to remove {}: {}", entry.path().display(), e);
+                false
+            }
+        })
+        .count()
+}
+
+// Every directory the app writes to; some platforms map several of these to the same place
+pub fn data_dirs(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = [
+        paths::data_dir(app_handle),
+        paths::local_data_dir(app_handle),
+        paths::config_dir(app_handle),
+        paths::cache_dir(app_handle),
+        paths::log_dir(app_handle),
+    ]
+    .into_iter()
+    .filter_map(|dir| dir.ok())
+    .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+fn remove_contents(dir: &Path, summary: &mut WipeSummary) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let bytes = size_of(&path);
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match result {
+            Ok(()) => summary.removed.push(RemovedItem {
+                path: path.display().to_string(),
+                bytes,
+            }),
+            Err(e) => {
+                log::warn!("Failed to remove {}: {}", path.display(), e);
+                summary.failed.push(path.display().to_string());
+            }
+        }
+    }
+}
+
+pub fn size_of(path: &Path) -> u64 {
+    if path.is_dir() {
+        std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| size_of(&entry.path())).sum())
+            .unwrap_or(0)
+    } else {
+        std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+    }
+}
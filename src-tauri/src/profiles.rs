@@ -0,0 +1,700 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreBuilder;
+
+use crate::auth_client::AuthClient;
+use crate::config::validate_idcs_url;
+use crate::endpoints;
+use crate::error::CommandError;
+use crate::maintenance::MaintenanceWindow;
+use crate::middleware::command;
+use crate::platform;
+
+const PROFILES_STORE_FILE: &str = "profiles.json";
+// Always available, even before the user has saved anything
+pub const DEFAULT_PROFILE: &str = "default";
+const DEFAULT_SCOPE: &str = "urn:opc:idm:__myscopes__";
+const OFFLINE_ACCESS_SCOPE: &str = "offline_access";
+const DEFAULT_TOKEN_PATH: &str = "/oauth2/v1/token";
+const DEFAULT_REDIRECT_URI: &str = "http://localhost/oci-auth/callback";
+const CLIENT_ID_VAR: &str = "OCI_CLIENT_ID";
+const CLIENT_SECRET_VAR: &str = "OCI_CLIENT_SECRET";
+// File holding the client secret, as rendered by a vault agent or mounted as a container secret
+const CLIENT_SECRET_FILE_VAR: &str = "OCI_CLIENT_SECRET_FILE";
+// Prefix of the missing-credentials error, which commands report as `ErrorKind::NotConfigured`
+const NOT_CONFIGURED: &str = "Client credentials are not configured";
+// Keychain entries are named `<profile>/<entry>`
+const CLIENT_ID_ENTRY: &str = "client-id";
+const CLIENT_SECRET_ENTRY: &str = "client-secret";
+const CERTIFICATE_PASSPHRASE_ENTRY: &str = "client-certificate-passphrase";
+// A resumable session's refresh token, and the rotated one while `TokenManager` replaces it
+pub(crate) const REFRESH_TOKEN_ENTRY: &str = "refresh-token";
+pub(crate) const PENDING_REFRESH_TOKEN_ENTRY: &str = "refresh-token-pending";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    // Pre-filled on the login form when this profile is selected
+    pub default_username: Option<String>,
+    // Go straight to the password step with `default_username`, e.g. on single-user machines
+    pub auto_start_login: bool,
+    // Known IDCS maintenance; automatic refreshes pause while one is in progress
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    // IDCS URLs tried in order, e.g. a custom domain then the default domain; empty uses the built-in URL
+    pub idcs_urls: Vec<String>,
+    // Confidential app registered in the tenant; `OCI_CLIENT_ID` when unset
+    pub client_id: Option<String>,
+    // Environment variable holding the client secret, `OCI_CLIENT_SECRET` when unset; the secret is never
+    // written to the app's files
+    pub client_secret_env: Option<String>,
+    // Requested with every token; `urn:opc:idm:__myscopes__` when empty
+    pub scopes: Vec<String>,
+    // Public client app: signs in through the browser with PKCE and has no client secret
+    pub public_client: bool,
+    // Redirect URI registered for the public client; `http://localhost/oci-auth/callback` when unset
+    pub redirect_uri: Option<String>,
+    // PEM RSA private key the client app authenticates with (`private_key_jwt`) instead of a client secret
+    pub private_key_file: Option<String>,
+    // Alias of the matching certificate in the IDCS client app; required with `private_key_file`
+    pub private_key_id: Option<String>,
+    // Binds user tokens to an ephemeral key (DPoP), for tenants and resource servers that require
+    // sender-constrained tokens
+    pub dpop: bool,
+    // PKCS#12 client certificate presented to IDCS, for tenants that require mutual TLS on the token
+    // endpoint; its passphrase is saved in the keychain with `save_client_certificate_passphrase`
+    pub client_certificate_file: Option<String>,
+    // Asks for `offline_access` with user tokens, so sessions renew silently before they expire and resume after
+    // a restart; the client app needs the Refresh Token grant
+    pub refresh_tokens: bool,
+    // Overrides of the private key assertion's `aud` (`https://identity.oraclecloud.com/` when unset) and `iss`
+    // (the client id), for identity-domain setups that expect other values
+    pub assertion_audience: Option<String>,
+    pub assertion_issuer: Option<String>,
+    // Token endpoint path on the IDCS URL; `/oauth2/v1/token` when unset
+    pub token_path: Option<String>,
+}
+
+// Token endpoint settings the profile overrides, checked against discovery before the token exchange
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TokenEndpointOverrides {
+    pub audience: Option<String>,
+    pub issuer: Option<String>,
+    pub token_path: Option<String>,
+}
+
+impl Profile {
+    fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+// The profile the auth flow runs against, set by `activate`
+static ACTIVE: RwLock<Option<Profile>> = RwLock::new(None);
+
+// What the login window should do for the selected profile
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginStart {
+    pub profile: String,
+    pub username: Option<String>,
+    // True when the identifier step can be skipped
+    pub auto_start: bool,
+}
+
+// One place client credentials are looked up, in the order `client_credentials` tries them
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialSource {
+    // "profile", "env", "keychain" or "vault"
+    pub source: &'static str,
+    // What was checked, e.g. the variable or keychain entry name
+    pub detail: String,
+    pub has_client_id: bool,
+    pub has_client_secret: bool,
+    // The source couldn't be read, e.g. no keychain on this system
+    pub error: Option<String>,
+}
+
+impl From<&Profile> for LoginStart {
+    fn from(profile: &Profile) -> Self {
+        Self {
+            profile: profile.name.clone(),
+            username: profile.default_username.clone(),
+            auto_start: profile.auto_start_login && profile.default_username.is_some(),
+        }
+    }
+}
+
+#[command]
+pub fn get_active_profile(app_handle: AppHandle) -> Result<Profile, CommandError> {
+    Ok(active_profile(&app_handle)?)
+}
+
+// Saved profiles, starting with the default one
+#[command]
+pub fn list_profiles(app_handle: AppHandle) -> Result<Vec<Profile>, CommandError> {
+    // A kiosk only signs in with its own profile
+    if crate::kiosk::is_enabled(&app_handle) {
+        return Ok(vec![active_profile(&app_handle)?]);
+    }
+    let mut profiles = load_profiles(&app_handle)?;
+    if !profiles.iter().any(|profile| profile.name == DEFAULT_PROFILE) {
+        profiles.insert(0, Profile::named(DEFAULT_PROFILE));
+    }
+    Ok(profiles)
+}
+
+#[command(unlocked)]
+pub fn add_profile(app_handle: AppHandle, profile: Profile) -> Result<(), CommandError> {
+    let profile = validate(profile)?;
+    let mut profiles = load_profiles(&app_handle)?;
+    if find(&profiles, &profile.name).is_some() {
+        return Err(format!("Profile {} already exists", profile.name).into());
+    }
+    log::info!("Added profile {}", profile.name);
+    profiles.push(profile);
+    save_profiles(&app_handle, &profiles)?;
+    Ok(())
+}
+
+// The default profile can't be deleted; deleting the active profile switches back to it
+#[command(unlocked)]
+pub fn delete_profile(app_handle: AppHandle, name: String) -> Result<(), CommandError> {
+    if name == DEFAULT_PROFILE {
+        return Err("The default profile can't be deleted".to_string().into());
+    }
+    let mut profiles = load_profiles(&app_handle)?;
+    let count = profiles.len();
+    profiles.retain(|profile| profile.name != name);
+    if profiles.len() == count {
+        return Err(format!("Unknown profile: {}", name).into());
+    }
+    save_profiles(&app_handle, &profiles)?;
+    if let Err(e) = delete_keychain_entries(&name) {
+        log::warn!("Failed to delete the keychain entries of profile {}: {}", name, e);
+    }
+
+    if active_profile_name(&app_handle)? == name {
+        set_active_profile(app_handle.clone(), DEFAULT_PROFILE.to_string())?;
+    }
+    log::info!("Deleted profile {}", name);
+    Ok(())
+}
+
+// Called by the login window at startup
+#[command]
+pub fn get_login_start(app_handle: AppHandle) -> Result<LoginStart, CommandError> {
+    Ok(LoginStart::from(&active_profile(&app_handle)?))
+}
+
+// Makes `name` the active profile and returns how its login should begin
+#[command(unlocked)]
+pub fn set_active_profile(app_handle: AppHandle, name: String) -> Result<LoginStart, CommandError> {
+    let profile = select(&app_handle, &name)?;
+    crate::preflight::sync_configured_state(&app_handle);
+    Ok(LoginStart::from(&profile))
+}
+
+pub(crate) fn select(app_handle: &AppHandle, name: &str) -> Result<Profile, String> {
+    let profiles = load_profiles(app_handle)?;
+    let profile = find(&profiles, name).ok_or_else(|| format!("Unknown profile: {}", name))?;
+
+    let store = StoreBuilder::new(app_handle, profiles_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    store.set("active", json!(name));
+    store.save().map_err(|e| e.to_string())?;
+
+    activate(app_handle)?;
+    log::info!("Selected profile {}", name);
+    Ok(profile)
+}
+
+#[command(unlocked)]
+pub fn set_profile_login_options(
+    app_handle: AppHandle,
+    name: String,
+    default_username: Option<String>,
+    auto_start_login: bool,
+) -> Result<(), CommandError> {
+    let default_username = default_username
+        .map(|username| username.trim().to_string())
+        .filter(|username| !username.is_empty());
+    update_profile(&app_handle, &name, |profile| {
+        profile.default_username = default_username;
+        profile.auto_start_login = auto_start_login;
+    })?;
+    Ok(())
+}
+
+#[command(unlocked)]
+pub fn set_maintenance_windows(
+    app_handle: AppHandle,
+    name: String,
+    windows: Vec<MaintenanceWindow>,
+) -> Result<(), CommandError> {
+    if let Some(window) = windows.iter().find(|window| window.end <= window.start) {
+        return Err(format!("Maintenance window ends before it starts: {}", window.start).into());
+    }
+    update_profile(&app_handle, &name, |profile| profile.maintenance_windows = windows)?;
+    Ok(())
+}
+
+#[command(unlocked)]
+pub fn set_idcs_urls(app_handle: AppHandle, name: String, urls: Vec<String>) -> Result<(), CommandError> {
+    let urls = validate_urls(&urls)?;
+    update_profile(&app_handle, &name, |profile| profile.idcs_urls = urls)?;
+    Ok(())
+}
+
+// Loads the active profile for the auth flow and the HTTP layer; call at startup and after it changes
+pub fn activate(app_handle: &AppHandle) -> Result<(), String> {
+    let profile = active_profile(app_handle)?;
+    *ACTIVE.write().map_err(|e| e.to_string())? = Some(profile);
+    endpoints::load(app_handle)?;
+    match app_handle.try_state::<AuthClient>() {
+        Some(client) => client.load_profile(),
+        None => Ok(()),
+    }
+}
+
+// Public clients sign in with PKCE and never need a secret
+pub fn is_public_client() -> bool {
+    ACTIVE
+        .read()
+        .ok()
+        .is_some_and(|active| active.as_ref().is_some_and(|profile| profile.public_client))
+}
+
+pub(crate) fn redirect_uri() -> String {
+    ACTIVE
+        .read()
+        .ok()
+        .and_then(|active| active.as_ref().and_then(|profile| profile.redirect_uri.clone()))
+        .unwrap_or_else(|| DEFAULT_REDIRECT_URI.to_string())
+}
+
+pub(crate) fn dpop_enabled() -> bool {
+    ACTIVE
+        .read()
+        .ok()
+        .is_some_and(|active| active.as_ref().is_some_and(|profile| profile.dpop))
+}
+
+// Name of the active profile when its sessions are saved in the keychain and resumed at startup: it uses
+// refresh tokens, and they aren't DPoP-bound, since the key doesn't outlive the app
+pub(crate) fn resumable_sessions() -> Option<String> {
+    let active = ACTIVE.read().ok()?;
+    let profile = active.as_ref().filter(|profile| profile.refresh_tokens && !profile.dpop)?;
+    Some(profile.name.clone())
+}
+
+// Key file and certificate alias when the active profile signs client assertions instead of sending a secret
+pub(crate) fn client_assertion_key() -> Option<(String, String)> {
+    ACTIVE.read().ok().and_then(|active| {
+        let profile = active.as_ref()?;
+        Some((profile.private_key_file.clone()?, profile.private_key_id.clone()?))
+    })
+}
+
+// Certificate file and passphrase when the active profile uses mutual TLS; the passphrase is empty when none
+// is saved
+pub(crate) fn client_certificate() -> Option<(String, String)> {
+    let active = ACTIVE.read().ok()?;
+    let profile = active.as_ref()?;
+    let file = profile.client_certificate_file.clone()?;
+    let passphrase = match platform::keychain_get(&keychain_account(&profile.name, CERTIFICATE_PASSPHRASE_ENTRY)) {
+        Ok(passphrase) => passphrase.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("Failed to read the client certificate passphrase from the keychain: {}", e);
+            String::new()
+        }
+    };
+    Some((file, passphrase))
+}
+
+pub(crate) fn token_endpoint_overrides() -> TokenEndpointOverrides {
+    ACTIVE
+        .read()
+        .ok()
+        .and_then(|active| {
+            active.as_ref().map(|profile| TokenEndpointOverrides {
+                audience: profile.assertion_audience.clone(),
+                issuer: profile.assertion_issuer.clone(),
+                token_path: profile.token_path.clone(),
+            })
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn token_path() -> String {
+    token_endpoint_overrides()
+        .token_path
+        .unwrap_or_else(|| DEFAULT_TOKEN_PATH.to_string())
+}
+
+// Whether the active profile has what its client type needs: a client id, plus a secret unless it is public
+// or authenticates with a private key
+pub fn check_configured() -> Result<(), String> {
+    if let Some((file, _)) = client_certificate() {
+        if !std::path::Path::new(&file).is_file() {
+            return Err(format!("{}: client certificate {} not found", NOT_CONFIGURED, file));
+        }
+    }
+    if is_public_client() {
+        return client_id().map(|_| ());
+    }
+    let Some((key_file, _)) = client_assertion_key() else {
+        return client_credentials().map(|_| ());
+    };
+    client_id()?;
+    if !std::path::Path::new(&key_file).is_file() {
+        return Err(format!("{}: private key {} not found", NOT_CONFIGURED, key_file));
+    }
+    Ok(())
+}
+
+// Client id of the active profile, from the same sources as `client_credentials`
+pub fn client_id() -> Result<String, String> {
+    let active = ACTIVE.read().map_err(|e| e.to_string())?;
+    let profile = active.as_ref();
+    let name = profile.map(|profile| profile.name.as_str()).unwrap_or(DEFAULT_PROFILE);
+    profile
+        .and_then(|profile| profile.client_id.clone())
+        .or_else(|| std::env::var(CLIENT_ID_VAR).ok())
+        .or_else(|| keychain_credentials(name).ok().and_then(|(id, _)| id))
+        .ok_or_else(|| format!("{}: {} is not set", NOT_CONFIGURED, CLIENT_ID_VAR))
+}
+
+// Client id and secret of the active profile: its own client id, then the environment, the OS keychain and
+// a vault secret file. The keychain is only read when the environment doesn't have them.
+pub fn client_credentials() -> Result<(String, String), String> {
+    let active = ACTIVE.read().map_err(|e| e.to_string())?;
+    let profile = active.as_ref();
+    let name = profile.map(|profile| profile.name.as_str()).unwrap_or(DEFAULT_PROFILE);
+    let secret_var = secret_var(profile);
+
+    let mut client_id = profile
+        .and_then(|profile| profile.client_id.clone())
+        .or_else(|| std::env::var(CLIENT_ID_VAR).ok());
+    let mut client_secret = std::env::var(&secret_var).ok();
+    if client_id.is_none() || client_secret.is_none() {
+        match keychain_credentials(name) {
+            Ok((id, secret)) => {
+                client_id = client_id.or(id);
+                client_secret = client_secret.or(secret);
+            }
+            Err(e) => log::warn!("Failed to read client credentials from the keychain: {}", e),
+        }
+    }
+    if client_secret.is_none() {
+        client_secret = vault_secret()?;
+    }
+
+    let client_id = client_id.ok_or_else(|| format!("{}: {} is not set", NOT_CONFIGURED, CLIENT_ID_VAR))?;
+    let client_secret = client_secret.ok_or_else(|| format!("{}: {} is not set", NOT_CONFIGURED, secret_var))?;
+    Ok((client_id, client_secret))
+}
+
+// Every credential source `client_credentials` would check for the active profile, for the preflight report
+pub fn credential_sources() -> Vec<CredentialSource> {
+    let active = ACTIVE.read().ok().and_then(|active| active.clone());
+    let profile = active.as_ref();
+    let name = profile.map(|profile| profile.name.as_str()).unwrap_or(DEFAULT_PROFILE);
+    let secret_var = secret_var(profile);
+
+    let keychain = keychain_credentials(name);
+    let vault = std::env::var(CLIENT_SECRET_FILE_VAR).ok().map(|_| vault_secret());
+    vec![
+        CredentialSource {
+            source: "profile",
+            detail: format!("client_id of the {} profile", name),
+            has_client_id: profile.is_some_and(|profile| profile.client_id.is_some()),
+            has_client_secret: false,
+            error: None,
+        },
+        CredentialSource {
+            source: "env",
+            detail: format!("{} and {}", CLIENT_ID_VAR, secret_var),
+            has_client_id: std::env::var(CLIENT_ID_VAR).is_ok(),
+            has_client_secret: std::env::var(&secret_var).is_ok(),
+            error: None,
+        },
+        CredentialSource {
+            source: "keychain",
+            detail: format!("{}/{} and {}/{}", name, CLIENT_ID_ENTRY, name, CLIENT_SECRET_ENTRY),
+            has_client_id: keychain.as_ref().is_ok_and(|(id, _)| id.is_some()),
+            has_client_secret: keychain.as_ref().is_ok_and(|(_, secret)| secret.is_some()),
+            error: keychain.err(),
+        },
+        CredentialSource {
+            source: "vault",
+            detail: match std::env::var(CLIENT_SECRET_FILE_VAR) {
+                Ok(path) => format!("{}={}", CLIENT_SECRET_FILE_VAR, path),
+                Err(_) => format!("{} is not set", CLIENT_SECRET_FILE_VAR),
+            },
+            has_client_id: false,
+            has_client_secret: vault.as_ref().is_some_and(|secret| secret.as_ref().is_ok_and(Option::is_some)),
+            error: vault.and_then(Result::err),
+        },
+    ]
+}
+
+// Saves client credentials for the active profile in the OS keychain, e.g. from the setup screen
+#[command(unlocked)]
+pub fn save_client_credentials(
+    app_handle: AppHandle,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+) -> Result<(), CommandError> {
+    let name = ACTIVE
+        .read()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|profile| profile.name.clone())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    if let Some(client_id) = client_id.filter(|client_id| !client_id.trim().is_empty()) {
+        platform::keychain_set(&keychain_account(&name, CLIENT_ID_ENTRY), client_id.trim())?;
+    }
+    // Public clients have no secret to save
+    if let Some(client_secret) = client_secret.filter(|client_secret| !client_secret.trim().is_empty()) {
+        platform::keychain_set(&keychain_account(&name, CLIENT_SECRET_ENTRY), client_secret.trim())?;
+    }
+    log::info!("Saved client credentials for the {} profile to the keychain", name);
+    crate::preflight::sync_configured_state(&app_handle);
+    Ok(())
+}
+
+// Saves the passphrase of the active profile's client certificate in the OS keychain; the client is rebuilt
+// with it right away
+#[command(unlocked)]
+pub fn save_client_certificate_passphrase(app_handle: AppHandle, passphrase: String) -> Result<(), CommandError> {
+    let name = ACTIVE
+        .read()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|profile| profile.name.clone())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    platform::keychain_set(&keychain_account(&name, CERTIFICATE_PASSPHRASE_ENTRY), &passphrase)?;
+    log::info!("Saved the client certificate passphrase for the {} profile to the keychain", name);
+    activate(&app_handle)?;
+    Ok(())
+}
+
+// Keychain entries saved for any profile, plus the device binding key and the proxy password, with what each
+// holds; for the data inventory and the wipe
+pub fn saved_keychain_entries(app_handle: &AppHandle) -> Vec<(String, String)> {
+    let mut names: Vec<String> = load_profiles(app_handle)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|profile| profile.name)
+        .collect();
+    if !names.iter().any(|name| name == DEFAULT_PROFILE) {
+        names.push(DEFAULT_PROFILE.to_string());
+    }
+    names
+        .iter()
+        .flat_map(|name| {
+            [
+                (CLIENT_ID_ENTRY, "Client id"),
+                (CLIENT_SECRET_ENTRY, "Client secret"),
+                (CERTIFICATE_PASSPHRASE_ENTRY, "Client certificate passphrase"),
+                (REFRESH_TOKEN_ENTRY, "Saved session"),
+                (PENDING_REFRESH_TOKEN_ENTRY, "Saved session being renewed"),
+            ]
+            .into_iter()
+            .map(move |(entry, label)| (keychain_account(name, entry), format!("{} of the {} profile", label, name)))
+        })
+        .chain([
+            (crate::device::KEYCHAIN_ACCOUNT.to_string(), "Device binding key".to_string()),
+            (crate::network::PROXY_PASSWORD_ACCOUNT.to_string(), "Proxy password".to_string()),
+        ])
+        .filter(|(account, _)| matches!(platform::keychain_get(account), Ok(Some(_))))
+        .collect()
+}
+
+fn delete_keychain_entries(name: &str) -> Result<(), String> {
+    for entry in [
+        CLIENT_ID_ENTRY,
+        CLIENT_SECRET_ENTRY,
+        CERTIFICATE_PASSPHRASE_ENTRY,
+        REFRESH_TOKEN_ENTRY,
+        PENDING_REFRESH_TOKEN_ENTRY,
+    ] {
+        platform::keychain_delete(&keychain_account(name, entry))?;
+    }
+    Ok(())
+}
+
+fn secret_var(profile: Option<&Profile>) -> String {
+    profile
+        .and_then(|profile| profile.client_secret_env.clone())
+        .unwrap_or_else(|| CLIENT_SECRET_VAR.to_string())
+}
+
+pub(crate) fn keychain_account(name: &str, entry: &str) -> String {
+    format!("{}/{}", name, entry)
+}
+
+// Client id and secret saved for the named profile, each None when not saved
+fn keychain_credentials(name: &str) -> Result<(Option<String>, Option<String>), String> {
+    Ok((
+        platform::keychain_get(&keychain_account(name, CLIENT_ID_ENTRY))?,
+        platform::keychain_get(&keychain_account(name, CLIENT_SECRET_ENTRY))?,
+    ))
+}
+
+// None when `OCI_CLIENT_SECRET_FILE` is unset
+fn vault_secret() -> Result<Option<String>, String> {
+    let Ok(path) = std::env::var(CLIENT_SECRET_FILE_VAR) else {
+        return Ok(None);
+    };
+    let secret = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(Some(secret.trim().to_string()).filter(|secret| !secret.is_empty()))
+}
+
+// Space-separated, as the token endpoint expects
+pub(crate) fn scope() -> String {
+    ACTIVE
+        .read()
+        .ok()
+        .and_then(|active| active.as_ref().map(|profile| profile.scopes.join(" ")))
+        .filter(|scope| !scope.is_empty())
+        .unwrap_or_else(|| DEFAULT_SCOPE.to_string())
+}
+
+// Scope for user tokens: `scope`, plus `offline_access` when the profile uses refresh tokens
+pub(crate) fn user_scope() -> String {
+    let scope = scope();
+    let refresh_tokens = ACTIVE
+        .read()
+        .ok()
+        .is_some_and(|active| active.as_ref().is_some_and(|profile| profile.refresh_tokens));
+    if !refresh_tokens || scope.split(' ').any(|scope| scope == OFFLINE_ACCESS_SCOPE) {
+        return scope;
+    }
+    format!("{} {}", scope, OFFLINE_ACCESS_SCOPE)
+}
+
+pub fn active_profile(app_handle: &AppHandle) -> Result<Profile, String> {
+    let active = active_profile_name(app_handle)?;
+    let profiles = load_profiles(app_handle)?;
+    Ok(find(&profiles, &active)
+        .or_else(|| find(&profiles, DEFAULT_PROFILE))
+        .unwrap_or_else(|| Profile::named(DEFAULT_PROFILE)))
+}
+
+// Applies `update` to the named profile and saves it; the default profile is created on first use
+pub fn update_profile(app_handle: &AppHandle, name: &str, update: impl FnOnce(&mut Profile)) -> Result<(), String> {
+    let mut profiles = load_profiles(app_handle)?;
+    let index = match profiles.iter().position(|profile| profile.name == name) {
+        Some(index) => index,
+        None if name == DEFAULT_PROFILE => {
+            profiles.push(Profile::named(DEFAULT_PROFILE));
+            profiles.len() - 1
+        }
+        None => return Err(format!("Unknown profile: {}", name)),
+    };
+    update(&mut profiles[index]);
+    save_profiles(app_handle, &profiles)?;
+    activate(app_handle)
+}
+
+fn validate(mut profile: Profile) -> Result<Profile, String> {
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err("Profile name can't be empty".to_string());
+    }
+    profile.idcs_urls = validate_urls(&profile.idcs_urls)?;
+    profile.client_id = profile.client_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty());
+    profile.client_secret_env = profile
+        .client_secret_env
+        .map(|var| var.trim().to_string())
+        .filter(|var| !var.is_empty());
+    profile.scopes.retain(|scope| !scope.trim().is_empty());
+    profile.redirect_uri = profile
+        .redirect_uri
+        .map(|uri| uri.trim().to_string())
+        .filter(|uri| !uri.is_empty());
+    if let Some(uri) = &profile.redirect_uri {
+        reqwest::Url::parse(uri).map_err(|e| format!("Invalid redirect URI {}: {}", uri, e))?;
+    }
+    let trimmed = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+    profile.private_key_file = trimmed(profile.private_key_file);
+    profile.private_key_id = trimmed(profile.private_key_id);
+    profile.client_certificate_file = trimmed(profile.client_certificate_file);
+    match (&profile.private_key_file, &profile.private_key_id) {
+        (Some(_), None) => return Err("A private key needs the alias of its certificate in IDCS".to_string()),
+        (None, Some(_)) => return Err("A certificate alias needs the private key file".to_string()),
+        (Some(_), Some(_)) if profile.public_client => {
+            return Err("Public clients can't authenticate with a private key".to_string())
+        }
+        _ => {}
+    }
+    profile.assertion_audience = trimmed(profile.assertion_audience);
+    profile.assertion_issuer = trimmed(profile.assertion_issuer);
+    if profile.private_key_file.is_none() && (profile.assertion_audience.is_some() || profile.assertion_issuer.is_some()) {
+        return Err("An assertion audience or issuer needs a private key".to_string());
+    }
+    profile.token_path = trimmed(profile.token_path);
+    if let Some(path) = &profile.token_path {
+        if !path.starts_with('/') || path.contains(['?', '#']) {
+            return Err(format!("Invalid token path {}: use a path such as /oauth2/v1/token", path));
+        }
+    }
+    Ok(profile)
+}
+
+fn validate_urls(urls: &[String]) -> Result<Vec<String>, String> {
+    urls.iter()
+        .filter(|url| !url.trim().is_empty())
+        .map(|url| validate_idcs_url(url))
+        .collect()
+}
+
+fn active_profile_name(app_handle: &AppHandle) -> Result<String, String> {
+    let store = StoreBuilder::new(app_handle, profiles_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(store
+        .get("active")
+        .and_then(|value| value.as_str().map(|name| name.to_string()))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+}
+
+fn save_profiles(app_handle: &AppHandle, profiles: &[Profile]) -> Result<(), String> {
+    let store = StoreBuilder::new(app_handle, profiles_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    store.set("profiles", json!(profiles));
+    store.save().map_err(|e| e.to_string())
+}
+
+fn load_profiles(app_handle: &AppHandle) -> Result<Vec<Profile>, String> {
+    let store = StoreBuilder::new(app_handle, profiles_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(store
+        .get("profiles")
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default())
+}
+
+fn find(profiles: &[Profile], name: &str) -> Option<Profile> {
+    profiles
+        .iter()
+        .find(|profile| profile.name == name)
+        .cloned()
+        .or_else(|| (name == DEFAULT_PROFILE).then(|| Profile::named(DEFAULT_PROFILE)))
+}
+
+fn profiles_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(PROFILES_STORE_FILE))
+}
@@ -0,0 +1,157 @@
+use reqwest::Url;
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpStream;
+
+use crate::demo::DemoState;
+use crate::endpoints;
+use crate::error::CommandError;
+use crate::middleware::command;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Enough to tell a broken route from one bad address
+const ADDRESSES_PER_FAMILY: usize = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FamilyCheck {
+    pub addresses: Vec<String>,
+    pub connected: bool,
+    pub connect_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReachabilityReport {
+    pub url: String,
+    pub host: String,
+    pub ipv4: FamilyCheck,
+    pub ipv6: FamilyCheck,
+    // At least one family connects
+    pub reachable: bool,
+    // What to fix, in plain words; empty when both stacks behave
+    pub guidance: Vec<String>,
+}
+
+// Resolves every configured IDCS URL and connects to it over IPv4 and IPv6 separately, so a broken
+// stack shows up as such rather than as an opaque request error
+#[command]
+pub async fn check_ipv6_reachability() -> Result<Vec<ReachabilityReport>, CommandError> {
+    let mut reports = Vec::new();
+    for url in endpoints::urls() {
+        reports.push(check(&url).await?);
+    }
+    Ok(reports)
+}
+
+// Runs once after startup and warns when the tenant can't be reached over the available stack
+pub async fn check_at_startup(app_handle: AppHandle) {
+    if app_handle.state::<DemoState>().enabled {
+        return;
+    }
+    let reports = match check_ipv6_reachability().await {
+        Ok(reports) => reports,
+        Err(e) => {
+            log::warn!("Network check failed: {}", e);
+            return;
+        }
+    };
+    for report in reports.iter().filter(|report| !report.guidance.is_empty()) {
+        for guidance in &report.guidance {
+            log::warn!("{}: {}", report.host, guidance);
+        }
+        crate::bus::emit(&app_handle, "network-check-warning", report);
+    }
+}
+
+async fn check(url: &str) -> Result<ReachabilityReport, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid IDCS URL {}: {}", url, e))?;
+    let host = parsed.host_str().ok_or_else(|| format!("No host in {}", url))?.to_string();
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let (addresses, resolve_error) = match tokio::net::lookup_host((host.as_str(), port)).await {
+        Ok(addresses) => (addresses.collect::<Vec<SocketAddr>>(), None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    let (v4, v6): (Vec<SocketAddr>, Vec<SocketAddr>) = addresses.into_iter().partition(|address| address.is_ipv4());
+    let ipv4 = connect_family(&v4).await;
+    let ipv6 = connect_family(&v6).await;
+
+    let guidance = guidance(&host, resolve_error.as_deref(), &ipv4, &ipv6);
+    Ok(ReachabilityReport {
+        url: url.to_string(),
+        reachable: ipv4.connected || ipv6.connected,
+        host,
+        ipv4,
+        ipv6,
+        guidance,
+    })
+}
+
+async fn connect_family(addresses: &[SocketAddr]) -> FamilyCheck {
+    let mut error = None;
+    for address in addresses.iter().take(ADDRESSES_PER_FAMILY) {
+        let started = Instant::now();
+        match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address)).await {
+            Ok(Ok(_)) => {
+                return FamilyCheck {
+                    addresses: addresses.iter().map(|address| address.ip().to_string()).collect(),
+                    connected: true,
+                    connect_ms: Some(started.elapsed().as_millis() as u64),
+                    error: None,
+                }
+            }
+            Ok(Err(e)) => error = Some(format!("{}: {}", address.ip(), e)),
+            Err(_) => error = Some(format!("{}: timed out after {}s", address.ip(), CONNECT_TIMEOUT.as_secs())),
+        }
+    }
+    FamilyCheck {
+        addresses: addresses.iter().map(|address| address.ip().to_string()).collect(),
+        connected: false,
+        connect_ms: None,
+        error,
+    }
+}
+
+fn guidance(host: &str, resolve_error: Option<&str>, ipv4: &FamilyCheck, ipv6: &FamilyCheck) -> Vec<String> {
+    let has_v4 = !ipv4.addresses.is_empty();
+    let has_v6 = !ipv6.addresses.is_empty();
+
+    if let Some(e) = resolve_error {
+        return vec![format!(
+            "{} could not be resolved ({}). Check your DNS server or VPN; on an IPv6-only network the DNS server must provide DNS64.",
+            host, e
+        )];
+    }
+    if !has_v4 && !has_v6 {
+        return vec![format!("{} resolved to no addresses. Check your DNS server or VPN.", host)];
+    }
+    if !ipv4.connected && !ipv6.connected {
+        return vec![if has_v6 {
+            format!("{} can't be reached over IPv4 or IPv6. Check your firewall, proxy or VPN.", host)
+        } else {
+            format!(
+                "{} only has IPv4 addresses and they can't be reached. On an IPv6-only network, NAT64 and DNS64 are needed; otherwise check your firewall, proxy or VPN.",
+                host
+            )
+        }];
+    }
+
+    let mut guidance = Vec::new();
+    // The common dual-stack fault: AAAA records resolve but the IPv6 route is broken, so every
+    // connection waits for the IPv6 attempt to fail before falling back
+    if has_v6 && !ipv6.connected {
+        guidance.push(format!(
+            "{} resolves to IPv6 addresses but IPv6 connections fail, which slows down or breaks sign-in. Fix the network's IPv6 route or turn off IPv6 on this connection.",
+            host
+        ));
+    }
+    if has_v4 && !ipv4.connected {
+        guidance.push(format!(
+            "IPv4 connections to {} fail and only IPv6 works. This is fine on an IPv6-only network; otherwise check your firewall or VPN.",
+            host
+        ));
+    }
+    guidance
+}
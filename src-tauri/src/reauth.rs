@@ -0,0 +1,164 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+use tokio::sync::oneshot;
+
+use crate::auth::{idcs_complete_auth, idcs_initiate_auth, idcs_submit_otp, select_preferred_factor, AuthResponse};
+use crate::auth_client::AuthClient;
+use crate::config::AuthFactor;
+use crate::demo::DemoState;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::policy::{self, PolicyState};
+
+// Must match the window listed in capabilities/reauth.json
+pub const REAUTH_WINDOW: &str = "reauth";
+
+pub struct PendingReauth {
+    pub reason: String,
+    responder: oneshot::Sender<bool>,
+}
+
+#[derive(Default)]
+pub struct ReauthState(pub Mutex<Option<PendingReauth>>);
+
+// Opens the re-authentication window and resolves once it is verified (true) or dismissed (false)
+#[command]
+pub async fn prompt_reauth(
+    app_handle: AppHandle,
+    reauth_state: State<'_, ReauthState>,
+    reason: String,
+) -> Result<bool, CommandError> {
+    let (responder, verified) = oneshot::channel();
+    {
+        let mut pending = reauth_state.0.lock().map_err(|e| e.to_string())?;
+        if pending.is_some() {
+            return Err("A re-authentication prompt is already open".to_string().into());
+        }
+        *pending = Some(PendingReauth { reason, responder });
+    }
+
+    if let Err(e) = open_reauth_window(&app_handle) {
+        reauth_state.0.lock().map_err(|e| e.to_string())?.take();
+        return Err(e.into());
+    }
+
+    Ok(verified.await.unwrap_or(false))
+}
+
+#[command]
+pub fn get_reauth_reason(window: WebviewWindow, reauth_state: State<ReauthState>) -> Result<String, CommandError> {
+    ensure_reauth_window(&window)?;
+    let pending = reauth_state.0.lock().map_err(|e| e.to_string())?;
+    pending
+        .as_ref()
+        .map(|pending| pending.reason.clone())
+        .ok_or_else(|| "No re-authentication in progress".into())
+}
+
+// Password step; a "pending" status means the prompt must follow up with `confirm_reauth`
+#[command]
+pub async fn submit_reauth(
+    app_handle: AppHandle,
+    window: WebviewWindow,
+    demo: State<'_, DemoState>,
+    username: String,
+    password: String,
+) -> Result<AuthResponse, CommandError> {
+    ensure_reauth_window(&window)?;
+    policy::check_allowed_hours(&app_handle.state::<PolicyState>().0)?;
+    let response = if demo.enabled {
+        demo.initiate(&username, &password)?
+    } else {
+        let response = idcs_initiate_auth(&app_handle.state::<AuthClient>(), username, password).await?;
+        select_preferred_factor(&app_handle, response).await.0
+    };
+
+    if response.status == "success" {
+        resolve_reauth(&app_handle, true);
+        let _ = window.close();
+    }
+    Ok(response)
+}
+
+// Second factor step, completing the request state returned by `submit_reauth`; TOTP users pass their passcode
+#[command]
+pub async fn confirm_reauth(
+    app_handle: AppHandle,
+    window: WebviewWindow,
+    demo: State<'_, DemoState>,
+    request_state: String,
+    otp_code: Option<String>,
+) -> Result<(), CommandError> {
+    ensure_reauth_window(&window)?;
+    let request_state = match otp_code {
+        Some(otp_code) if demo.enabled => demo.submit_otp(&request_state, AuthFactor::Totp, &otp_code)?.request_state,
+        Some(otp_code) => {
+            idcs_submit_otp(&app_handle.state::<AuthClient>(), AuthFactor::Totp, &request_state, &otp_code)
+                .await?
+                .request_state
+        }
+        None => request_state,
+    };
+    if demo.enabled {
+        demo.complete(&request_state)?;
+    } else {
+        idcs_complete_auth(&app_handle, &app_handle.state::<AuthClient>(), request_state).await?;
+    }
+
+    resolve_reauth(&app_handle, true);
+    let _ = window.close();
+    Ok(())
+}
+
+// A dedicated window so the prompt shows even when the main window is hidden or in the tray
+fn open_reauth_window(app_handle: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(REAUTH_WINDOW) {
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+
+    let window = WebviewWindowBuilder::new(app_handle, REAUTH_WINDOW, WebviewUrl::App("reauth".into()))
+        .title("Confirm your identity")
+        .inner_size(380.0, 280.0)
+        .resizable(false)
+        .minimizable(false)
+        .maximizable(false)
+        .always_on_top(true)
+        .content_protected(crate::platform::screen_capture_blocked(app_handle))
+        .skip_taskbar(true)
+        .focused(true)
+        .center()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Closing the window without verifying counts as a cancellation
+    let handle = app_handle.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            resolve_reauth(&handle, false);
+        }
+    });
+
+    log::info!("Re-authentication prompt opened");
+    Ok(())
+}
+
+fn resolve_reauth(app_handle: &AppHandle, verified: bool) {
+    let pending = app_handle
+        .state::<ReauthState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take());
+
+    if let Some(pending) = pending {
+        log::info!("Re-authentication {}", if verified { "verified" } else { "cancelled" });
+        let _ = pending.responder.send(verified);
+    }
+}
+
+fn ensure_reauth_window(window: &WebviewWindow) -> Result<(), String> {
+    if window.label() != REAUTH_WINDOW {
+        return Err("Only available to the re-authentication prompt".to_string());
+    }
+    Ok(())
+}
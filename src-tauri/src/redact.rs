@@ -0,0 +1,145 @@
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+pub const REDACTED: &str = "[redacted]";
+
+// Fields that hold credentials, compared in lower case without `_` or `-`, so `authnToken` and `authn_token` both
+// match. Answers and passcodes are as good as a password for the step they belong to.
+const SECRET_FIELDS: &[&str] = &[
+    "accesstoken",
+    "refreshtoken",
+    "idtoken",
+    "authntoken",
+    "requeststate",
+    "password",
+    "clientsecret",
+    "clientassertion",
+    "assertion",
+    "devicecode",
+    "otpcode",
+    "passcode",
+    "bypasscode",
+    "answer",
+    "secret",
+];
+
+const SECRET_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie", "set-cookie", "dpop"];
+
+fn is_secret_field(key: &str) -> bool {
+    let key: String = key
+        .chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    SECRET_FIELDS.contains(&key.as_str())
+}
+
+// Masks the secret fields of `value` in place, at any depth
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_secret_field(key) && !field.is_null() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+// Logs as the JSON of the wrapped value with its secret fields masked, e.g. `log::debug!("{}", Redacted(&body))`
+pub struct Redacted<'a, T: Serialize>(pub &'a T);
+
+impl<T: Serialize> fmt::Display for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self.0) {
+            Ok(mut value) => {
+                redact(&mut value);
+                write!(f, "{}", value)
+            }
+            Err(_) => f.write_str(REDACTED),
+        }
+    }
+}
+
+// A response body fit for the log. Bodies that aren't JSON, such as a proxy's HTML error page, are only
+// described, since there is no telling what they contain.
+pub fn body(text: &str) -> String {
+    match serde_json::from_str::<Value>(text) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes, not JSON>", text.len()),
+    }
+}
+
+// Headers fit for the log, with credentials and cookies masked
+pub fn headers(headers: &HeaderMap) -> String {
+    let entries: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                REDACTED
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect();
+    entries.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+    use serde_json::json;
+
+    #[test]
+    fn redact_masks_secret_fields_at_any_depth() {
+        let mut value = json!({
+            "access_token": "a",
+            "authnToken": "b",
+            "user": { "name": "jdoe", "Password": "c" },
+            "steps": [{ "otp-code": "123456" }]
+        });
+        redact(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "access_token": REDACTED,
+                "authnToken": REDACTED,
+                "user": { "name": "jdoe", "Password": REDACTED },
+                "steps": [{ "otp-code": REDACTED }]
+            })
+        );
+    }
+
+    #[test]
+    fn redact_leaves_null_secrets_and_other_fields() {
+        let mut value = json!({ "refresh_token": null, "expires_in": 3600, "token_type": "Bearer" });
+        redact(&mut value);
+        assert_eq!(value, json!({ "refresh_token": null, "expires_in": 3600, "token_type": "Bearer" }));
+    }
+
+    #[test]
+    fn body_describes_text_that_isnt_json() {
+        assert_eq!(body("{\"requestState\":\"s\"}"), "{\"requestState\":\"[redacted]\"}");
+        assert_eq!(body("<html>password</html>"), "<21 bytes, not JSON>");
+    }
+
+    #[test]
+    fn headers_masks_credentials() {
+        let mut map = HeaderMap::new();
+        map.insert(AUTHORIZATION, HeaderValue::from_static("Bearer token"));
+        map.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert_eq!(headers(&map), "authorization: [redacted], content-type: application/json");
+    }
+}
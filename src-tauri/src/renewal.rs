@@ -0,0 +1,290 @@
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::auth;
+use crate::auth_client::AuthClient;
+use crate::demo::DemoState;
+use crate::dpop;
+use crate::error::{AuthError, CommandError};
+use crate::lifecycle::{self, AppState};
+use crate::maintenance;
+use crate::middleware::command;
+use crate::profiles;
+use crate::session::{ScopeGrant, Session, SessionState};
+use crate::supervisor;
+use crate::tenant::ScimError;
+use crate::token_manager::TokenManager;
+
+const RENEWAL_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+// Renew this long before the access token runs out, leaving room for a retry if IDCS is slow
+const RENEW_BEFORE: chrono::Duration = chrono::Duration::minutes(5);
+
+// Payload of `session-refreshed`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRefreshed {
+    pub expires_at: DateTime<Utc>,
+}
+
+// Outcome of the renewal in flight, None until it finishes
+type Outcome = Option<Result<SessionRefreshed, AuthError>>;
+
+// The renewal in flight, if any. The frontend, the background renewal and a retry after a 401 can all ask for one
+// at the same moment; they share it instead of each redeeming the refresh token.
+#[derive(Default)]
+pub struct RenewalState(Mutex<Option<watch::Receiver<Outcome>>>);
+
+// Renews the session's access token with its refresh token now, e.g. to leave the expired state without signing
+// in again
+#[command]
+pub async fn refresh_session(app_handle: AppHandle) -> Result<SessionRefreshed, CommandError> {
+    Ok(refresh(&app_handle).await?)
+}
+
+// Joins the renewal in flight, or starts one. It runs on its own task, so it completes even when the caller that
+// started it goes away.
+pub(crate) async fn refresh(app_handle: &AppHandle) -> Result<SessionRefreshed, AuthError> {
+    let renewal = app_handle.state::<RenewalState>();
+    let (mut receiver, sender) = {
+        let mut in_flight = renewal.0.lock().map_err(|e| e.to_string())?;
+        // A closed channel means the renewal's task died without an outcome
+        match in_flight.as_ref().filter(|receiver| receiver.has_changed().is_ok()) {
+            Some(receiver) => (receiver.clone(), None),
+            None => {
+                let (sender, receiver) = watch::channel(None);
+                *in_flight = Some(receiver.clone());
+                (receiver, Some(sender))
+            }
+        }
+    };
+    if let Some(sender) = sender {
+        let handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let outcome = renew(&handle).await;
+            if let Ok(mut in_flight) = handle.state::<RenewalState>().0.lock() {
+                *in_flight = None;
+            }
+            let _ = sender.send(Some(outcome));
+        });
+    }
+
+    let outcome = receiver.wait_for(Option::is_some).await.ok().and_then(|outcome| outcome.clone());
+    outcome.unwrap_or_else(|| Err("The session renewal was interrupted".into()))
+}
+
+// Renews the session after an IDCS call with its access token failed with 401, when it has a refresh token;
+// true when the call is worth retrying
+pub(crate) async fn renew_after_unauthorized(app_handle: &AppHandle, error: &ScimError) -> bool {
+    if error.status != Some(StatusCode::UNAUTHORIZED) {
+        return false;
+    }
+    let renewable = app_handle
+        .state::<SessionState>()
+        .0
+        .lock()
+        .is_ok_and(|session| session.as_ref().is_some_and(|session| session.refresh_token.is_some()));
+    if !renewable {
+        return false;
+    }
+    match refresh(app_handle).await {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("Failed to renew the session after a 401: {}", e);
+            false
+        }
+    }
+}
+
+// Resumes the session saved by the last run; once, at startup
+pub async fn resume_saved_session(app_handle: AppHandle) {
+    if let Err(e) = resume(&app_handle).await {
+        log::warn!("Failed to resume the saved session: {}", e);
+    }
+}
+
+// Renews sessions that have a refresh token shortly before they expire; sessions without one expire as before
+pub async fn renew_sessions(app_handle: AppHandle) {
+    loop {
+        supervisor::alive(&app_handle, RENEWAL_CHECK_INTERVAL);
+        tokio::time::sleep(RENEWAL_CHECK_INTERVAL).await;
+        let due = app_handle
+            .state::<SessionState>()
+            .0
+            .lock()
+            .ok()
+            .and_then(|session| {
+                let session = session.as_ref()?;
+                session.refresh_token.as_ref()?;
+                session.expires_at
+            })
+            .is_some_and(|expires_at| expires_at - RENEW_BEFORE <= Utc::now());
+        if !due || maintenance::in_progress(&app_handle) {
+            continue;
+        }
+        if let Err(e) = refresh(&app_handle).await {
+            log::warn!("Failed to renew the session: {}", e);
+        }
+    }
+}
+
+async fn renew(app_handle: &AppHandle) -> Result<SessionRefreshed, AuthError> {
+    // The background renewal and `refresh_session` must not redeem the same token
+    let manager = app_handle.state::<TokenManager>();
+    let _rotation = manager.begin_rotation().await;
+    let session_state = app_handle.state::<SessionState>();
+    let (refresh_token, dpop_key) = {
+        let session = session_state.0.lock().map_err(|e| e.to_string())?;
+        let session = session.as_ref().ok_or("Not signed in")?;
+        let refresh_token = session
+            .refresh_token
+            .clone()
+            .ok_or("This session can't be renewed; sign in again")?;
+        (refresh_token, session.dpop_key.clone())
+    };
+
+    let client = app_handle.state::<AuthClient>();
+    let token_response = match auth::refresh_user_token(&client, &refresh_token, dpop_key.as_deref()).await {
+        Ok(token_response) => token_response,
+        // Stop retrying a token that won't work again
+        Err(e) => {
+            if spent(&e) {
+                forget_refresh_token(&session_state, &refresh_token);
+                forget_saved(app_handle);
+            }
+            return Err(e);
+        }
+    };
+    if let Some(key) = &dpop_key {
+        key.bind(&token_response.access_token);
+    }
+    // Saved before the session switches to it, so a restart never finds the spent one; `remember` deletes that when
+    // the rotated one can't be saved
+    if let Some(rotated) = &token_response.refresh_token {
+        remember(app_handle, rotated);
+    }
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64);
+    {
+        let mut session = session_state.0.lock().map_err(|e| e.to_string())?;
+        // Signed out or signed in again while the request was in flight
+        let Some(session) = session
+            .as_mut()
+            .filter(|session| session.refresh_token.as_deref() == Some(refresh_token.as_str()))
+        else {
+            return Err("The session changed while it was being renewed".into());
+        };
+        session.access_token = Some(token_response.access_token);
+        session.expires_at = Some(expires_at);
+        if let Some(rotated) = token_response.refresh_token {
+            session.refresh_token = Some(rotated);
+        }
+        // Renewal keeps the original request; only a reported scope can change the grant
+        if let (Some(scopes), Some(granted)) = (&mut session.scopes, token_response.scope.as_deref()) {
+            *scopes = ScopeGrant::new(&scopes.requested.join(" "), Some(granted));
+        }
+    }
+
+    if lifecycle::current(app_handle) == AppState::Expired {
+        lifecycle::transition(app_handle, AppState::LoggedIn);
+    }
+    let refreshed = SessionRefreshed { expires_at };
+    crate::bus::emit(app_handle, "session-refreshed", &refreshed);
+    log::info!("Renewed the session until {}", expires_at);
+    Ok(refreshed)
+}
+
+fn forget_refresh_token(session_state: &SessionState, refresh_token: &str) {
+    if let Ok(mut session) = session_state.0.lock() {
+        if let Some(session) = session
+            .as_mut()
+            .filter(|session| session.refresh_token.as_deref() == Some(refresh_token))
+        {
+            session.refresh_token = None;
+        }
+    }
+}
+
+// Signs in with the refresh token saved by the last run, when the active profile resumes sessions
+async fn resume(app_handle: &AppHandle) -> Result<(), AuthError> {
+    let Some(profile_name) = profiles::resumable_sessions() else {
+        return Ok(());
+    };
+    if app_handle.state::<DemoState>().enabled {
+        return Ok(());
+    }
+    let manager = app_handle.state::<TokenManager>();
+    let _rotation = manager.begin_rotation().await;
+    let Some(refresh_token) = manager.load(&profile_name)? else {
+        return Ok(());
+    };
+
+    let client = app_handle.state::<AuthClient>();
+    let token_response = match auth::refresh_user_token(&client, &refresh_token, None).await {
+        Ok(token_response) => token_response,
+        Err(e) => {
+            if spent(&e) {
+                manager.clear(&profile_name)?;
+            }
+            return Err(e);
+        }
+    };
+    let refresh_token = match token_response.refresh_token {
+        Some(rotated) => {
+            remember(app_handle, &rotated);
+            rotated
+        }
+        None => refresh_token,
+    };
+
+    let authorization = dpop::authorization(&token_response.access_token, None);
+    let profile = auth::get_user_profile(app_handle, &client, &authorization).await?;
+    auth::finish_sign_in(
+        app_handle,
+        Session {
+            profile,
+            expires_at: Some(Utc::now() + chrono::Duration::seconds(token_response.expires_in as i64)),
+            access_token: Some(token_response.access_token),
+            client_ip: None,
+            dpop_key: None,
+            scopes: Some(ScopeGrant::new(&profiles::user_scope(), token_response.scope.as_deref())),
+            refresh_token: Some(refresh_token),
+        },
+    )?;
+    log::info!("Resumed the saved session of the {} profile", profile_name);
+    Ok(())
+}
+
+// Saves a session's refresh token for the next run, when the active profile resumes sessions. When it can't be
+// saved, the one saved before is deleted instead: after a rotation IDCS rejects it, or reports it as reused.
+pub(crate) fn remember(app_handle: &AppHandle, refresh_token: &str) {
+    let Some(profile_name) = profiles::resumable_sessions().filter(|_| !crate::guest::is_active(app_handle)) else {
+        return;
+    };
+    let manager = app_handle.state::<TokenManager>();
+    if let Err(e) = manager.save(&profile_name, refresh_token) {
+        log::warn!("Failed to save the refresh token, the next start will need a sign-in: {}", e);
+        if let Err(e) = manager.clear(&profile_name) {
+            log::error!("Failed to delete the saved refresh token, the next start may resume a spent one: {}", e);
+        }
+    }
+}
+
+// A guest's was never saved, and the one saved before belongs to someone else
+pub(crate) fn forget_saved(app_handle: &AppHandle) {
+    let Some(profile_name) = profiles::resumable_sessions().filter(|_| !crate::guest::is_active(app_handle)) else {
+        return;
+    };
+    if let Err(e) = app_handle.state::<TokenManager>().clear(&profile_name) {
+        log::warn!("Failed to delete the saved refresh token: {}", e);
+    }
+}
+
+// A revoked or expired refresh token won't start working again. Only an OAuth error from the token endpoint says
+// so: a bodyless status, a server error or a response that couldn't be read leaves the token as it was.
+fn spent(e: &AuthError) -> bool {
+    matches!(e, AuthError::InvalidCredentials { code: Some(_), .. } | AuthError::Rejected { code: Some(_), .. })
+}
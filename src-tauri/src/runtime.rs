@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::cache::CacheState;
+use crate::config::ConfigState;
+use crate::error::CommandError;
+use crate::middleware::{self, command, CommandStats};
+use crate::supervisor::{SupervisorState, TaskStatus};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(600);
+const TOP_CONSUMERS: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeStats {
+    // Resident set size of this process; None where it couldn't be read
+    pub rss_bytes: Option<u64>,
+    pub cache_entries: usize,
+    pub cache_bytes: usize,
+    pub max_cache_bytes: usize,
+    // Restarts and last failure of each supervised background task
+    pub background_tasks: Vec<TaskStatus>,
+    // Calls, failures and timings of each command the webview invoked
+    pub commands: Vec<CommandStats>,
+}
+
+#[command]
+pub fn get_runtime_stats(
+    config_state: State<ConfigState>,
+    cache: State<CacheState>,
+    supervisor: State<SupervisorState>,
+) -> Result<RuntimeStats, CommandError> {
+    let max_cache_mb = config_state.0.lock().performance.max_cache_mb;
+    let cache = cache.0.lock().map_err(|e| e.to_string())?;
+    Ok(RuntimeStats {
+        rss_bytes: process_rss(),
+        cache_entries: cache.len(),
+        cache_bytes: cache.size_bytes(),
+        max_cache_bytes: (max_cache_mb * 1024 * 1024) as usize,
+        background_tasks: supervisor.statuses(),
+        commands: middleware::stats(),
+    })
+}
+
+// Keeps long-running tray sessions in check by trimming the cache back under its budget
+pub async fn monitor_memory(app_handle: AppHandle) {
+    loop {
+        crate::supervisor::alive(&app_handle, CHECK_INTERVAL);
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let max_cache_bytes = app_handle
+            .state::<ConfigState>()
+            .0
+            .read(|config| (config.performance.max_cache_mb * 1024 * 1024) as usize);
+        let cache_state = app_handle.state::<CacheState>();
+        let Ok(mut cache) = cache_state.0.lock() else {
+            continue;
+        };
+
+        let cache_bytes = cache.size_bytes();
+        log::debug!(
+            "Runtime stats: rss={:?} cache_entries={} cache_bytes={}",
+            process_rss(),
+            cache.len(),
+            cache_bytes
+        );
+        if cache_bytes <= max_cache_bytes {
+            continue;
+        }
+
+        log::warn!(
+            "Cache uses {} bytes, over the {} byte limit; largest entries: {:?}",
+            cache_bytes,
+            max_cache_bytes,
+            cache.top_consumers(TOP_CONSUMERS)
+        );
+        // Trim below the limit so the next few inserts don't immediately trip it again
+        let removed = cache.trim_to(max_cache_bytes / 4 * 3);
+        log::info!("Trimmed {} cache entries", removed);
+    }
+}
+
+fn process_rss() -> Option<u64> {
+    let pid = std::process::id().to_string();
+    if cfg!(target_os = "linux") {
+        // "VmRSS:    123456 kB"
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    } else if cfg!(target_os = "windows") {
+        // CSV row whose last column is the working set, e.g. "123,456 K"
+        let output = Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let memory = stdout.trim().rsplit("\",\"").next()?;
+        let kb: u64 = memory.chars().filter(|c| c.is_ascii_digit()).collect::<String>().parse().ok()?;
+        Some(kb * 1024)
+    } else {
+        let output = Command::new("ps").args(["-o", "rss=", "-p", &pid]).output().ok()?;
+        let kb: u64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+        Some(kb * 1024)
+    }
+}
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+use crate::auth::{idcs_complete_session, idcs_initiate_auth, idcs_submit_otp, revoke_tokens};
+use crate::auth_client::AuthClient;
+use crate::config::AuthFactor;
+use crate::demo::DemoState;
+use crate::error::CommandError;
+use crate::middleware::command;
+use crate::session::Session;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScenarioProvider {
+    Mock,
+    Real,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScenarioAction {
+    Login,
+    Totp,
+    Complete,
+    // Revokes the tokens from the last `complete` step; the app's own session is left alone
+    Logout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expectation {
+    Success,
+    Pending,
+    Error,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioStep {
+    pub name: Option<String>,
+    pub action: ScenarioAction,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    // Read the password from this environment variable instead of the file
+    pub password_env: Option<String>,
+    // Passcode for `totp` steps
+    pub otp_code: Option<String>,
+    pub expect: Expectation,
+    // Substring the outcome message must contain
+    pub message_contains: Option<String>,
+    pub repeat: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default = "default_provider")]
+    pub provider: ScenarioProvider,
+    pub steps: Vec<ScenarioStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub index: usize,
+    pub name: String,
+    pub expected: Expectation,
+    pub outcome: Expectation,
+    pub message: String,
+    pub passed: bool,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub provider: ScenarioProvider,
+    pub passed: bool,
+    pub steps: Vec<StepResult>,
+}
+
+fn default_provider() -> ScenarioProvider {
+    ScenarioProvider::Mock
+}
+
+// Executes a YAML-described sequence of auth operations and reports pass/fail per step
+#[command]
+pub async fn run_scenario(app_handle: AppHandle, file: String) -> Result<ScenarioReport, CommandError> {
+    let contents = std::fs::read_to_string(&file).map_err(|e| format!("Failed to read scenario {}: {}", file, e))?;
+    let scenario: Scenario =
+        serde_yaml::from_str(&contents).map_err(|e| format!("Failed to parse scenario {}: {}", file, e))?;
+    log::info!("Running scenario '{}' against the {:?} provider", scenario.name, scenario.provider);
+
+    // Mock scenarios get a fresh provider so lockout counters don't leak between runs
+    let mock = DemoState::new(true);
+    let app_demo = app_handle.state::<DemoState>();
+    let demo = match scenario.provider {
+        ScenarioProvider::Mock => &mock,
+        ScenarioProvider::Real if app_demo.enabled => {
+            return Err("Real-provider scenarios cannot run while the app is in demo mode".to_string().into());
+        }
+        ScenarioProvider::Real => app_demo.inner(),
+    };
+
+    let mut request_state = String::new();
+    let mut session = None;
+    let mut results = Vec::new();
+    for step in &scenario.steps {
+        for _ in 0..step.repeat.unwrap_or(1).max(1) {
+            let started = Instant::now();
+            let (outcome, message) = match run_step(&app_handle, demo, step, &mut request_state, &mut session).await {
+                Ok(result) => result,
+                Err(e) => (Expectation::Error, e),
+            };
+            let passed = outcome == step.expect
+                && step.message_contains.as_ref().map(|m| message.contains(m.as_str())).unwrap_or(true);
+
+            let result = StepResult {
+                index: results.len() + 1,
+                name: step.name.clone().unwrap_or_else(|| format!("{:?}", step.action).to_lowercase()),
+                expected: step.expect,
+                outcome,
+                message,
+                passed,
+                elapsed_ms: started.elapsed().as_millis(),
+            };
+            log::info!(
+                "Step {} '{}': {} (expected {:?}, got {:?})",
+                result.index,
+                result.name,
+                if passed { "PASS" } else { "FAIL" },
+                result.expected,
+                result.outcome
+            );
+            results.push(result);
+        }
+    }
+
+    Ok(ScenarioReport {
+        name: scenario.name,
+        provider: scenario.provider,
+        passed: results.iter().all(|r| r.passed),
+        steps: results,
+    })
+}
+
+async fn run_step(
+    app_handle: &AppHandle,
+    demo: &DemoState,
+    step: &ScenarioStep,
+    request_state: &mut String,
+    session: &mut Option<Session>,
+) -> Result<(Expectation, String), String> {
+    match step.action {
+        ScenarioAction::Login => {
+            let username = step.username.clone().ok_or("Login steps require a username")?;
+            let password = match (&step.password, &step.password_env) {
+                (_, Some(var)) => std::env::var(var).map_err(|e| format!("{}: {}", var, e))?,
+                (Some(password), None) => password.clone(),
+                (None, None) => return Err("Login steps require a password or password_env".to_string()),
+            };
+            let response = if demo.enabled {
+                demo.initiate(&username, &password)?
+            } else {
+                idcs_initiate_auth(&app_handle.state::<AuthClient>(), username, password).await?
+            };
+            *request_state = response.request_state.clone();
+            let message = response.cause.first().map(|c| c.message.clone()).unwrap_or_default();
+            let outcome = if response.status == "success" { Expectation::Success } else { Expectation::Pending };
+            Ok((outcome, message))
+        }
+        ScenarioAction::Totp => {
+            let otp_code = step.otp_code.as_deref().ok_or("TOTP steps require an otp_code")?;
+            let response = if demo.enabled {
+                demo.submit_otp(request_state, AuthFactor::Totp, otp_code)?
+            } else {
+                idcs_submit_otp(&app_handle.state::<AuthClient>(), AuthFactor::Totp, request_state, otp_code).await?
+            };
+            *request_state = response.request_state.clone();
+            let message = response.cause.first().map(|c| c.message.clone()).unwrap_or_default();
+            let outcome = if response.status == "success" { Expectation::Success } else { Expectation::Pending };
+            Ok((outcome, message))
+        }
+        ScenarioAction::Complete => {
+            let state = std::mem::take(request_state);
+            let profile = if demo.enabled {
+                demo.complete(&state)?
+            } else {
+                let signed_in = idcs_complete_session(app_handle, &app_handle.state::<AuthClient>(), state).await?;
+                let profile = signed_in.profile.clone();
+                *session = Some(signed_in);
+                profile
+            };
+            let message = profile["userName"].as_str().unwrap_or_default().to_string();
+            Ok((Expectation::Success, message))
+        }
+        ScenarioAction::Logout => {
+            let user_tokens: Vec<String> = session
+                .take()
+                .map(|session| session.access_token.into_iter().chain(session.refresh_token).collect())
+                .unwrap_or_default();
+            if demo.enabled || user_tokens.is_empty() {
+                return Ok((Expectation::Success, "Signed out".to_string()));
+            }
+            let revoked = revoke_tokens(&app_handle.state::<AuthClient>(), user_tokens).await?;
+            Ok((Expectation::Success, format!("Revoked {} tokens", revoked)))
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::{Store, StoreBuilder};
+
+// Format version of a versioned store file; files from before versioning have none and count as 0
+const VERSION_KEY: &str = "schema_version";
+
+// Upgrades a store file by one format version, in place
+pub(crate) type Migration = fn(&AppHandle, &Store<Wry>) -> Result<(), String>;
+
+// Opens a store file whose current format is `migrations.len()`: `migrations[n]` upgrades format n to n + 1.
+// Missing upgrades run in order and are saved before the store is returned. A file from a newer version of the
+// app is refused, since writing it back in an older format would drop what the newer one added.
+pub(crate) fn open(app_handle: &AppHandle, path: &Path, migrations: &[Migration]) -> Result<Arc<Store<Wry>>, String> {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let store = StoreBuilder::new(app_handle, path).build().map_err(|e| e.to_string())?;
+    let current = migrations.len() as u64;
+    let version = store.get(VERSION_KEY).and_then(|value| value.as_u64()).unwrap_or(0);
+    if version > current {
+        return Err(format!(
+            "{} was written by a newer version of the app (format {}; this version reads up to {})",
+            name, version, current
+        ));
+    }
+    if version == current {
+        return Ok(store);
+    }
+
+    let created = store.is_empty();
+    for (from, migrate) in migrations.iter().enumerate().skip(version as usize) {
+        migrate(app_handle, &store).map_err(|e| format!("Failed to upgrade {} from format {}: {}", name, from, e))?;
+        store.set(VERSION_KEY, json!(from + 1));
+    }
+    store.save().map_err(|e| e.to_string())?;
+    if !created {
+        log::info!("Upgraded {} from format {} to {}", name, version, current);
+    }
+    Ok(store)
+}
+
+// For format 1 of files whose layout didn't change when versioning was added
+pub(crate) fn add_version(_app_handle: &AppHandle, _store: &Store<Wry>) -> Result<(), String> {
+    Ok(())
+}
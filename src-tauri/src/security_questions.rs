@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthResponse};
+use crate::auth_client::AuthClient;
+use crate::config::AuthFactor;
+use crate::demo::DemoState;
+use crate::error::{AuthError, CommandError};
+use crate::lifecycle;
+use crate::middleware::command;
+use crate::session::AuthSessionState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityQuestion {
+    // IDCS question id such as "MaidenName"; answers are matched to questions by it
+    pub id: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityQuestionsPrompt {
+    // The request state the answers are submitted with
+    pub request_state: String,
+    pub questions: Vec<SecurityQuestion>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityAnswer {
+    pub question_id: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecurityAnswersResult {
+    pub response: AuthResponse,
+    // Questions to answer again with `response.request_state` when some answers were wrong; empty on success
+    pub questions: Vec<SecurityQuestion>,
+    // Submitted question ids that were asked again
+    pub failed_question_ids: Vec<String>,
+}
+
+// Selects the security questions factor and returns the questions the user enrolled with
+#[command]
+pub async fn get_security_questions(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+) -> Result<SecurityQuestionsPrompt, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let prompt = if demo.enabled {
+        demo.security_questions(&request_state).map_err(AuthError::from)?
+    } else {
+        idcs_security_questions(&auth_client, &request_state).await?
+    };
+    auth::factor_started(&app_handle, AuthFactor::SecurityQuestions, &prompt.request_state);
+    Ok(prompt)
+}
+
+// Submits one answer per question; on success, finish with `complete_auth`
+#[command]
+pub async fn submit_security_answers(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    answers: Vec<SecurityAnswer>,
+) -> Result<SecurityAnswersResult, CommandError> {
+    validate_answers(&answers)?;
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let result = if demo.enabled {
+        demo.submit_security_answers(&request_state, &answers).map_err(AuthError::from)
+    } else {
+        idcs_submit_answers(&auth_client, &request_state, &answers).await
+    };
+    let mut result = result.inspect_err(|e| lifecycle::factor_failed(&app_handle, e))?;
+    auth::factor_submitted(&app_handle, AuthFactor::SecurityQuestions, &result.response);
+    if !result.failed_question_ids.is_empty() {
+        log::info!("{} security answers were rejected", result.failed_question_ids.len());
+    }
+
+    if crate::clipboard::tokens_protected(&app_handle) {
+        result.response.authn_token = result.response.authn_token.as_deref().map(crate::clipboard::mask_token);
+    }
+    Ok(result)
+}
+
+// Every answer needs a question id, an answer and no repeats; IDCS rejects the whole step otherwise
+fn validate_answers(answers: &[SecurityAnswer]) -> Result<(), String> {
+    if answers.is_empty() {
+        return Err("Answer the security questions to continue".to_string());
+    }
+    let mut seen = HashSet::new();
+    for answer in answers {
+        if answer.question_id.trim().is_empty() {
+            return Err("Each answer needs the id of its question".to_string());
+        }
+        if answer.answer.trim().is_empty() {
+            return Err(format!("The answer to {} is empty", answer.question_id));
+        }
+        if !seen.insert(answer.question_id.as_str()) {
+            return Err(format!("{} was answered more than once", answer.question_id));
+        }
+    }
+    Ok(())
+}
+
+async fn idcs_security_questions(
+    client: &AuthClient,
+    request_state: &str,
+) -> Result<SecurityQuestionsPrompt, AuthError> {
+    log::debug!("Requesting security questions");
+    let step = auth::authenticate_raw(client, json!({
+        "op": "credSubmit",
+        "authFactor": AuthFactor::SecurityQuestions.idcs_name(),
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Failed to select factor"))?;
+
+    let questions = parse_questions(&step);
+    if questions.is_empty() {
+        return Err("IDCS did not return any security questions".into());
+    }
+    Ok(SecurityQuestionsPrompt {
+        request_state: step["requestState"].as_str().unwrap_or(request_state).to_string(),
+        questions,
+    })
+}
+
+async fn idcs_submit_answers(
+    client: &AuthClient,
+    request_state: &str,
+    answers: &[SecurityAnswer],
+) -> Result<SecurityAnswersResult, AuthError> {
+    log::debug!("Submitting {} security answers", answers.len());
+    let step = auth::authenticate_raw(client, json!({
+        "op": "credSubmit",
+        "authFactor": AuthFactor::SecurityQuestions.idcs_name(),
+        "credentials": {
+            "questions": answers
+                .iter()
+                .map(|answer| json!({ "questionId": answer.question_id, "answer": answer.answer.trim() }))
+                .collect::<Vec<_>>()
+        },
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Security answers rejected"))?;
+
+    // A partial failure comes back pending, asking the wrong ones again
+    let questions = parse_questions(&step);
+    let mut response: AuthResponse =
+        serde_json::from_value(step).map_err(|e| format!("Failed to parse response: {}", e))?;
+    crate::i18n::localize_causes(&mut response.cause);
+    Ok(answers_result(response, questions, answers))
+}
+
+// IDCS lists the questions under the factor's name as { "questionId": ..., "text": ... }
+fn parse_questions(step: &Value) -> Vec<SecurityQuestion> {
+    step[AuthFactor::SecurityQuestions.idcs_name()]["questions"]
+        .as_array()
+        .map(|questions| {
+            questions
+                .iter()
+                .filter_map(|question| {
+                    Some(SecurityQuestion {
+                        id: question["questionId"].as_str()?.to_string(),
+                        text: question["text"].as_str().unwrap_or_default().to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn answers_result(
+    response: AuthResponse,
+    questions: Vec<SecurityQuestion>,
+    answers: &[SecurityAnswer],
+) -> SecurityAnswersResult {
+    if response.status == "success" {
+        return SecurityAnswersResult {
+            response,
+            questions: vec![],
+            failed_question_ids: vec![],
+        };
+    }
+    let failed_question_ids = answers
+        .iter()
+        .filter(|answer| questions.iter().any(|question| question.id == answer.question_id))
+        .map(|answer| answer.question_id.clone())
+        .collect();
+    SecurityAnswersResult {
+        response,
+        questions,
+        failed_question_ids,
+    }
+}
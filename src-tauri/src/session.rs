@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+use crate::config::AuthFactor;
+use crate::dpop::{self, DpopKey};
+use crate::error::CommandError;
+use crate::lifecycle::{self, AppState};
+use crate::middleware::command;
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    // As returned by IDCS `/admin/v1/Me` (or the demo provider)
+    pub profile: Value,
+    // User access token for calls made on the user's behalf; None in demo mode
+    pub access_token: Option<String>,
+    // Public IP reported by IDCS through `network.location_header`, if configured
+    pub client_ip: Option<String>,
+    // When `access_token` stops working; None when there is no token to expire
+    pub expires_at: Option<DateTime<Utc>>,
+    // Key `access_token` is bound to when the profile uses DPoP
+    pub dpop_key: Option<Arc<DpopKey>>,
+    // Redeemed for a new access token before `expires_at` when the profile asks for refresh tokens
+    pub refresh_token: Option<String>,
+    // What the user token was requested with and what IDCS granted; None without a user token
+    pub scopes: Option<ScopeGrant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentStatus {
+    // Every requested scope was granted
+    Full,
+    // IDCS dropped some, e.g. ones the user or the client app wasn't allowed
+    Partial,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopeGrant {
+    pub requested: Vec<String>,
+    pub granted: Vec<String>,
+    // Requested but not granted
+    pub dropped: Vec<String>,
+    pub consent: ConsentStatus,
+}
+
+impl ScopeGrant {
+    // `granted` is the token response's `scope`; a response without one granted everything requested
+    pub fn new(requested: &str, granted: Option<&str>) -> Self {
+        let split = |scope: &str| scope.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+        let requested = split(requested);
+        let granted = granted.map(split).unwrap_or_else(|| requested.clone());
+        let dropped: Vec<String> = requested.iter().filter(|scope| !granted.contains(scope)).cloned().collect();
+        if !dropped.is_empty() {
+            log::warn!("IDCS did not grant the requested scopes {}", dropped.join(" "));
+        }
+        Self {
+            consent: if dropped.is_empty() { ConsentStatus::Full } else { ConsentStatus::Partial },
+            requested,
+            granted,
+            dropped,
+        }
+    }
+}
+
+// What the frontend can show about the signed-in session, without the tokens themselves
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub user: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub scopes: Option<ScopeGrant>,
+    // The session renews itself with a refresh token before it expires
+    pub renewable: bool,
+    pub dpop_bound: bool,
+    pub client_ip: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SessionState(pub Mutex<Option<Session>>);
+
+impl SessionState {
+    pub fn profile(&self) -> Result<Value, String> {
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .map(|session| session.profile.clone())
+            .ok_or_else(|| "Not signed in".to_string())
+    }
+
+    // Ready to use as an Authorization header; DPoP-bound tokens use the DPoP scheme, and the HTTP layer adds
+    // the proof
+    pub fn bearer_token(&self) -> Result<String, String> {
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .and_then(|session| {
+                let token = session.access_token.as_ref()?;
+                Some(dpop::authorization(token, session.dpop_key.as_deref()))
+            })
+            .ok_or_else(|| "Not signed in to IDCS".to_string())
+    }
+}
+
+// Where the password sign-in stands. The step commands move it along, so the frontend doesn't have to carry the
+// request state and the offered factors from one step to the next, and a step out of order is refused.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum AuthSession {
+    #[default]
+    Unauthenticated,
+    // Password accepted. `next_factors` are the second factors IDCS offers; with none, `complete_auth` finishes.
+    PasswordSubmitted { request_state: String, next_factors: Vec<String> },
+    // A second factor was started; once IDCS accepted it (`verified`), `complete_auth` finishes
+    MfaPending { factor: AuthFactor, request_state: String, verified: bool },
+    Authenticated,
+}
+
+#[derive(Default)]
+pub struct AuthSessionState(pub Mutex<AuthSession>);
+
+impl AuthSessionState {
+    // The request state the next step continues from, as the last step left it. Fails when no sign-in is in
+    // progress.
+    pub fn request_state(&self) -> Result<String, String> {
+        match &*self.0.lock().map_err(|e| e.to_string())? {
+            AuthSession::PasswordSubmitted { request_state, .. } | AuthSession::MfaPending { request_state, .. } => {
+                Ok(request_state.clone())
+            }
+            AuthSession::Unauthenticated => Err("No sign-in is in progress; start again with your password".into()),
+            AuthSession::Authenticated => Err("Already signed in".into()),
+        }
+    }
+}
+
+pub(crate) fn set_auth_session(app_handle: &AppHandle, next: AuthSession) {
+    if let Ok(mut auth_session) = app_handle.state::<AuthSessionState>().0.lock() {
+        *auth_session = next;
+    }
+}
+
+#[command]
+pub fn get_session_state(auth_session: State<AuthSessionState>) -> Result<AuthSession, CommandError> {
+    Ok(auth_session.0.lock().map_err(|e| e.to_string())?.clone())
+}
+
+// Abandons the sign-in in progress, e.g. when the user goes back to the password step; stops push polling. A
+// signed-in session is kept.
+#[command]
+pub fn reset_session(
+    app_handle: AppHandle,
+    auth_session: State<AuthSessionState>,
+    session: State<SessionState>,
+) -> Result<AuthSession, CommandError> {
+    let signed_in = session.0.lock().map_err(|e| e.to_string())?.is_some();
+    let next = if signed_in { AuthSession::Authenticated } else { AuthSession::Unauthenticated };
+    *auth_session.0.lock().map_err(|e| e.to_string())? = next.clone();
+    if matches!(lifecycle::current(&app_handle), AppState::Authenticating | AppState::MfaPending) {
+        lifecycle::transition(&app_handle, if signed_in { AppState::LoggedIn } else { AppState::LoggedOut });
+    }
+    log::info!("Sign-in reset");
+    Ok(next)
+}
+
+// None when signed out
+#[command]
+pub fn get_session_info(session: State<SessionState>) -> Result<Option<SessionInfo>, CommandError> {
+    Ok(session
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|session| SessionInfo {
+            user: session.profile["userName"].as_str().map(str::to_string),
+            expires_at: session.expires_at,
+            scopes: session.scopes.clone(),
+            renewable: session.refresh_token.is_some(),
+            dpop_bound: session.dpop_key.is_some(),
+            client_ip: session.client_ip.clone(),
+        }))
+}
+
+#[command]
+pub fn get_profile(session: State<SessionState>) -> Result<Option<Value>, CommandError> {
+    Ok(session
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|session| session.profile.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scope_grant_is_full_when_everything_was_granted() {
+        let grant = ScopeGrant::new("openid urn:opc:idm:__myscopes__", Some("urn:opc:idm:__myscopes__ openid"));
+        assert_eq!(grant.consent, ConsentStatus::Full);
+        assert!(grant.dropped.is_empty());
+    }
+
+    #[test]
+    fn scope_grant_lists_the_dropped_scopes() {
+        let grant = ScopeGrant::new("openid offline_access urn:opc:idm:__myscopes__", Some("openid"));
+        assert_eq!(grant.consent, ConsentStatus::Partial);
+        assert_eq!(grant.granted, ["openid"]);
+        assert_eq!(grant.dropped, ["offline_access", "urn:opc:idm:__myscopes__"]);
+    }
+
+    #[test]
+    fn scope_grant_without_a_scope_in_the_response_granted_everything() {
+        let grant = ScopeGrant::new("openid  offline_access", None);
+        assert_eq!(grant.requested, ["openid", "offline_access"]);
+        assert_eq!(grant.granted, grant.requested);
+        assert_eq!(grant.consent, ConsentStatus::Full);
+    }
+}
@@ -0,0 +1,372 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::cell::Cell;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::config::{ConfigState, LogLevel, LogSinkConfig, LogSinkKind};
+use crate::error::CommandError;
+use crate::middleware::command;
+
+const BATCH_WINDOW: Duration = Duration::from_secs(2);
+const MAX_BATCH: usize = 200;
+const APP_NAME: &str = "oci-auth-tauri";
+
+// Crates that log while shipping records; forwarding them to a network sink would loop
+const NETWORK_TARGETS: &[&str] = &["reqwest", "hyper", "h2", "rustls", "tokio_util"];
+
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+// A log destination. Sinks are called on the logging thread, so slow ones should hand records off
+pub trait LogSink: Send + Sync {
+    fn write(&self, record: &LogRecord);
+
+    fn flush(&self) {}
+
+    // Network sinks skip records from the HTTP stack they use themselves
+    fn is_network(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogSinkStatus {
+    pub name: String,
+    pub kind: String,
+    pub enabled: bool,
+}
+
+struct RegisteredSink {
+    name: String,
+    kind: &'static str,
+    level: log::LevelFilter,
+    enabled: AtomicBool,
+    sink: Box<dyn LogSink>,
+}
+
+static SINKS: RwLock<Vec<RegisteredSink>> = RwLock::new(Vec::new());
+
+thread_local! {
+    static DISPATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+// Target for the log plugin that fans records out to the registered sinks
+pub fn dispatch() -> fern::Dispatch {
+    fern::Dispatch::new().chain(fern::Output::call(|record| {
+        // A sink that logs while writing must not re-enter itself
+        if DISPATCHING.with(|dispatching| dispatching.replace(true)) {
+            return;
+        }
+        let record = LogRecord {
+            timestamp: Utc::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        if let Ok(sinks) = SINKS.read() {
+            let from_network = NETWORK_TARGETS.iter().any(|target| record.target.starts_with(target));
+            for registered in sinks.iter() {
+                if registered.enabled.load(Ordering::Relaxed)
+                    && record.level <= registered.level
+                    && !(from_network && registered.sink.is_network())
+                {
+                    registered.sink.write(&record);
+                }
+            }
+        }
+        DISPATCHING.with(|dispatching| dispatching.set(false));
+    }))
+}
+
+// Replaces the registered sinks with the ones from the config
+pub fn register_from_config(sinks: &[LogSinkConfig]) {
+    let mut registered = Vec::new();
+    for config in sinks {
+        let name = config.name();
+        match build_sink(&config.kind) {
+            Ok(sink) => registered.push(RegisteredSink {
+                kind: kind_name(&config.kind),
+                level: config.level.as_ref().map(level_filter).unwrap_or(log::LevelFilter::Trace),
+                enabled: AtomicBool::new(config.enabled),
+                sink,
+                name,
+            }),
+            Err(e) => log::error!("Failed to set up log sink {}: {}", name, e),
+        }
+    }
+
+    if let Ok(mut current) = SINKS.write() {
+        current.iter().for_each(|registered| registered.sink.flush());
+        *current = registered;
+    }
+}
+
+// Adds a sink from code, e.g. one that isn't configurable from config.json
+pub fn register(name: &str, sink: Box<dyn LogSink>) {
+    if let Ok(mut current) = SINKS.write() {
+        current.retain(|registered| registered.name != name);
+        current.push(RegisteredSink {
+            name: name.to_string(),
+            kind: "custom",
+            level: log::LevelFilter::Trace,
+            enabled: AtomicBool::new(true),
+            sink,
+        });
+    }
+}
+
+#[command]
+pub fn list_log_sinks() -> Result<Vec<LogSinkStatus>, CommandError> {
+    let sinks = SINKS.read().map_err(|e| e.to_string())?;
+    Ok(sinks
+        .iter()
+        .map(|registered| LogSinkStatus {
+            name: registered.name.clone(),
+            kind: registered.kind.to_string(),
+            enabled: registered.enabled.load(Ordering::Relaxed),
+        })
+        .collect())
+}
+
+#[command(unlocked)]
+pub fn set_log_sink_enabled(
+    app_handle: AppHandle,
+    config_state: State<ConfigState>,
+    name: String,
+    enabled: bool,
+) -> Result<(), CommandError> {
+    {
+        let sinks = SINKS.read().map_err(|e| e.to_string())?;
+        let registered = sinks
+            .iter()
+            .find(|registered| registered.name == name)
+            .ok_or_else(|| format!("Unknown log sink: {}", name))?;
+        registered.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            registered.sink.flush();
+        }
+    }
+
+    let mut config = config_state.0.lock();
+    if config.logging.sinks.iter().any(|sink| sink.name() == name) {
+        config
+            .set_log_sink_enabled(&app_handle, &name, enabled)
+            .map_err(|e| e.to_string())?;
+    }
+    log::info!("Log sink {} {}", name, if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+fn build_sink(kind: &LogSinkKind) -> Result<Box<dyn LogSink>, String> {
+    Ok(match kind {
+        LogSinkKind::File { path } => Box::new(FileSink::open(path)?),
+        LogSinkKind::Syslog { address } => Box::new(SyslogSink::connect(address)?),
+        LogSinkKind::Webhook { url } => Box::new(HttpSink::spawn(url.clone(), webhook_body)),
+        LogSinkKind::Otlp { endpoint } => Box::new(HttpSink::spawn(
+            format!("{}/v1/logs", endpoint.trim_end_matches('/')),
+            otlp_body,
+        )),
+    })
+}
+
+fn kind_name(kind: &LogSinkKind) -> &'static str {
+    match kind {
+        LogSinkKind::File { .. } => "file",
+        LogSinkKind::Syslog { .. } => "syslog",
+        LogSinkKind::Webhook { .. } => "webhook",
+        LogSinkKind::Otlp { .. } => "otlp",
+    }
+}
+
+fn level_filter(level: &LogLevel) -> log::LevelFilter {
+    match level {
+        LogLevel::Trace => log::LevelFilter::Trace,
+        LogLevel::Debug => log::LevelFilter::Debug,
+        LogLevel::Info => log::LevelFilter::Info,
+        LogLevel::Warn => log::LevelFilter::Warn,
+        LogLevel::Error => log::LevelFilter::Error,
+        LogLevel::Off => log::LevelFilter::Off,
+    }
+}
+
+// Plain-text file outside the rotated log directory, e.g. on a share collected by a log agent
+pub struct FileSink {
+    file: Mutex<File>,
+}
+
+impl FileSink {
+    pub fn open(path: &std::path::Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+}
+
+impl LogSink for FileSink {
+    fn write(&self, record: &LogRecord) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(
+                file,
+                "{} [{}] {}: {}",
+                record.timestamp.to_rfc3339(),
+                record.level,
+                record.target,
+                record.message
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+// RFC 5424 messages over UDP
+pub struct SyslogSink {
+    socket: UdpSocket,
+}
+
+impl SyslogSink {
+    pub fn connect(address: &str) -> Result<Self, String> {
+        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+        socket.connect(address).map_err(|e| e.to_string())?;
+        Ok(Self { socket })
+    }
+}
+
+impl LogSink for SyslogSink {
+    fn write(&self, record: &LogRecord) {
+        // Facility user (1)
+        let severity = match record.level {
+            log::Level::Error => 3,
+            log::Level::Warn => 4,
+            log::Level::Info => 6,
+            log::Level::Debug | log::Level::Trace => 7,
+        };
+        let message = format!(
+            "<{}>1 {} - {} {} - - {}",
+            8 + severity,
+            record.timestamp.to_rfc3339(),
+            APP_NAME,
+            std::process::id(),
+            record.message
+        );
+        let _ = self.socket.send(message.as_bytes());
+    }
+}
+
+// Batches records and posts them from a background task so logging never waits on the network
+pub struct HttpSink {
+    sender: UnboundedSender<LogRecord>,
+}
+
+impl HttpSink {
+    pub fn spawn(url: String, encode: fn(&[LogRecord]) -> Value) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        tauri::async_runtime::spawn(ship(url, receiver, encode));
+        Self { sender }
+    }
+}
+
+impl LogSink for HttpSink {
+    fn write(&self, record: &LogRecord) {
+        let _ = self.sender.send(record.clone());
+    }
+
+    fn is_network(&self) -> bool {
+        true
+    }
+}
+
+async fn ship(url: String, mut receiver: UnboundedReceiver<LogRecord>, encode: fn(&[LogRecord]) -> Value) {
+    let client = reqwest::Client::new();
+    while let Some(record) = receiver.recv().await {
+        tokio::time::sleep(BATCH_WINDOW).await;
+        let mut batch = vec![record];
+        while batch.len() < MAX_BATCH {
+            match receiver.try_recv() {
+                Ok(record) => batch.push(record),
+                Err(_) => break,
+            }
+        }
+
+        let result = client
+            .post(&url)
+            .json(&encode(&batch))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        // Logging the failure would feed straight back into this sink
+        if let Err(e) = result {
+            eprintln!("Failed to ship {} log records to {}: {}", batch.len(), url, e);
+        }
+    }
+}
+
+fn webhook_body(batch: &[LogRecord]) -> Value {
+    json!({
+        "source": APP_NAME,
+        "records": batch
+            .iter()
+            .map(|record| json!({
+                "timestamp": record.timestamp.to_rfc3339(),
+                "level": record.level.to_string(),
+                "target": record.target,
+                "message": record.message,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+// OTLP/HTTP JSON encoding of a logs export request
+fn otlp_body(batch: &[LogRecord]) -> Value {
+    let records: Vec<Value> = batch
+        .iter()
+        .map(|record| {
+            let severity_number = match record.level {
+                log::Level::Trace => 1,
+                log::Level::Debug => 5,
+                log::Level::Info => 9,
+                log::Level::Warn => 13,
+                log::Level::Error => 17,
+            };
+            json!({
+                "timeUnixNano": record.timestamp.timestamp_nanos_opt().unwrap_or_default().to_string(),
+                "severityNumber": severity_number,
+                "severityText": record.level.to_string(),
+                "body": { "stringValue": record.message },
+                "attributes": [{ "key": "log.target", "value": { "stringValue": record.target } }],
+            })
+        })
+        .collect();
+
+    json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [{ "key": "service.name", "value": { "stringValue": APP_NAME } }]
+            },
+            "scopeLogs": [{ "scope": { "name": APP_NAME }, "logRecords": records }]
+        }]
+    })
+}
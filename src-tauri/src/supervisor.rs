@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+// How often running tasks are checked for stalls
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+// Allowed on top of the interval a task promised to check in within, for a slow network request or a busy lock
+const STALL_GRACE: Duration = Duration::from_secs(300);
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+// A task that ran this long before failing again starts over from the first backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(600);
+
+tokio::task_local! {
+    // Name of the supervised task being polled, so `alive` knows who is checking in
+    static TASK: &'static str;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub name: &'static str,
+    pub restarts: u32,
+    pub last_failure: Option<String>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    last_beat: Instant,
+    // Set by `alive`; tasks that never call it are only restarted when they panic
+    #[serde(skip)]
+    due_within: Option<Duration>,
+}
+
+// Background loops such as the session renewal and the cache trimming, restarted when they panic or stop making
+// progress, so one bad response doesn't quietly turn off automatic refreshes until the app is restarted
+#[derive(Default)]
+pub struct SupervisorState(Mutex<BTreeMap<&'static str, TaskStatus>>);
+
+impl SupervisorState {
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        self.0.lock().map(|tasks| tasks.values().cloned().collect()).unwrap_or_default()
+    }
+}
+
+// Runs `task` in the background and restarts it with backoff whenever it panics or stalls. A task that returns
+// is done and isn't started again.
+pub fn supervise<F, Fut>(app_handle: &AppHandle, name: &'static str, task: F)
+where
+    F: Fn(AppHandle) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0;
+        loop {
+            update(&app_handle, name, |status| {
+                status.last_beat = Instant::now();
+                status.due_within = None;
+            });
+            let started = Instant::now();
+            // tokio's handle rather than Tauri's, for the panic message
+            let handle = tokio::spawn(TASK.scope(name, task(app_handle.clone())));
+            let Some(failure) = watch(&app_handle, name, handle).await else {
+                log::debug!("Background task {} finished", name);
+                return;
+            };
+
+            if started.elapsed() >= HEALTHY_RUN {
+                failures = 0;
+            }
+            failures += 1;
+            let backoff = (FIRST_BACKOFF * 2u32.saturating_pow(failures - 1)).min(MAX_BACKOFF);
+            log::error!(
+                "Background task {} {} after running for {} seconds; restarting it in {} seconds (failure {} in a row)",
+                name,
+                failure,
+                started.elapsed().as_secs(),
+                backoff.as_secs(),
+                failures
+            );
+            update(&app_handle, name, |status| {
+                status.restarts += 1;
+                status.last_failure = Some(failure);
+                status.last_failure_at = Some(Utc::now());
+            });
+            tokio::time::sleep(backoff).await;
+        }
+    });
+}
+
+// For supervised tasks: call once per round with how long until the next call at the latest
+pub fn alive(app_handle: &AppHandle, next_within: Duration) {
+    let Ok(name) = TASK.try_with(|name| *name) else {
+        return;
+    };
+    update(app_handle, name, |status| {
+        status.last_beat = Instant::now();
+        status.due_within = Some(next_within);
+    });
+}
+
+// Waits for the task to end; returns what went wrong, or None when it simply returned
+async fn watch(app_handle: &AppHandle, name: &'static str, mut handle: tokio::task::JoinHandle<()>) -> Option<String> {
+    let mut last_check = Instant::now();
+    loop {
+        tokio::select! {
+            result = &mut handle => {
+                return match result {
+                    Ok(()) => None,
+                    Err(e) if e.is_panic() => Some(format!("panicked: {}", panic_message(e.into_panic()))),
+                    Err(e) => Some(format!("was stopped: {}", e)),
+                };
+            }
+            _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+        }
+
+        let now = Instant::now();
+        // After the machine slept, every task is late through no fault of its own
+        let slept = now.duration_since(last_check) > CHECK_INTERVAL * 3;
+        last_check = now;
+        let stalled_for = app_handle.state::<SupervisorState>().0.lock().ok().and_then(|mut tasks| {
+            let status = tasks.get_mut(name)?;
+            if slept {
+                status.last_beat = now;
+            }
+            let silent = now.duration_since(status.last_beat);
+            (silent > status.due_within? + STALL_GRACE).then_some(silent)
+        });
+        if let Some(silent) = stalled_for {
+            handle.abort();
+            return Some(format!("stalled, with no progress for {} seconds", silent.as_secs()));
+        }
+    }
+}
+
+fn update(app_handle: &AppHandle, name: &'static str, change: impl FnOnce(&mut TaskStatus)) {
+    let Some(state) = app_handle.try_state::<SupervisorState>() else {
+        return;
+    };
+    let Ok(mut tasks) = state.0.lock() else {
+        return;
+    };
+    let status = tasks.entry(name).or_insert_with(|| TaskStatus {
+        name,
+        restarts: 0,
+        last_failure: None,
+        last_failure_at: None,
+        last_beat: Instant::now(),
+        due_within: None,
+    });
+    change(status);
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "no message".to_string())
+}
@@ -0,0 +1,390 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::client_bearer_token;
+use crate::auth_client::AuthClient;
+use crate::cache::{get_or_revalidate, read_disk, write_disk, write_disk_with_etag, CacheState, BRANDING_KEY, LOGIN_HINTS_KEY};
+use crate::circuit;
+use crate::demo::DemoState;
+use crate::endpoints::base_url;
+use crate::error::CommandError;
+use crate::i18n;
+use crate::middleware::command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsernameFormat {
+    Email,
+    ShortName,
+}
+
+// Tenant-specific hints the login form uses to adapt its fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginHints {
+    pub tenant_name: Option<String>,
+    pub username_formats: Vec<UsernameFormat>,
+    pub remember_me_enabled: bool,
+    pub self_registration_enabled: bool,
+}
+
+impl Default for LoginHints {
+    fn default() -> Self {
+        Self {
+            tenant_name: None,
+            username_formats: vec![UsernameFormat::ShortName],
+            remember_me_enabled: false,
+            self_registration_enabled: false,
+        }
+    }
+}
+
+// Corporate branding shown on the hosted IDCS sign-in page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantBranding {
+    pub product_name: Option<String>,
+    pub logo_url: Option<String>,
+    // Logo bytes as a data URL so the webview doesn't need network access to the tenant
+    pub logo_data_url: Option<String>,
+    pub favicon_url: Option<String>,
+    // IDCS keeps tenant colors in the custom stylesheet rather than in discrete attributes
+    pub custom_css_url: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+const BRANDING_MAX_AGE: Duration = Duration::from_secs(24 * 3600);
+const BRANDING_MEMORY_TTL: Duration = Duration::from_secs(3600);
+const LOGIN_HINTS_TTL: Duration = Duration::from_secs(3600);
+// Larger logos are skipped: they end up base64-encoded in the branding cache and the webview
+const LOGO_MAX_BYTES: usize = 512 * 1024;
+
+#[command]
+pub async fn get_login_hints(
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    cache: State<'_, CacheState>,
+) -> Result<LoginHints, CommandError> {
+    if demo.enabled {
+        return Ok(LoginHints {
+            tenant_name: Some("Demo Tenant".to_string()),
+            username_formats: vec![UsernameFormat::ShortName, UsernameFormat::Email],
+            remember_me_enabled: true,
+            self_registration_enabled: false,
+        });
+    }
+
+    Ok(cache.get_or_fetch(LOGIN_HINTS_KEY, LOGIN_HINTS_TTL, || fetch_login_hints(&auth_client)).await?)
+}
+
+async fn fetch_login_hints(client: &AuthClient) -> Result<LoginHints, String> {
+    let bearer_token = client_bearer_token(client).await?;
+    let mut hints = LoginHints::default();
+
+    // Each settings resource needs its own app role, so a missing one only drops that hint
+    match get_settings(client, &bearer_token, "/admin/v1/Settings/Settings?attributes=companyNames,defaultCompanyNames").await {
+        Ok(settings) => hints.tenant_name = first_value(&settings["companyNames"]).or_else(|| first_value(&settings["defaultCompanyNames"])),
+        Err(e) => log::warn!("Could not read tenant settings: {}", e),
+    }
+
+    match get_settings(client, &bearer_token, "/admin/v1/IdentitySettings/IdentitySettings").await {
+        Ok(settings) => {
+            if settings["primaryEmailRequired"].as_bool().unwrap_or(false) {
+                hints.username_formats.push(UsernameFormat::Email);
+            }
+        }
+        Err(e) => log::warn!("Could not read identity settings: {}", e),
+    }
+
+    match get_settings(client, &bearer_token, "/admin/v1/KmsiSettings/KmsiSettings").await {
+        Ok(settings) => hints.remember_me_enabled = settings["kmsiFeatureEnabled"].as_bool().unwrap_or(false),
+        Err(e) => log::warn!("Could not read keep-me-signed-in settings: {}", e),
+    }
+
+    match get_settings(client, &bearer_token, "/admin/v1/SelfRegistrationProfiles?attributes=active&count=50").await {
+        Ok(profiles) => {
+            hints.self_registration_enabled = profiles["Resources"]
+                .as_array()
+                .map(|resources| resources.iter().any(|p| p["active"].as_bool().unwrap_or(false)))
+                .unwrap_or(false)
+        }
+        Err(e) => log::warn!("Could not read self-registration profiles: {}", e),
+    }
+
+    Ok(hints)
+}
+
+pub(crate) async fn get_settings(client: &AuthClient, bearer_token: &str, path: &str) -> Result<Value, String> {
+    Ok(get_resource(client, bearer_token, path).await?)
+}
+
+// Like `get_settings`, keeping the status IDCS answered with, e.g. to renew the session after a 401
+pub(crate) async fn get_resource(client: &AuthClient, bearer_token: &str, path: &str) -> Result<Value, ScimError> {
+    match get_scim(client, bearer_token, path, None).await? {
+        ScimResponse::Modified { value, .. } => Ok(value),
+        ScimResponse::NotModified => Err(format!("Unexpected 304 for {}", path).into()),
+    }
+}
+
+pub(crate) enum ScimResponse {
+    NotModified,
+    Modified { value: Value, etag: Option<String> },
+}
+
+// A failed SCIM request; `status` is set when IDCS answered with an error status
+#[derive(Debug)]
+pub(crate) struct ScimError {
+    pub status: Option<StatusCode>,
+    message: String,
+}
+
+impl std::fmt::Display for ScimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for ScimError {
+    fn from(message: String) -> Self {
+        Self { status: None, message }
+    }
+}
+
+impl From<ScimError> for String {
+    fn from(error: ScimError) -> Self {
+        error.message
+    }
+}
+
+impl From<ScimError> for CommandError {
+    fn from(error: ScimError) -> Self {
+        Self::from(error.message)
+    }
+}
+
+// GET with an optional If-None-Match validator
+pub(crate) async fn get_scim(
+    client: &AuthClient,
+    bearer_token: &str,
+    path: &str,
+    etag: Option<&str>,
+) -> Result<ScimResponse, ScimError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, i18n::accept_language());
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(bearer_token).map_err(|e| e.to_string())?,
+    );
+    if let Some(etag) = etag {
+        headers.insert(IF_NONE_MATCH, HeaderValue::from_str(etag).map_err(|e| e.to_string())?);
+    }
+
+    let url = client.url(path);
+    log::debug!("Making settings request to URL: {}", url);
+
+    let response = circuit::send(client.http().get(&url).headers(headers)).await?;
+
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(ScimResponse::NotModified);
+    }
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let response_text = response.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(ScimError {
+            status: Some(status),
+            message: format!("Failed to get {}: {} {}", path, status, response_text),
+        });
+    }
+
+    let value = serde_json::from_str(&response_text)
+        .map_err(|e| format!("Failed to parse settings response: {}. Response text: {}", e, response_text))?;
+    Ok(ScimResponse::Modified { value, etag })
+}
+
+// Fetches a SCIM resource, reusing the disk-cached copy when the server answers 304
+pub(crate) async fn get_scim_cached(
+    app_handle: &AppHandle,
+    client: &AuthClient,
+    key: &str,
+    bearer_token: &str,
+    path: &str,
+    max_age: Duration,
+) -> Result<Value, String> {
+    let cached = read_disk::<Value>(app_handle, key);
+    let etag = cached.as_ref().and_then(|cached| cached.etag.clone());
+
+    let (value, etag) = match get_scim(client, bearer_token, path, etag.as_deref()).await? {
+        ScimResponse::Modified { value, etag } => (value, etag),
+        ScimResponse::NotModified => match cached {
+            Some(cached) => {
+                log::debug!("{} not modified, using cached copy", path);
+                (cached.value, etag)
+            }
+            None => return Err(format!("Unexpected 304 for {}", path)),
+        },
+    };
+
+    if let Err(e) = write_disk_with_etag(app_handle, key, &value, max_age, etag) {
+        log::warn!("Failed to cache {}: {}", path, e);
+    }
+    Ok(value)
+}
+
+// SCIM multi-valued attributes look like [{ "value": ..., "locale": ... }]
+fn first_value(attribute: &Value) -> Option<String> {
+    attribute
+        .as_array()?
+        .iter()
+        .find_map(|entry| entry["value"].as_str())
+        .map(|value| value.to_string())
+}
+
+#[command]
+pub async fn get_tenant_branding(
+    app_handle: AppHandle,
+    demo: State<'_, DemoState>,
+    cache: State<'_, CacheState>,
+    refresh: Option<bool>,
+) -> Result<TenantBranding, CommandError> {
+    if demo.enabled {
+        return Ok(TenantBranding {
+            product_name: Some("Demo Tenant".to_string()),
+            logo_url: None,
+            logo_data_url: None,
+            favicon_url: None,
+            custom_css_url: None,
+            fetched_at: Utc::now(),
+        });
+    }
+
+    if !refresh.unwrap_or(false) {
+        if let Some(branding) = cache.get::<TenantBranding>(BRANDING_KEY) {
+            return Ok(branding);
+        }
+        let client = app_handle.state::<AuthClient>().inner().clone();
+        let branding = get_or_revalidate(&app_handle, BRANDING_KEY, BRANDING_MAX_AGE, || fetch_branding(client)).await?;
+        cache.insert(BRANDING_KEY, &branding, BRANDING_MEMORY_TTL);
+        return Ok(branding);
+    }
+
+    match fetch_branding(app_handle.state::<AuthClient>().inner().clone()).await {
+        Ok(branding) => {
+            if let Err(e) = write_disk(&app_handle, BRANDING_KEY, &branding, BRANDING_MAX_AGE) {
+                log::warn!("Failed to cache tenant branding: {}", e);
+            }
+            cache.insert(BRANDING_KEY, &branding, BRANDING_MEMORY_TTL);
+            Ok(branding)
+        }
+        // A stale cache is still better than an unbranded login window
+        Err(e) => match read_disk::<TenantBranding>(&app_handle, BRANDING_KEY) {
+            Some(cached) => {
+                log::warn!("Failed to refresh tenant branding, using cached copy: {}", e);
+                Ok(cached.value)
+            }
+            None => Err(e.into()),
+        },
+    }
+}
+
+// Loads branding into the memory cache, revalidating the disk copy if it's stale
+pub(crate) async fn warm_branding(app_handle: &AppHandle) -> Result<(), String> {
+    let client = app_handle.state::<AuthClient>().inner().clone();
+    let branding = get_or_revalidate(app_handle, BRANDING_KEY, BRANDING_MAX_AGE, || fetch_branding(client)).await?;
+    app_handle
+        .state::<CacheState>()
+        .insert(BRANDING_KEY, &branding, BRANDING_MEMORY_TTL);
+    Ok(())
+}
+
+// Takes the client by value, since revalidation finishes in the background
+async fn fetch_branding(client: AuthClient) -> Result<TenantBranding, String> {
+    let bearer_token = client_bearer_token(&client).await?;
+    let settings = get_settings(
+        &client,
+        &bearer_token,
+        "/admin/v1/Settings/Settings?attributes=companyNames,defaultCompanyNames,images,defaultImages,customCssLocation",
+    )
+    .await?;
+
+    let image_of = |kind: &str| {
+        find_image(&settings["images"], kind).or_else(|| find_image(&settings["defaultImages"], kind))
+    };
+    let logo_url = image_of("desktop logo").or_else(|| image_of("logo"));
+    let logo_data_url = match &logo_url {
+        Some(url) => match download_data_url(&client, url).await {
+            Ok(data_url) => Some(data_url),
+            Err(e) => {
+                log::warn!("Failed to download tenant logo: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(TenantBranding {
+        product_name: first_value(&settings["companyNames"]).or_else(|| first_value(&settings["defaultCompanyNames"])),
+        logo_url,
+        logo_data_url,
+        favicon_url: image_of("favicon"),
+        custom_css_url: settings["customCssLocation"].as_str().map(absolute_url),
+        fetched_at: Utc::now(),
+    })
+}
+
+// Image entries look like [{ "type": "desktop logo", "value": "/ui/v1/..." }]
+fn find_image(images: &Value, kind: &str) -> Option<String> {
+    images
+        .as_array()?
+        .iter()
+        .find(|image| image["type"].as_str().map(|t| t.to_lowercase().contains(kind)).unwrap_or(false))
+        .and_then(|image| image["value"].as_str())
+        .map(absolute_url)
+}
+
+fn absolute_url(url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_string()
+    } else {
+        format!("{}/{}", base_url(), url.trim_start_matches('/'))
+    }
+}
+
+// The logo may be hosted outside IDCS, so it's fetched without the device thumbprint
+async fn download_data_url(client: &AuthClient, url: &str) -> Result<String, String> {
+    let mut response = client.external().get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to get {}: {}", url, response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("image/png")
+        .to_string();
+    if !content_type.starts_with("image/") {
+        return Err(format!("{} is not an image: {}", url, content_type));
+    }
+    if response.content_length().is_some_and(|length| length > LOGO_MAX_BYTES as u64) {
+        return Err(format!("{} is larger than {} bytes", url, LOGO_MAX_BYTES));
+    }
+
+    // The length header may be missing or wrong, so the read is bounded too
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if bytes.len() + chunk.len() > LOGO_MAX_BYTES {
+            return Err(format!("{} is larger than {} bytes", url, LOGO_MAX_BYTES));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(format!("data:{};base64,{}", content_type, STANDARD.encode(bytes)))
+}
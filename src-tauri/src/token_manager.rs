@@ -0,0 +1,46 @@
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::platform;
+use crate::profiles::{keychain_account, PENDING_REFRESH_TOKEN_ENTRY, REFRESH_TOKEN_ENTRY};
+
+// Owns the refresh token of a resumable session in the OS keychain. IDCS may rotate the refresh token each time
+// it is redeemed, after which the old one fails with "refresh token already used"; so redemptions run one at a
+// time, and a rotated token is saved so that a crash at any point leaves the one IDCS will accept.
+#[derive(Default)]
+pub struct TokenManager {
+    rotation: Mutex<()>,
+}
+
+impl TokenManager {
+    // Hold for a whole renewal: reading the refresh token, redeeming it and saving the rotated one
+    pub async fn begin_rotation(&self) -> MutexGuard<'_, ()> {
+        self.rotation.lock().await
+    }
+
+    // The saved refresh token of `profile`, after finishing a rotation a crash interrupted
+    pub fn load(&self, profile: &str) -> Result<Option<String>, String> {
+        let pending = keychain_account(profile, PENDING_REFRESH_TOKEN_ENTRY);
+        if let Some(rotated) = platform::keychain_get(&pending)? {
+            // Only written once IDCS issued it, so the current token is already spent
+            platform::keychain_set(&keychain_account(profile, REFRESH_TOKEN_ENTRY), &rotated)?;
+            platform::keychain_delete(&pending)?;
+            log::info!("Recovered the refresh token of an interrupted renewal for the {} profile", profile);
+        }
+        platform::keychain_get(&keychain_account(profile, REFRESH_TOKEN_ENTRY))
+    }
+
+    // Two-phase write: the new token goes to the pending entry, then replaces the current one, then the pending
+    // entry is removed. `load` completes the swap when the app stopped in between.
+    pub fn save(&self, profile: &str, refresh_token: &str) -> Result<(), String> {
+        let pending = keychain_account(profile, PENDING_REFRESH_TOKEN_ENTRY);
+        platform::keychain_set(&pending, refresh_token)?;
+        platform::keychain_set(&keychain_account(profile, REFRESH_TOKEN_ENTRY), refresh_token)?;
+        platform::keychain_delete(&pending)
+    }
+
+    // After signing out, or when IDCS rejected the token
+    pub fn clear(&self, profile: &str) -> Result<(), String> {
+        platform::keychain_delete(&keychain_account(profile, PENDING_REFRESH_TOKEN_ENTRY))?;
+        platform::keychain_delete(&keychain_account(profile, REFRESH_TOKEN_ENTRY))
+    }
+}
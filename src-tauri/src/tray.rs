@@ -0,0 +1,263 @@
+use chrono::Utc;
+use std::time::Duration;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager};
+
+use crate::branding;
+use crate::config::ConfigState;
+use crate::lifecycle::{self, AppState};
+use crate::session::SessionState;
+use crate::window::{apply_always_on_top, move_to_cursor_monitor, save_placement, toggle_compact_mode, CompactState};
+
+pub const TRAY_ID: &str = "main-tray";
+
+const EXPIRY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const ICON_SIZE: u32 = 32;
+const DIGIT_SCALE: u32 = 2;
+// Minutes left at which the badge turns yellow, then red
+const EXPIRY_WARNING_MINUTES: i64 = 15;
+const EXPIRY_CRITICAL_MINUTES: i64 = 5;
+
+// 3x5 bitmap digits, one row per byte with the leftmost pixel in bit 2
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+// Check items whose state can also change from commands, so they are kept in sync
+pub struct TrayMenuState {
+    compact_item: CheckMenuItem<tauri::Wry>,
+    always_on_top_item: CheckMenuItem<tauri::Wry>,
+    // Only enabled while the window's webview isn't responding
+    recreate_window_item: MenuItem<tauri::Wry>,
+}
+
+pub fn create_tray(app_handle: &AppHandle) -> tauri::Result<()> {
+    let always_on_top = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.ui.always_on_top);
+
+    let show_item = MenuItem::with_id(app_handle, "tray-show", "Show", true, None::<&str>)?;
+    let compact_item = CheckMenuItem::with_id(app_handle, "tray-compact", "Compact mode", true, false, None::<&str>)?;
+    let always_on_top_item =
+        CheckMenuItem::with_id(app_handle, "tray-always-on-top", "Always on top", true, always_on_top, None::<&str>)?;
+    let recreate_window_item =
+        MenuItem::with_id(app_handle, "tray-recreate-window", "Recreate window", false, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app_handle)?;
+    let quit_item = MenuItem::with_id(app_handle, "tray-quit", "Quit", true, None::<&str>)?;
+
+    // A kiosk's window stays fullscreen, so its settings are left out
+    let menu = if crate::kiosk::is_enabled(app_handle) {
+        Menu::with_items(app_handle, &[&show_item, &recreate_window_item, &separator, &quit_item])?
+    } else {
+        Menu::with_items(
+            app_handle,
+            &[
+                &show_item,
+                &recreate_window_item,
+                &compact_item,
+                &always_on_top_item,
+                &separator,
+                &quit_item,
+            ],
+        )?
+    };
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(branding::APP_NAME)
+        .menu(&menu)
+        .on_menu_event(handle_menu_event);
+    if let Some(icon) = branding::icon().or_else(|| app_handle.default_window_icon().cloned()) {
+        builder = builder.icon(icon);
+    }
+    builder.build(app_handle)?;
+
+    app_handle.manage(TrayMenuState {
+        compact_item,
+        always_on_top_item,
+        recreate_window_item,
+    });
+    Ok(())
+}
+
+// Pending approvals count; macOS shows it next to the icon, elsewhere only the tooltip carries it
+pub fn set_badge(app_handle: &AppHandle, count: usize) {
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let (title, tooltip) = match count {
+        0 => (None, branding::APP_NAME.to_string()),
+        1 => (Some("1".to_string()), format!("{} - 1 pending approval", branding::APP_NAME)),
+        n => (Some(n.to_string()), format!("{} - {} pending approvals", branding::APP_NAME, n)),
+    };
+    let _ = tray.set_title(title);
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+// Offers to rebuild the window while its webview isn't responding, and says so in the tooltip
+pub fn set_window_unresponsive(app_handle: &AppHandle, unresponsive: bool) {
+    if let Some(tray_menu) = app_handle.try_state::<TrayMenuState>() {
+        let _ = tray_menu.recreate_window_item.set_enabled(unresponsive);
+    }
+    let Some(tray) = app_handle.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let tooltip = if unresponsive {
+        format!("{} - window not responding", branding::APP_NAME)
+    } else {
+        branding::APP_NAME.to_string()
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+// Swaps the tray icon for a green/yellow/red badge with the minutes left on the access token,
+// and back to the app icon when nobody is signed in
+pub async fn watch_token_expiry(app_handle: AppHandle) {
+    let mut shown: Option<Option<i64>> = None;
+    loop {
+        let minutes_left = app_handle
+            .state::<SessionState>()
+            .0
+            .lock()
+            .ok()
+            .and_then(|session| session.as_ref().and_then(|session| session.expires_at))
+            .map(|expires_at| (expires_at - Utc::now()).num_minutes().max(0))
+            .filter(|_| matches!(lifecycle::current(&app_handle), AppState::LoggedIn | AppState::Expired));
+
+        if shown != Some(minutes_left) {
+            if let Some(tray) = app_handle.tray_by_id(TRAY_ID) {
+                let icon = match minutes_left {
+                    Some(minutes) => Some(render_expiry_icon(minutes)),
+                    None => branding::icon().or_else(|| app_handle.default_window_icon().cloned()),
+                };
+                if let Err(e) = tray.set_icon(icon) {
+                    log::warn!("Failed to update the tray icon: {}", e);
+                }
+            }
+            shown = Some(minutes_left);
+        }
+        tokio::time::sleep(EXPIRY_REFRESH_INTERVAL).await;
+    }
+}
+
+fn render_expiry_icon(minutes: i64) -> Image<'static> {
+    let color = if minutes > EXPIRY_WARNING_MINUTES {
+        [46, 160, 67, 255]
+    } else if minutes > EXPIRY_CRITICAL_MINUTES {
+        [219, 171, 9, 255]
+    } else {
+        [207, 34, 46, 255]
+    };
+
+    let mut rgba = vec![0u8; (ICON_SIZE * ICON_SIZE * 4) as usize];
+    let mut put = |x: u32, y: u32, pixel: [u8; 4]| {
+        let offset = ((y * ICON_SIZE + x) * 4) as usize;
+        rgba[offset..offset + 4].copy_from_slice(&pixel);
+    };
+
+    let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+    let radius = ICON_SIZE as f32 / 2.0;
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let (dx, dy) = (x as f32 - center, y as f32 - center);
+            if dx * dx + dy * dy <= radius * radius {
+                put(x, y, color);
+            }
+        }
+    }
+
+    // Two digits fit; anything longer shows as 99
+    let text = minutes.min(99).to_string();
+    let advance = 4 * DIGIT_SCALE;
+    let left = (ICON_SIZE - (text.len() as u32 * advance - DIGIT_SCALE)) / 2;
+    let top = (ICON_SIZE - 5 * DIGIT_SCALE) / 2;
+    for (index, digit) in text.bytes().enumerate() {
+        let glyph = DIGITS[(digit - b'0') as usize];
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for sy in 0..DIGIT_SCALE {
+                    for sx in 0..DIGIT_SCALE {
+                        put(
+                            left + index as u32 * advance + column * DIGIT_SCALE + sx,
+                            top + row as u32 * DIGIT_SCALE + sy,
+                            [255, 255, 255, 255],
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Image::new_owned(rgba, ICON_SIZE, ICON_SIZE)
+}
+
+// Reflects the current window modes in the tray check items
+pub fn sync_menu(app_handle: &AppHandle) {
+    let Some(tray_menu) = app_handle.try_state::<TrayMenuState>() else {
+        return;
+    };
+
+    let compact = app_handle
+        .state::<CompactState>()
+        .0
+        .lock()
+        .map(|size| size.is_some())
+        .unwrap_or(false);
+    let always_on_top = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.ui.always_on_top);
+
+    let _ = tray_menu.compact_item.set_checked(compact);
+    let _ = tray_menu.always_on_top_item.set_checked(always_on_top);
+}
+
+fn handle_menu_event(app_handle: &AppHandle, event: MenuEvent) {
+    log::debug!("Tray menu event received: {}", event.id().0);
+
+    let result = match event.id().0.as_str() {
+        "tray-show" => move_to_cursor_monitor(app_handle.clone()).map_err(|e| e.message),
+        "tray-recreate-window" => crate::heartbeat::recreate_window(app_handle),
+        "tray-compact" => toggle_compact_mode(app_handle),
+        "tray-always-on-top" => {
+            let enabled = app_handle
+                .state::<ConfigState>()
+                .0
+                .read(|config| !config.ui.always_on_top);
+            apply_always_on_top(app_handle, enabled)
+        }
+        "tray-quit" => {
+            log::info!("Application exit requested via tray");
+            if let Err(e) = save_placement(app_handle) {
+                log::warn!("Failed to save window placement: {}", e);
+            }
+            app_handle.exit(0);
+            Ok(())
+        }
+        _ => {
+            log::warn!("Unknown tray menu item clicked: {}", event.id().0);
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        log::error!("Tray action {} failed: {}", event.id().0, e);
+    }
+    // Check items toggle themselves on click; undo that if the action failed or was redundant
+    sync_menu(app_handle);
+}
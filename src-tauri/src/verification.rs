@@ -0,0 +1,50 @@
+use serde_json::json;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreBuilder;
+
+use crate::error::CommandError;
+use crate::middleware::command;
+
+// Machine-local on purpose: never part of the config, exports or anything synced
+const VERIFICATION_STORE_FILE: &str = "verification.json";
+const MAX_PHRASE_CHARS: usize = 64;
+
+// The phrase the user chose, shown on the login window so a look-alike window can't fake it
+#[command]
+pub fn get_verification_phrase(app_handle: AppHandle) -> Result<Option<String>, CommandError> {
+    let store = StoreBuilder::new(&app_handle, verification_store_path(&app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(store
+        .get("phrase")
+        .and_then(|value| value.as_str().map(|phrase| phrase.to_string())))
+}
+
+// An empty phrase removes it
+#[command(unlocked)]
+pub fn set_verification_phrase(app_handle: AppHandle, phrase: String) -> Result<(), CommandError> {
+    let phrase = phrase.trim();
+    if phrase.chars().count() > MAX_PHRASE_CHARS {
+        return Err(format!("The verification phrase can be at most {} characters", MAX_PHRASE_CHARS).into());
+    }
+
+    let store = StoreBuilder::new(&app_handle, verification_store_path(&app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    if phrase.is_empty() {
+        store.delete("phrase");
+        log::info!("Verification phrase removed");
+    } else {
+        store.set("phrase", json!(phrase));
+        log::info!("Verification phrase updated");
+    }
+    store.save().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn verification_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::local_data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(VERIFICATION_STORE_FILE))
+}
@@ -0,0 +1,71 @@
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::auth::{client_bearer_token, prefetch, JWKS_PATH};
+use crate::auth_client::AuthClient;
+use crate::cache::{CacheState, DISCOVERY_KEY, JWKS_KEY};
+use crate::config::ConfigState;
+use crate::demo::DemoState;
+use crate::tenant::warm_branding;
+
+const DISCOVERY_TTL: Duration = Duration::from_secs(24 * 3600);
+
+// Runs once after startup so the first login doesn't pay for DNS, TLS, the client token
+// and tenant metadata. Disabled with `network.preconnect: false`.
+pub async fn warm_up(app_handle: AppHandle) {
+    if app_handle.state::<DemoState>().enabled {
+        return;
+    }
+    let preconnect = app_handle
+        .state::<ConfigState>()
+        .0
+        .read(|config| config.network.preconnect);
+    if !preconnect {
+        log::debug!("Startup warm-up disabled by network.preconnect");
+        return;
+    }
+
+    let started = Instant::now();
+    let (discovery, jwks, branding) = tokio::join!(
+        warm_discovery(&app_handle),
+        warm_jwks(&app_handle),
+        warm_branding(&app_handle),
+    );
+    for (step, result) in [("discovery", discovery), ("JWKS", jwks), ("branding", branding)] {
+        if let Err(e) = result {
+            log::debug!("Warm-up of {} failed: {}", step, e);
+        }
+    }
+    log::info!("Startup warm-up finished in {} ms", started.elapsed().as_millis());
+}
+
+// The discovery document is public, so this is also the first connection to the tenant host
+async fn warm_discovery(app_handle: &AppHandle) -> Result<(), String> {
+    discovery(app_handle).await.map(|_| ())
+}
+
+// The tenant's OpenID discovery document, from the cache while it is fresh
+pub(crate) async fn discovery(app_handle: &AppHandle) -> Result<Value, String> {
+    let cache = app_handle.state::<CacheState>();
+    if let Some(discovery) = cache.get::<Value>(DISCOVERY_KEY) {
+        return Ok(discovery);
+    }
+    let client = app_handle.state::<AuthClient>();
+    let url = client.url("/.well-known/openid-configuration");
+    let response = client.http().get(&url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to get {}: {}", url, response.status()));
+    }
+    let discovery: Value = response.json().await.map_err(|e| e.to_string())?;
+    cache.insert(DISCOVERY_KEY, &discovery, DISCOVERY_TTL);
+    Ok(discovery)
+}
+
+// Also leaves a fresh client credentials token in the token cache
+async fn warm_jwks(app_handle: &AppHandle) -> Result<(), String> {
+    let client = app_handle.state::<AuthClient>();
+    let bearer_token = client_bearer_token(&client).await?;
+    prefetch(app_handle, &client, JWKS_KEY, &bearer_token, JWKS_PATH).await;
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthResponse};
+use crate::auth_client::AuthClient;
+use crate::config::AuthFactor;
+use crate::demo::DemoState;
+use crate::error::{AuthError, CommandError};
+use crate::lifecycle;
+use crate::middleware::command;
+use crate::session::AuthSessionState;
+
+// What the webview passes to `navigator.credentials.get({ publicKey })`; binary fields are base64url
+#[derive(Debug, Clone, Serialize)]
+pub struct WebAuthnChallenge {
+    // The request state `submit_webauthn_assertion` continues from
+    pub request_state: String,
+    pub challenge: String,
+    pub rp_id: Option<String>,
+    // Credential ids of the keys registered to the account; empty lets the browser offer any
+    pub allow_credentials: Vec<String>,
+    pub timeout_ms: Option<u64>,
+    pub user_verification: Option<String>,
+}
+
+// The `PublicKeyCredential` returned by `navigator.credentials.get()`, base64url encoded
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebAuthnAssertion {
+    pub credential_id: String,
+    pub authenticator_data: String,
+    pub client_data_json: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+// Selects the security key factor and returns the challenge for the webview to sign
+#[command]
+pub async fn start_webauthn(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+) -> Result<WebAuthnChallenge, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let challenge = if demo.enabled {
+        demo.webauthn_challenge(&request_state).map_err(AuthError::from)?
+    } else {
+        idcs_webauthn_challenge(&auth_client, &request_state).await?
+    };
+    auth::factor_started(&app_handle, AuthFactor::FidoAuthenticator, &challenge.request_state);
+    Ok(challenge)
+}
+
+// Completes the factor with the browser's assertion; on success, finish with `complete_auth`
+#[command]
+pub async fn submit_webauthn_assertion(
+    app_handle: AppHandle,
+    auth_client: State<'_, AuthClient>,
+    demo: State<'_, DemoState>,
+    assertion: WebAuthnAssertion,
+) -> Result<AuthResponse, CommandError> {
+    let request_state = app_handle.state::<AuthSessionState>().request_state()?;
+    let result = if demo.enabled {
+        demo.submit_webauthn(&request_state, &assertion).map_err(AuthError::from)
+    } else {
+        idcs_submit_assertion(&auth_client, &request_state, &assertion).await
+    };
+    let mut response = result.inspect_err(|e| lifecycle::factor_failed(&app_handle, e))?;
+    auth::factor_submitted(&app_handle, AuthFactor::FidoAuthenticator, &response);
+
+    if crate::clipboard::tokens_protected(&app_handle) {
+        response.authn_token = response.authn_token.as_deref().map(crate::clipboard::mask_token);
+    }
+    Ok(response)
+}
+
+async fn idcs_webauthn_challenge(client: &AuthClient, request_state: &str) -> Result<WebAuthnChallenge, AuthError> {
+    log::debug!("Requesting a security key challenge");
+    let step = auth::authenticate_raw(client, json!({
+        "op": "credSubmit",
+        "authFactor": AuthFactor::FidoAuthenticator.idcs_name(),
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Failed to select factor"))?;
+    Ok(parse_challenge(&step)?)
+}
+
+// IDCS puts the request options under the factor's name, next to the new request state
+fn parse_challenge(step: &Value) -> Result<WebAuthnChallenge, String> {
+    let options = &step[AuthFactor::FidoAuthenticator.idcs_name()];
+    let challenge = options["challenge"]
+        .as_str()
+        .ok_or("IDCS did not return a security key challenge")?;
+    let request_state = step["requestState"].as_str().ok_or("IDCS did not return a request state")?;
+    // Either plain ids or { "id": ..., "type": "public-key" } descriptors
+    let allow_credentials = options["allowCredentials"]
+        .as_array()
+        .map(|credentials| {
+            credentials
+                .iter()
+                .filter_map(|credential| credential.as_str().or_else(|| credential["id"].as_str()))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(WebAuthnChallenge {
+        request_state: request_state.to_string(),
+        challenge: challenge.to_string(),
+        rp_id: options["rpId"].as_str().map(str::to_string),
+        allow_credentials,
+        timeout_ms: options["timeout"].as_u64(),
+        user_verification: options["userVerification"].as_str().map(str::to_string),
+    })
+}
+
+async fn idcs_submit_assertion(
+    client: &AuthClient,
+    request_state: &str,
+    assertion: &WebAuthnAssertion,
+) -> Result<AuthResponse, AuthError> {
+    log::debug!("Submitting security key assertion");
+    let step = auth::authenticate_raw(client, json!({
+        "op": "credSubmit",
+        "authFactor": AuthFactor::FidoAuthenticator.idcs_name(),
+        "credentials": {
+            "credentialId": assertion.credential_id,
+            "authenticatorData": assertion.authenticator_data,
+            "clientDataJson": assertion.client_data_json,
+            "signature": assertion.signature,
+            "userHandle": assertion.user_handle
+        },
+        "requestState": request_state
+    }))
+    .await
+    .map_err(|e| e.context("Security key rejected"))?;
+
+    let mut response: AuthResponse =
+        serde_json::from_value(step).map_err(|e| format!("Failed to parse response: {}", e))?;
+    crate::i18n::localize_causes(&mut response.cause);
+    Ok(response)
+}
@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::window::Monitor;
+use tauri::{
+    AppHandle, LogicalSize, Manager, PhysicalPosition, PhysicalSize, State, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
+use tauri_plugin_store::StoreBuilder;
+
+use crate::config::{ConfigState, ThemePreference};
+use crate::error::CommandError;
+use crate::middleware::command;
+
+// Small fixed-size token widget (expiry countdown and copy button)
+const COMPACT_WIDTH: f64 = 320.0;
+const COMPACT_HEIGHT: f64 = 140.0;
+
+// Size of the main window in tauri.conf.json, for when it has to be rebuilt
+const MAIN_WIDTH: f64 = 800.0;
+const MAIN_HEIGHT: f64 = 600.0;
+
+const PLACEMENT_STORE_FILE: &str = "window-state.json";
+
+// Window size before compact mode was entered, restored when leaving it
+#[derive(Default)]
+pub struct CompactState(pub Mutex<Option<PhysicalSize<u32>>>);
+
+// Last position of the main window, in physical pixels, and the monitor it was on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowPlacement {
+    pub monitor: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[command(unlocked)]
+pub fn set_always_on_top(app_handle: AppHandle, enabled: bool) -> Result<(), CommandError> {
+    apply_always_on_top(&app_handle, enabled)?;
+    Ok(())
+}
+
+pub fn apply_always_on_top(app_handle: &AppHandle, enabled: bool) -> Result<(), String> {
+    main_window(app_handle)?
+        .set_always_on_top(enabled)
+        .map_err(|e| e.to_string())?;
+
+    let config_state = app_handle.state::<ConfigState>();
+    let mut config = config_state.0.lock();
+    config.set_always_on_top(app_handle, enabled).map_err(|e| e.to_string())?;
+    drop(config);
+
+    crate::tray::sync_menu(app_handle);
+    Ok(())
+}
+
+#[command(unlocked)]
+pub fn set_compact_mode(app_handle: AppHandle, enabled: bool) -> Result<(), CommandError> {
+    apply_compact_mode(&app_handle, enabled)?;
+    Ok(())
+}
+
+#[command]
+pub fn is_compact_mode(compact_state: State<CompactState>) -> Result<bool, CommandError> {
+    Ok(compact_state.0.lock().map_err(|e| e.to_string())?.is_some())
+}
+
+pub fn toggle_compact_mode(app_handle: &AppHandle) -> Result<(), String> {
+    let enabled = app_handle
+        .state::<CompactState>()
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    apply_compact_mode(app_handle, !enabled)
+}
+
+pub fn apply_compact_mode(app_handle: &AppHandle, enabled: bool) -> Result<(), String> {
+    let window = main_window(app_handle)?;
+    let compact_state = app_handle.state::<CompactState>();
+    let mut previous_size = compact_state.0.lock().map_err(|e| e.to_string())?;
+    if enabled == previous_size.is_some() {
+        return Ok(());
+    }
+
+    if enabled {
+        *previous_size = Some(window.inner_size().map_err(|e| e.to_string())?);
+        window.set_resizable(false).map_err(|e| e.to_string())?;
+        window
+            .set_size(LogicalSize::new(COMPACT_WIDTH, COMPACT_HEIGHT))
+            .map_err(|e| e.to_string())?;
+        window.set_always_on_top(true).map_err(|e| e.to_string())?;
+    } else {
+        if let Some(size) = previous_size.take() {
+            window.set_size(size).map_err(|e| e.to_string())?;
+        }
+        window.set_resizable(true).map_err(|e| e.to_string())?;
+        let always_on_top = app_handle
+            .state::<ConfigState>()
+            .0
+            .read(|config| config.ui.always_on_top);
+        window.set_always_on_top(always_on_top).map_err(|e| e.to_string())?;
+    }
+    drop(previous_size);
+
+    log::info!("Compact mode {}", if enabled { "enabled" } else { "disabled" });
+    if enabled {
+        crate::analytics::track(app_handle, "compact_mode");
+    }
+    crate::bus::emit(app_handle, "compact-mode-changed", enabled);
+    crate::tray::sync_menu(app_handle);
+    Ok(())
+}
+
+pub fn main_window(app_handle: &AppHandle) -> Result<WebviewWindow, String> {
+    app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())
+}
+
+pub fn apply_theme(app_handle: &AppHandle, theme: ThemePreference) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.set_theme(theme.to_window_theme()) {
+            log::warn!("Failed to apply theme {}: {}", theme, e);
+        }
+    }
+}
+
+// Everything the main window gets beyond tauri.conf.json: branding, theme, placement, event handlers and menu.
+// Run for the window created at startup and again for one rebuilt by `recreate_main_window`.
+pub fn prepare_main_window(app_handle: &AppHandle) -> Result<(), String> {
+    let window = main_window(app_handle)?;
+    let (theme, always_on_top, block_screen_capture) = {
+        let config_state = app_handle.state::<ConfigState>();
+        let config = config_state.0.lock();
+        (config.ui.theme, config.ui.always_on_top, config.platform.block_screen_capture)
+    };
+
+    if let Err(e) = crate::branding::apply(app_handle) {
+        log::warn!("Failed to apply branding: {}", e);
+    }
+    apply_theme(app_handle, theme);
+    if crate::kiosk::is_enabled(app_handle) {
+        if let Err(e) = crate::kiosk::apply_window(app_handle) {
+            log::warn!("Failed to make the kiosk window fullscreen: {}", e);
+        }
+    } else if let Err(e) = restore_placement(app_handle) {
+        log::warn!("Failed to restore window placement: {}", e);
+    }
+    let handle = app_handle.clone();
+    window.on_window_event(move |event| match event {
+        tauri::WindowEvent::CloseRequested { api, .. } => {
+            if let Err(e) = save_placement(&handle) {
+                log::warn!("Failed to save window placement: {}", e);
+            }
+            crate::guest::on_close_requested(&handle, api);
+        }
+        tauri::WindowEvent::DragDrop(drag_drop) => crate::import::handle_drag_drop(&handle, drag_drop),
+        _ => {}
+    });
+    if always_on_top {
+        window.set_always_on_top(true).map_err(|e| e.to_string())?;
+    }
+    if block_screen_capture {
+        crate::platform::apply_screen_capture_blocking(app_handle);
+    }
+    crate::menu::install(app_handle)
+}
+
+// Throws away the main window and builds a new one in its place, for a webview whose renderer died. The session
+// lives in the backend, so the new page picks up where the old one was.
+pub fn recreate_main_window(app_handle: &AppHandle) -> Result<(), String> {
+    if let Ok(window) = main_window(app_handle) {
+        if let Err(e) = save_placement(app_handle) {
+            log::warn!("Failed to save window placement: {}", e);
+        }
+        window.destroy().map_err(|e| e.to_string())?;
+    }
+    // The new window opens at its regular size
+    if let Ok(mut compact_size) = app_handle.state::<CompactState>().0.lock() {
+        *compact_size = None;
+    }
+
+    WebviewWindowBuilder::new(app_handle, "main", WebviewUrl::App(PathBuf::from("index.html")))
+        .title(crate::branding::APP_NAME)
+        .inner_size(MAIN_WIDTH, MAIN_HEIGHT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    prepare_main_window(app_handle)?;
+    crate::tray::sync_menu(app_handle);
+    log::info!("Main window recreated");
+    Ok(())
+}
+
+// Centers the main window on the monitor under the cursor and brings it to the front
+#[command]
+pub fn move_to_cursor_monitor(app_handle: AppHandle) -> Result<(), CommandError> {
+    let window = main_window(&app_handle)?;
+    let cursor = window.cursor_position().map_err(|e| e.to_string())?;
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+
+    if let Some(monitor) = nearest_monitor(&monitors, cursor.x as i32, cursor.y as i32) {
+        let size = window.outer_size().map_err(|e| e.to_string())?;
+        let (x, y, width, height) = monitor_rect(monitor);
+        let (x, y) = clamp_to_monitor(
+            monitor,
+            x + (width - size.width as i32) / 2,
+            y + (height - size.height as i32) / 2,
+            size.width,
+            size.height,
+        );
+        window
+            .set_position(PhysicalPosition::new(x, y))
+            .map_err(|e| e.to_string())?;
+    }
+
+    window.unminimize().map_err(|e| e.to_string())?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Not in kiosk mode, whose fullscreen window would replace the placement used otherwise
+pub fn save_placement(app_handle: &AppHandle) -> Result<(), String> {
+    if crate::kiosk::is_enabled(app_handle) {
+        return Ok(());
+    }
+    let window = main_window(app_handle)?;
+    let position = window.outer_position().map_err(|e| e.to_string())?;
+    // Remember the regular size rather than the compact preset
+    let compact_size = *app_handle
+        .state::<CompactState>()
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?;
+    let size = match compact_size {
+        Some(size) => size,
+        None => window.inner_size().map_err(|e| e.to_string())?,
+    };
+    let monitor = window
+        .current_monitor()
+        .map_err(|e| e.to_string())?
+        .and_then(|monitor| monitor.name().cloned());
+
+    let placement = WindowPlacement {
+        monitor,
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let store = StoreBuilder::new(app_handle, placement_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    store.set("placement", serde_json::to_value(&placement).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+// Puts the main window back where it was; if that monitor is gone, onto the nearest one still connected
+pub fn restore_placement(app_handle: &AppHandle) -> Result<(), String> {
+    let store = StoreBuilder::new(app_handle, placement_store_path(app_handle)?)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let Some(placement) = store
+        .get("placement")
+        .and_then(|value| serde_json::from_value::<WindowPlacement>(value).ok())
+    else {
+        return Ok(());
+    };
+
+    let window = main_window(app_handle)?;
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let saved_monitor = monitors
+        .iter()
+        .find(|monitor| placement.monitor.is_some() && monitor.name() == placement.monitor.as_ref());
+    let Some(monitor) = saved_monitor.or_else(|| nearest_monitor(&monitors, placement.x, placement.y)) else {
+        return Ok(());
+    };
+
+    let (_, _, monitor_width, monitor_height) = monitor_rect(monitor);
+    let width = placement.width.min(monitor_width as u32);
+    let height = placement.height.min(monitor_height as u32);
+    let (x, y) = clamp_to_monitor(monitor, placement.x, placement.y, width, height);
+
+    log::debug!("Restoring window placement on monitor {:?}", monitor.name());
+    window
+        .set_size(PhysicalSize::new(width, height))
+        .map_err(|e| e.to_string())?;
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| e.to_string())
+}
+
+fn monitor_rect(monitor: &Monitor) -> (i32, i32, i32, i32) {
+    let position = monitor.position();
+    let size = monitor.size();
+    (position.x, position.y, size.width as i32, size.height as i32)
+}
+
+// Monitor containing the point, otherwise the one closest to it
+fn nearest_monitor(monitors: &[Monitor], x: i32, y: i32) -> Option<&Monitor> {
+    monitors.iter().min_by_key(|monitor| {
+        let (left, top, width, height) = monitor_rect(monitor);
+        let dx = (left - x).max(0).max(x - (left + width - 1)) as i64;
+        let dy = (top - y).max(0).max(y - (top + height - 1)) as i64;
+        dx * dx + dy * dy
+    })
+}
+
+fn clamp_to_monitor(monitor: &Monitor, x: i32, y: i32, width: u32, height: u32) -> (i32, i32) {
+    let (left, top, monitor_width, monitor_height) = monitor_rect(monitor);
+    let max_x = left + (monitor_width - width as i32).max(0);
+    let max_y = top + (monitor_height - height as i32).max(0);
+    (x.clamp(left, max_x), y.clamp(top, max_y))
+}
+
+fn placement_store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::data_dir(app_handle)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(PLACEMENT_STORE_FILE))
+}